@@ -14,29 +14,25 @@ fn configure_criterion() -> Criterion {
 use pulz_ecs::prelude::*;
 
 #[derive(Copy, Clone, Component, bevy_ecs::component::Component)]
-struct A(usize);
+struct A;
 #[derive(Copy, Clone, Component, bevy_ecs::component::Component)]
-struct B(usize);
+struct B;
 #[derive(Copy, Clone, Component, bevy_ecs::component::Component)]
-struct C(usize);
+struct C;
 #[derive(Copy, Clone, Component, bevy_ecs::component::Component)]
-struct D(usize);
+struct D;
 #[derive(Copy, Clone, Component, bevy_ecs::component::Component)]
-struct E(usize);
+struct E;
 #[derive(Copy, Clone, Component, bevy_ecs::component::Component)]
-struct F(usize);
+struct F;
 #[derive(Copy, Clone, Component, bevy_ecs::component::Component)]
-struct G(usize);
+struct G;
 #[derive(Copy, Clone, Component, bevy_ecs::component::Component)]
-struct H(usize);
+struct H;
 #[derive(Copy, Clone, Component, bevy_ecs::component::Component)]
-struct I(usize);
+struct I;
 #[derive(Copy, Clone, Component, bevy_ecs::component::Component)]
-struct J(usize);
-#[derive(Copy, Clone, Component, bevy_ecs::component::Component)]
-struct K(usize);
-#[derive(Copy, Clone, Component, bevy_ecs::component::Component)]
-struct L(usize);
+struct J;
 
 #[derive(Copy, Clone, Component, bevy_ecs::component::Component)]
 struct X<A: Send + Sync + 'static, B: Send + Sync + 'static>(A, B);
@@ -53,26 +49,26 @@ pub fn world_spawn(c: &mut Criterion) {
                 let mut world = Resources::new();
                 let mut world = world.world_mut();
                 let mut entities = Vec::new();
-                for i in 0..entity_count {
-                    entities.push(world.spawn().insert(A(i)).insert(B(i)).insert(C(i)).id());
+                for _ in 0..entity_count {
+                    entities.push(world.spawn().insert(A).insert(B).insert(C).id());
                 }
-                for (i, entity) in entities.iter().enumerate() {
+                for entity in &entities {
                     world
                         .entity_mut(*entity)
                         .unwrap()
-                        .insert(C(i))
-                        .insert(D(i))
-                        .insert(E(i));
+                        .insert(C)
+                        .insert(D)
+                        .insert(E);
                 }
-                for (i, entity) in entities.iter().enumerate() {
+                for entity in &entities {
                     world
                         .entity_mut(*entity)
                         .unwrap()
                         .remove::<A>()
                         .remove::<C>()
                         .remove::<E>()
-                        .insert(F(i))
-                        .insert(G(i));
+                        .insert(F)
+                        .insert(G);
                 }
                 for entity in entities {
                     world.despawn(entity);
@@ -85,21 +81,17 @@ pub fn world_spawn(c: &mut Criterion) {
             bencher.iter(|| {
                 let mut world = World::new();
                 let mut entities = Vec::new();
-                for i in 0..entity_count {
-                    entities.push(world.spawn(()).insert(A(i)).insert(B(i)).insert(C(i)).id());
+                for _ in 0..entity_count {
+                    entities.push(world.spawn(()).insert(A).insert(B).insert(C).id());
                 }
-                for (i, entity) in entities.iter().enumerate() {
-                    world
-                        .entity_mut(*entity)
-                        .insert(C(i))
-                        .insert(D(i))
-                        .insert(E(i));
+                for entity in &entities {
+                    world.entity_mut(*entity).insert(C).insert(D).insert(E);
                 }
-                for (i, entity) in entities.iter().enumerate() {
+                for entity in &entities {
                     world.entity_mut(*entity).remove::<A>();
                     world.entity_mut(*entity).remove::<C>();
                     world.entity_mut(*entity).remove::<E>();
-                    world.entity_mut(*entity).insert(F(i)).insert(G(i));
+                    world.entity_mut(*entity).insert(F).insert(G);
                 }
                 for entity in entities {
                     world.despawn(entity);
@@ -125,20 +117,20 @@ pub fn world_spawn2(c: &mut Criterion) {
                 world.init::<F>();
                 world.init::<G>();
                 let mut entities = Vec::new();
-                for i in 0..entity_count {
-                    entities.push(world.spawn().insert(A(i)).insert(B(i)).insert(C(i)).id());
+                for _ in 0..entity_count {
+                    entities.push(world.spawn().insert(A).insert(B).insert(C).id());
                 }
                 for (i, entity) in entities.iter().enumerate() {
                     let mut e = world.entity_mut(*entity).unwrap();
                     match i % 4 {
                         1 => {
-                            e.insert(D(i));
+                            e.insert(D);
                         }
                         2 => {
-                            e.insert(E(i));
+                            e.insert(E);
                         }
                         3 => {
-                            e.insert(F(i));
+                            e.insert(F);
                         }
                         _ => {}
                     }
@@ -148,15 +140,15 @@ pub fn world_spawn2(c: &mut Criterion) {
                     match i % 4 {
                         1 => {
                             e.remove::<D>();
-                            e.insert(E(i));
+                            e.insert(E);
                         }
                         2 => {
                             e.remove::<E>();
-                            e.insert(F(i));
+                            e.insert(F);
                         }
                         3 => {
                             e.remove::<F>();
-                            e.insert(G(i));
+                            e.insert(G);
                         }
                         _ => {}
                     }
@@ -172,20 +164,20 @@ pub fn world_spawn2(c: &mut Criterion) {
             bencher.iter(|| {
                 let mut world = World::new();
                 let mut entities = Vec::new();
-                for i in 0..entity_count {
-                    entities.push(world.spawn(()).insert(A(i)).insert(B(i)).insert(C(i)).id());
+                for _ in 0..entity_count {
+                    entities.push(world.spawn(()).insert(A).insert(B).insert(C).id());
                 }
                 for (i, entity) in entities.iter().enumerate() {
                     let mut e = world.entity_mut(*entity);
                     match i % 4 {
                         1 => {
-                            e.insert(D(i));
+                            e.insert(D);
                         }
                         2 => {
-                            e.insert(E(i));
+                            e.insert(E);
                         }
                         3 => {
-                            e.insert(F(i));
+                            e.insert(F);
                         }
                         _ => {}
                     }
@@ -195,15 +187,15 @@ pub fn world_spawn2(c: &mut Criterion) {
                     match i % 4 {
                         1 => {
                             e.remove::<D>();
-                            e.insert(E(i));
+                            e.insert(E);
                         }
                         2 => {
                             e.remove::<E>();
-                            e.insert(F(i));
+                            e.insert(F);
                         }
                         3 => {
                             e.remove::<F>();
-                            e.insert(G(i));
+                            e.insert(G);
                         }
                         _ => {}
                     }
@@ -222,64 +214,64 @@ fn pulz_insert_many_components2<T>(e: &mut EntityMut, value: T)
 where
     T: Send + Sync + Copy + 'static,
 {
-    e.insert(X(value, A(1)));
-    e.insert(X(value, B(1)));
-    e.insert(X(value, C(1)));
-    e.insert(X(value, D(1)));
-    e.insert(X(value, E(1)));
-    e.insert(X(value, F(1)));
-    e.insert(X(value, G(1)));
-    e.insert(X(value, H(1)));
-    e.insert(X(value, I(1)));
-    e.insert(X(value, J(1)));
+    e.insert(X(value, A));
+    e.insert(X(value, B));
+    e.insert(X(value, C));
+    e.insert(X(value, D));
+    e.insert(X(value, E));
+    e.insert(X(value, F));
+    e.insert(X(value, G));
+    e.insert(X(value, H));
+    e.insert(X(value, I));
+    e.insert(X(value, J));
 }
 
 fn pulz_insert_many_components<T>(e: &mut EntityMut, value: T)
 where
     T: Send + Sync + Copy + 'static,
 {
-    pulz_insert_many_components2(e, (value, A(2)));
-    pulz_insert_many_components2(e, (value, B(2)));
-    pulz_insert_many_components2(e, (value, C(2)));
-    pulz_insert_many_components2(e, (value, D(2)));
-    pulz_insert_many_components2(e, (value, E(2)));
-    pulz_insert_many_components2(e, (value, F(2)));
-    pulz_insert_many_components2(e, (value, G(2)));
-    pulz_insert_many_components2(e, (value, H(2)));
-    pulz_insert_many_components2(e, (value, I(2)));
-    pulz_insert_many_components2(e, (value, J(2)));
+    pulz_insert_many_components2(e, (value, A));
+    pulz_insert_many_components2(e, (value, B));
+    pulz_insert_many_components2(e, (value, C));
+    pulz_insert_many_components2(e, (value, D));
+    pulz_insert_many_components2(e, (value, E));
+    pulz_insert_many_components2(e, (value, F));
+    pulz_insert_many_components2(e, (value, G));
+    pulz_insert_many_components2(e, (value, H));
+    pulz_insert_many_components2(e, (value, I));
+    pulz_insert_many_components2(e, (value, J));
 }
 
 fn bevy_insert_many_components2<T>(e: &mut bevy_ecs::world::EntityWorldMut, value: T)
 where
     T: bevy_ecs::component::Component + Copy,
 {
-    e.insert(X(value, A(1)));
-    e.insert(X(value, B(1)));
-    e.insert(X(value, C(1)));
-    e.insert(X(value, D(1)));
-    e.insert(X(value, E(1)));
-    e.insert(X(value, F(1)));
-    e.insert(X(value, G(1)));
-    e.insert(X(value, H(1)));
-    e.insert(X(value, I(1)));
-    e.insert(X(value, J(1)));
+    e.insert(X(value, A));
+    e.insert(X(value, B));
+    e.insert(X(value, C));
+    e.insert(X(value, D));
+    e.insert(X(value, E));
+    e.insert(X(value, F));
+    e.insert(X(value, G));
+    e.insert(X(value, H));
+    e.insert(X(value, I));
+    e.insert(X(value, J));
 }
 
 fn bevy_insert_many_components<T>(e: &mut bevy_ecs::world::EntityWorldMut, value: T)
 where
     T: bevy_ecs::component::Component + Copy,
 {
-    bevy_insert_many_components2(e, X(value, A(2)));
-    bevy_insert_many_components2(e, X(value, B(2)));
-    bevy_insert_many_components2(e, X(value, C(2)));
-    bevy_insert_many_components2(e, X(value, D(2)));
-    bevy_insert_many_components2(e, X(value, E(2)));
-    bevy_insert_many_components2(e, X(value, F(2)));
-    bevy_insert_many_components2(e, X(value, G(2)));
-    bevy_insert_many_components2(e, X(value, H(2)));
-    bevy_insert_many_components2(e, X(value, I(2)));
-    bevy_insert_many_components2(e, X(value, J(2)));
+    bevy_insert_many_components2(e, X(value, A));
+    bevy_insert_many_components2(e, X(value, B));
+    bevy_insert_many_components2(e, X(value, C));
+    bevy_insert_many_components2(e, X(value, D));
+    bevy_insert_many_components2(e, X(value, E));
+    bevy_insert_many_components2(e, X(value, F));
+    bevy_insert_many_components2(e, X(value, G));
+    bevy_insert_many_components2(e, X(value, H));
+    bevy_insert_many_components2(e, X(value, I));
+    bevy_insert_many_components2(e, X(value, J));
 }
 
 /// Span a number of entities and change their sparse-component configuration
@@ -292,38 +284,38 @@ pub fn world_many_components(c: &mut Criterion) {
                 let mut world = Resources::new();
                 let mut world = world.world_mut();
                 let mut entities = Vec::new();
-                for i in 0..1000 {
+                for _ in 0..1000 {
                     let mut e = world.spawn();
-                    pulz_insert_many_components(&mut e, A(i));
+                    pulz_insert_many_components(&mut e, A);
                     entities.push(e.id());
                 }
                 for (i, entity) in entities.iter().enumerate() {
                     let mut e = world.entity_mut(*entity).unwrap();
                     match i % 2 {
                         1 => {
-                            pulz_insert_many_components(&mut e, B(i));
+                            pulz_insert_many_components(&mut e, B);
                         }
                         2 => {
-                            pulz_insert_many_components(&mut e, C(i));
+                            pulz_insert_many_components(&mut e, C);
                         }
                         _ => {}
                     }
                 }
                 if component_count > 100 {
-                    for (i, entity) in entities.iter().enumerate() {
+                    for entity in &entities {
                         let mut e = world.entity_mut(*entity).unwrap();
-                        pulz_insert_many_components(&mut e, D(i));
-                        // pulz_insert_many_components(&mut e, E(i));
-                        // pulz_insert_many_components(&mut e, F(i));
-                        // pulz_insert_many_components(&mut e, G(i));
+                        pulz_insert_many_components(&mut e, D);
+                        // pulz_insert_many_components(&mut e, E);
+                        // pulz_insert_many_components(&mut e, F);
+                        // pulz_insert_many_components(&mut e, G);
                     }
                 }
                 if component_count > 200 {
-                    for (i, entity) in entities.iter().enumerate() {
+                    for entity in &entities {
                         let mut e = world.entity_mut(*entity).unwrap();
-                        pulz_insert_many_components(&mut e, H(i));
-                        // pulz_insert_many_components(&mut e, I(i));
-                        // pulz_insert_many_components(&mut e, J(i));
+                        pulz_insert_many_components(&mut e, H);
+                        // pulz_insert_many_components(&mut e, I);
+                        // pulz_insert_many_components(&mut e, J);
                         // pulz_insert_many_components(&mut e, K(i));
                         // pulz_insert_many_components(&mut e, L(i));
                     }
@@ -339,38 +331,38 @@ pub fn world_many_components(c: &mut Criterion) {
             bencher.iter(|| {
                 let mut world = World::new();
                 let mut entities = Vec::new();
-                for i in 0..1000 {
+                for _ in 0..1000 {
                     let mut e = world.spawn(());
-                    bevy_insert_many_components(&mut e, A(i));
+                    bevy_insert_many_components(&mut e, A);
                     entities.push(e.id());
                 }
                 for (i, entity) in entities.iter().enumerate() {
                     let mut e = world.entity_mut(*entity);
                     match i % 2 {
                         1 => {
-                            bevy_insert_many_components(&mut e, B(i));
+                            bevy_insert_many_components(&mut e, B);
                         }
                         2 => {
-                            bevy_insert_many_components(&mut e, C(i));
+                            bevy_insert_many_components(&mut e, C);
                         }
                         _ => {}
                     }
                 }
                 if component_count > 100 {
-                    for (i, entity) in entities.iter().enumerate() {
+                    for entity in &entities {
                         let mut e = world.entity_mut(*entity);
-                        bevy_insert_many_components(&mut e, D(i));
-                        // bevy_insert_many_components(&mut e, E(i));
-                        // bevy_insert_many_components(&mut e, F(i));
-                        // bevy_insert_many_components(&mut e, G(i));
+                        bevy_insert_many_components(&mut e, D);
+                        // bevy_insert_many_components(&mut e, E);
+                        // bevy_insert_many_components(&mut e, F);
+                        // bevy_insert_many_components(&mut e, G);
                     }
                 }
                 if component_count > 200 {
-                    for (i, entity) in entities.iter().enumerate() {
+                    for entity in &entities {
                         let mut e = world.entity_mut(*entity);
-                        bevy_insert_many_components(&mut e, H(i));
-                        // bevy_insert_many_components(&mut e, I(i));
-                        // bevy_insert_many_components(&mut e, J(i));
+                        bevy_insert_many_components(&mut e, H);
+                        // bevy_insert_many_components(&mut e, I);
+                        // bevy_insert_many_components(&mut e, J);
                         // bevy_insert_many_components(&mut e, K(i));
                         // bevy_insert_many_components(&mut e, L(i));
                     }
@@ -0,0 +1,412 @@
+use core::mem::ManuallyDrop;
+
+use crate::{
+    get_disjoint_slots_mut, get_slot, get_slot_by_offset, get_slot_mut, get_slot_mut_by_offset,
+    remove_slot, Index, Iter, IterMut, Slot,
+};
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl<T> Sealed for &mut [crate::Slot<T>] {}
+    impl<T, const N: usize> Sealed for [crate::Slot<T>; N] {}
+}
+
+/// A storage of [`Slot`]s for a [`FixedArena`].
+///
+/// This trait is sealed: the arena relies on `slots()` and `slots_mut()`
+/// always returning the same slots, so it is only implemented for
+/// `&mut [Slot<T>]` and `[Slot<T>; N]`.
+pub trait SlotStorage: sealed::Sealed {
+    /// The type of the elements.
+    type Item;
+
+    /// Returns the slots of this storage.
+    fn slots(&self) -> &[Slot<Self::Item>];
+
+    /// Returns the slots of this storage.
+    fn slots_mut(&mut self) -> &mut [Slot<Self::Item>];
+}
+
+impl<T> SlotStorage for &mut [Slot<T>] {
+    type Item = T;
+    #[inline]
+    fn slots(&self) -> &[Slot<T>] {
+        self
+    }
+    #[inline]
+    fn slots_mut(&mut self) -> &mut [Slot<T>] {
+        self
+    }
+}
+
+impl<T, const N: usize> SlotStorage for [Slot<T>; N] {
+    type Item = T;
+    #[inline]
+    fn slots(&self) -> &[Slot<T>] {
+        self
+    }
+    #[inline]
+    fn slots_mut(&mut self) -> &mut [Slot<T>] {
+        self
+    }
+}
+
+/// An arena with a fixed capacity, that doesn't allocate.
+///
+/// The elements are stored in the provided [`SlotStorage`]: either a
+/// caller-provided slice ([`ArenaSlice`]) or an inline array
+/// ([`StaticArena`]). The arena uses the same [`Index`] and
+/// [`Generation`](crate::Generation) types as an [`Arena`](crate::Arena).
+///
+/// # Example
+///
+/// ```
+/// use pulz_arena::StaticArena;
+///
+/// let mut arena = StaticArena::<&str, 2>::new();
+/// let index = arena.try_insert("test").unwrap();
+/// assert!(arena.try_insert("foo").is_ok());
+/// assert_eq!(Err("bar"), arena.try_insert("bar"));
+/// assert_eq!(Some("test"), arena.remove(index));
+/// assert!(arena.try_insert("bar").is_ok());
+/// ```
+pub struct FixedArena<S: SlotStorage> {
+    slots: S,
+    next_free: u32,
+    // number of slots at the start of the storage, that were in use
+    used: u32,
+    len: usize,
+}
+
+/// A [`FixedArena`] backed by a caller-provided slice of [`Slot`]s.
+pub type ArenaSlice<'a, T> = FixedArena<&'a mut [Slot<T>]>;
+
+/// A [`FixedArena`] backed by an inline array of `N` [`Slot`]s.
+pub type StaticArena<T, const N: usize> = FixedArena<[Slot<T>; N]>;
+
+impl<'a, T> FixedArena<&'a mut [Slot<T>]> {
+    /// Constructs a new, empty arena, that stores its elements in `slots`.
+    ///
+    /// Elements that are still in `slots` (e.g. when a previous arena was
+    /// leaked) are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics when there are more than `u32::MAX - 1` slots.
+    #[inline]
+    pub fn new(slots: &'a mut [Slot<T>]) -> Self {
+        Self::from_slots(slots)
+    }
+}
+
+impl<T, const N: usize> FixedArena<[Slot<T>; N]> {
+    /// Constructs a new, empty arena with a capacity of `N` elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `N` is larger than `u32::MAX - 1`.
+    #[inline]
+    pub const fn new() -> Self {
+        assert!(N < u32::MAX as usize, "too many slots");
+        Self {
+            slots: [Slot::EMPTY; N],
+            next_free: u32::MAX,
+            used: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<T, const N: usize> Default for FixedArena<[Slot<T>; N]> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: SlotStorage> FixedArena<S> {
+    /// Constructs a new, empty arena, that stores its elements in the given
+    /// storage.
+    ///
+    /// Elements that are still in the storage are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics when there are more than `u32::MAX - 1` slots.
+    pub fn from_slots(slots: S) -> Self {
+        assert!(slots.slots().len() < u32::MAX as usize, "too many slots");
+        let mut arena = Self {
+            slots,
+            next_free: u32::MAX,
+            used: u32::MAX,
+            len: 0,
+        };
+        arena.clear();
+        arena
+    }
+
+    /// Returns the number of elements the arena can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.slots().len()
+    }
+
+    /// Returns the number of elements in this arena.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the arena contains no elements.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if no more elements can be inserted into the arena.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    /// Removes all elements from the arena.
+    ///
+    /// The generations of the slots are kept, so indices of the removed
+    /// elements don't resolve to new elements.
+    pub fn clear(&mut self) {
+        let used = self.capacity().min(self.used as usize);
+        for Slot(generation, entry) in &mut self.slots.slots_mut()[..used] {
+            if !generation.is_removed() {
+                generation.remove();
+                // SAFETY: slot was not marked as removed, so it is occupied
+                unsafe { ManuallyDrop::drop(&mut entry.occupied) };
+            }
+        }
+        self.next_free = u32::MAX;
+        self.used = 0;
+        self.len = 0;
+    }
+
+    fn take_next_free(&mut self) -> Option<(u32, &mut Slot<S::Item>)> {
+        let slots = self.slots.slots_mut();
+        let offset = if (self.next_free as usize) < slots.len() {
+            let offset = self.next_free;
+            // SAFETY: slot is in the free-list: so we can use `next_free`
+            self.next_free = unsafe { slots[offset as usize].1.next_free };
+            offset
+        } else if (self.used as usize) < slots.len() {
+            self.used += 1;
+            self.used - 1
+        } else {
+            return None;
+        };
+        Some((offset, &mut slots[offset as usize]))
+    }
+
+    /// Attempts to insert `value` into the arena at a free spot.
+    ///
+    /// If there is a free spot, the provided value is inserted into this spot
+    /// and the method returns the `Index` pointing to this spot.
+    ///
+    /// # Errors
+    ///
+    /// If the arena is full, it returns `Err(value)` with the provided
+    /// `value`, to give back ownership to the caller.
+    pub fn try_insert(&mut self, value: S::Item) -> Result<Index, S::Item> {
+        if let Some((offset, Slot(generation, entry))) = self.take_next_free() {
+            entry.occupied = ManuallyDrop::new(value);
+            generation.increment();
+            let index = Index(offset, *generation);
+            self.len += 1;
+            Ok(index)
+        } else {
+            Err(value)
+        }
+    }
+
+    /// Attempts to insert a new value returned by `create` into the arena at
+    /// a free spot.
+    ///
+    /// The `create` method is called with the `Index` of the spot, where the
+    /// created value will be inserted.
+    ///
+    /// # Errors
+    ///
+    /// If the arena is full, it returns `Err(create)` with the provided
+    /// `create` function, to give back ownership to the caller.
+    pub fn try_insert_with<F>(&mut self, create: F) -> Result<Index, F>
+    where
+        F: FnOnce(Index) -> S::Item,
+    {
+        if let Some((offset, Slot(generation, entry))) = self.take_next_free() {
+            let new_generation = generation.next();
+            let index = Index(offset, new_generation);
+            entry.occupied = ManuallyDrop::new(create(index));
+            *generation = new_generation;
+            self.len += 1;
+            Ok(index)
+        } else {
+            Err(create)
+        }
+    }
+
+    /// Removes the element at the given `index` from this arena.
+    ///
+    /// The method returns the old value, if it is still in the arena.
+    /// If it is not in the arena, then `None` is returned.
+    pub fn remove(&mut self, index: Index) -> Option<S::Item> {
+        let value = remove_slot(self.slots.slots_mut(), index, &mut self.next_free)?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Checks, if the element at the given `index` is still in the arena.
+    #[inline]
+    pub fn contains(&self, index: Index) -> bool {
+        self.get(index).is_some()
+    }
+
+    /// Get a shared reference to the element at the given `index`.
+    #[inline]
+    pub fn get(&self, index: Index) -> Option<&S::Item> {
+        get_slot(self.slots.slots(), index)
+    }
+
+    /// Get a exclusive reference to the element at the given `index`.
+    #[inline]
+    pub fn get_mut(&mut self, index: Index) -> Option<&mut S::Item> {
+        get_slot_mut(self.slots.slots_mut(), index)
+    }
+
+    /// Get exclusive references to the elements at multiple distinct `indices` at once.
+    ///
+    /// Returns `None`, when one of the `indices` is not in the arena, or when
+    /// an index is contained more than once.
+    #[inline]
+    pub fn get_disjoint_mut<const N: usize>(
+        &mut self,
+        indices: [Index; N],
+    ) -> Option<[&mut S::Item; N]> {
+        get_disjoint_slots_mut(self.slots.slots_mut(), indices)
+    }
+
+    /// Get a shared reference to the element at the given `offset`.
+    #[inline]
+    pub fn get_by_offset(&self, offset: u32) -> Option<&S::Item> {
+        get_slot_by_offset(self.slots.slots(), offset)
+    }
+
+    /// Get a exclusive reference to the element at the given `offset`.
+    #[inline]
+    pub fn get_mut_by_offset(&mut self, offset: u32) -> Option<&mut S::Item> {
+        get_slot_mut_by_offset(self.slots.slots_mut(), offset)
+    }
+
+    /// Creates an shared iterator over the elements of this arena.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, S::Item> {
+        Iter {
+            len: self.len,
+            inner: self.slots.slots()[..self.used as usize].iter().enumerate(),
+        }
+    }
+
+    /// Creates an exclusive iterator over the elements of this arena.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, S::Item> {
+        IterMut {
+            len: self.len,
+            inner: self.slots.slots_mut()[..self.used as usize]
+                .iter_mut()
+                .enumerate(),
+        }
+    }
+}
+
+impl<S: SlotStorage> core::ops::Index<Index> for FixedArena<S> {
+    type Output = S::Item;
+    #[inline]
+    fn index(&self, index: Index) -> &S::Item {
+        self.get(index).expect("invalid index")
+    }
+}
+
+impl<S: SlotStorage> core::ops::IndexMut<Index> for FixedArena<S> {
+    #[inline]
+    fn index_mut(&mut self, index: Index) -> &mut S::Item {
+        self.get_mut(index).expect("invalid index")
+    }
+}
+
+impl<S: SlotStorage> Drop for FixedArena<S> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::*;
+    use crate::Generation;
+
+    #[test]
+    fn test_static_arena() {
+        let mut arena = StaticArena::<usize, 3>::new();
+        assert_eq!(3, arena.capacity());
+        let index0 = arena.try_insert(0).unwrap();
+        let index1 = arena.try_insert_with(|i| i.offset() as usize).ok().unwrap();
+        let index2 = arena.try_insert(2).unwrap();
+        assert!(arena.is_full());
+        assert_eq!(Err(3), arena.try_insert(3));
+        assert_eq!(1, arena[index1]);
+
+        assert_eq!(Some(1), arena.remove(index1));
+        assert_eq!(None, arena.get(index1));
+        let index3 = arena.try_insert(3).unwrap();
+        assert_eq!(1, index3.offset());
+        assert_eq!(Generation::ONE.next(), index3.generation());
+
+        let [a, b] = arena.get_disjoint_mut([index0, index3]).unwrap();
+        core::mem::swap(a, b);
+        let mut iter = arena.iter();
+        assert_eq!(Some((index0, &3)), iter.next());
+        assert_eq!(Some((index3, &0)), iter.next());
+        assert_eq!(Some((index2, &2)), iter.next());
+        assert_eq!(None, iter.next());
+    }
+
+    #[test]
+    fn test_arena_slice() {
+        let drops = Cell::new(0);
+        struct Element<'c>(&'c Cell<usize>);
+        impl Drop for Element<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let mut slots: [Slot<Element<'_>>; 4] = Default::default();
+        let index = {
+            let mut arena = ArenaSlice::new(&mut slots);
+            let index = arena.try_insert(Element(&drops)).ok().unwrap();
+            arena.try_insert(Element(&drops)).ok().unwrap();
+            assert_eq!(2, arena.len());
+            assert_eq!(2, arena.iter_mut().count());
+            index
+        };
+        assert_eq!(2, drops.get());
+
+        // generations of the slice are kept
+        let mut arena = ArenaSlice::new(&mut slots);
+        assert!(arena.is_empty());
+        assert!(!arena.contains(index));
+        let new_index = arena.try_insert(Element(&drops)).ok().unwrap();
+        assert_eq!(index.offset(), new_index.offset());
+        assert_ne!(index, new_index);
+        arena.clear();
+        assert_eq!(3, drops.get());
+    }
+}
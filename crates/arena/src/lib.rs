@@ -24,19 +24,32 @@
 #![doc(html_logo_url = "https://raw.githubusercontent.com/HellButcher/pulz/master/docs/logo.png")]
 #![doc(html_no_source)]
 #![no_std]
-#![doc = include_str!("../README.md")]
+#![cfg_attr(feature = "alloc", doc = include_str!("../README.md"))]
+#![cfg_attr(
+    not(feature = "alloc"),
+    doc = "A _generational arena_ allocator with compact generational indices.\n\n\
+           Without the `alloc` feature, only the fixed-capacity arenas \
+           ([`StaticArena`] and [`ArenaSlice`]) are available."
+)]
 
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::{cmp::max, iter::FromIterator};
 use core::{
-    cmp::max,
-    iter::{FromIterator, FusedIterator},
+    iter::FusedIterator,
     mem::{replace, ManuallyDrop},
     num::NonZeroU32,
     ops::DerefMut,
 };
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
+mod fixed;
+
+pub use fixed::{ArenaSlice, FixedArena, SlotStorage, StaticArena};
+
 /// A generational index into an [`Arena`]
 ///
 /// You get a new `Index` for each element that you insert
@@ -45,10 +58,12 @@ extern crate alloc;
 /// # Example
 ///
 /// ```
+/// # #[cfg(feature = "alloc")] {
 /// # use pulz_arena::Arena;
 /// let mut arena = Arena::new();
 /// let index = arena.insert("test");
 /// assert_eq!("test", arena[index]);
+/// # }
 /// ```
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Index(u32, Generation);
@@ -65,12 +80,14 @@ impl Index {
     /// # Example
     ///
     /// ```
+    /// # #[cfg(feature = "alloc")] {
     /// # use pulz_arena::{Arena,Generation};
     /// let mut arena = Arena::new();
     /// let index0 = arena.insert("test");
     /// let index1 = arena.insert("test2");
     /// assert_eq!((0, Generation::ONE), index0.into_parts());
     /// assert_eq!((1, Generation::ONE), index1.into_parts());
+    /// # }
     /// ```
     #[inline]
     pub const fn into_parts(self) -> (u32, Generation) {
@@ -84,12 +101,14 @@ impl Index {
     /// # Example
     ///
     /// ```
+    /// # #[cfg(feature = "alloc")] {
     /// # use pulz_arena::{Arena,Generation};
     /// let mut arena = Arena::new();
     /// let index0 = arena.insert("test");
     /// let index1 = arena.insert("test2");
     /// assert_eq!(0, index0.offset());
     /// assert_eq!(1, index1.offset());
+    /// # }
     /// ```
     #[inline]
     pub const fn offset(self) -> u32 {
@@ -104,6 +123,7 @@ impl Index {
     /// # Example
     ///
     /// ```
+    /// # #[cfg(feature = "alloc")] {
     /// # use pulz_arena::{Arena,Generation};
     /// let mut arena = Arena::new();
     /// let index = arena.insert("test");
@@ -111,6 +131,7 @@ impl Index {
     /// arena.remove(index);
     /// let index = arena.insert("test2");
     /// assert_eq!(2, index.generation().get());
+    /// # }
     /// ```
     #[inline]
     pub const fn generation(self) -> Generation {
@@ -132,10 +153,12 @@ impl core::fmt::Debug for Index {
 /// # Example
 ///
 /// ```
+/// # #[cfg(feature = "alloc")] {
 /// # use pulz_arena::{Arena,Generation};
 /// let mut arena = Arena::new();
 /// let index = arena.insert("test");
 /// assert_eq!(Generation::ONE, index.generation());
+/// # }
 /// ```
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
@@ -149,15 +172,20 @@ impl core::fmt::Debug for Generation {
 
 impl Generation {
     /// The first/initial generation of an [`Index`].
-    // SAFETY: `1` is not zero!
-    pub const ONE: Self = Self(unsafe { NonZeroU32::new_unchecked(1) });
+    pub const ONE: Self = Self::from_const(1);
 
-    // SAFETY: `!1u32` is not zero!
-    const NEW: Self = Self(unsafe { NonZeroU32::new_unchecked(!1u32) });
+    const NEW: Self = Self::from_const(!1u32);
 
-    // SAFETY: `u32::MAX >> 1` is not zero!
-    #[cfg(test)]
-    const MAX: Self = Self(unsafe { NonZeroU32::new_unchecked(u32::MAX >> 1) });
+    #[cfg(all(test, feature = "alloc"))]
+    const MAX: Self = Self::from_const(u32::MAX >> 1);
+
+    // `Option::unwrap` is not `const` on our MSRV
+    const fn from_const(value: u32) -> Self {
+        match NonZeroU32::new(value) {
+            Some(value) => Self(value),
+            None => panic!("generation must not be zero"),
+        }
+    }
 
     /// Retrieves the value of this `Generation`
     #[inline]
@@ -226,33 +254,61 @@ impl Generation {
 /// let index = arena.insert("test");
 /// assert_eq!(1, arena.len());
 /// ```
+#[cfg(feature = "alloc")]
 #[derive(Clone)]
 pub struct Arena<T> {
     storage: Storage<T>,
     next_free: u32,
 }
 
+#[cfg(feature = "alloc")]
 #[derive(Clone)]
 struct Storage<T> {
-    data: Vec<Entry<T>>,
+    data: Vec<Slot<T>>,
     len: usize,
 }
 
-struct Entry<T>(Generation, EntryData<T>);
+/// A slot in the storage of an arena, that holds a single element or is free.
+///
+/// Storage for a [`FixedArena`] is provided as an array or slice of slots:
+///
+/// ```
+/// # use pulz_arena::{ArenaSlice, Slot};
+/// let mut slots = [Slot::EMPTY; 8];
+/// let mut arena = ArenaSlice::new(&mut slots);
+/// let index = arena.try_insert("test").unwrap();
+/// assert_eq!("test", arena[index]);
+/// ```
+pub struct Slot<T>(Generation, EntryData<T>);
 
 union EntryData<T> {
     next_free: u32,
     occupied: ManuallyDrop<T>,
 }
 
-impl<T> Entry<T> {
+impl<T> Slot<T> {
+    /// A free slot.
+    pub const EMPTY: Self = Self(
+        Generation::NEW,
+        EntryData {
+            next_free: u32::MAX,
+        },
+    );
+
     #[inline]
-    pub fn is_removed(&self) -> bool {
+    fn is_removed(&self) -> bool {
         self.0.is_removed()
     }
 }
 
-impl<T: Clone> Clone for Entry<T> {
+impl<T> Default for Slot<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl<T: Clone> Clone for Slot<T> {
     fn clone(&self) -> Self {
         if self.is_removed() {
             unsafe {
@@ -276,6 +332,89 @@ impl<T: Clone> Clone for Entry<T> {
     }
 }
 
+fn remove_slot<T>(slots: &mut [Slot<T>], index: Index, next_free: &mut u32) -> Option<T> {
+    let (offset, generation) = index.into_parts();
+    debug_assert!(!generation.is_removed());
+    match slots.get_mut(offset as usize) {
+        Some(Slot(entry_gen, entry)) if *entry_gen == generation => {
+            entry_gen.remove();
+            // SAFETY: user has an index with current generation: item was occupied
+            let value = unsafe { ManuallyDrop::take(&mut entry.occupied) };
+            entry.next_free = replace(next_free, offset);
+            Some(value)
+        }
+        _ => None,
+    }
+}
+
+fn get_slot<T>(slots: &[Slot<T>], index: Index) -> Option<&T> {
+    let (offset, generation) = index.into_parts();
+    debug_assert!(!generation.is_removed());
+    match slots.get(offset as usize) {
+        Some(Slot(entry_gen, entry)) if *entry_gen == generation => {
+            // SAFETY: user has an index with current generation: item was occupied
+            Some(unsafe { &entry.occupied })
+        }
+        _ => None,
+    }
+}
+
+fn get_slot_mut<T>(slots: &mut [Slot<T>], index: Index) -> Option<&mut T> {
+    let (offset, generation) = index.into_parts();
+    debug_assert!(!generation.is_removed());
+    match slots.get_mut(offset as usize) {
+        Some(Slot(entry_gen, entry)) if *entry_gen == generation => {
+            // SAFETY: user has an index with current generation: item was occupied
+            Some(unsafe { &mut entry.occupied })
+        }
+        _ => None,
+    }
+}
+
+fn get_disjoint_slots_mut<T, const N: usize>(
+    slots: &mut [Slot<T>],
+    indices: [Index; N],
+) -> Option<[&mut T; N]> {
+    for (i, index) in indices.iter().enumerate() {
+        let (offset, generation) = index.into_parts();
+        debug_assert!(!generation.is_removed());
+        match slots.get(offset as usize) {
+            Some(Slot(entry_gen, _)) if *entry_gen == generation => {}
+            _ => return None,
+        }
+        if indices[..i].iter().any(|other| other.offset() == offset) {
+            return None;
+        }
+    }
+    let data = slots.as_mut_ptr();
+    // SAFETY: all offsets are in bounds, occupied and distinct
+    Some(indices.map(|index| unsafe {
+        let entry = &mut *data.add(index.offset() as usize);
+        entry.1.occupied.deref_mut()
+    }))
+}
+
+fn get_slot_by_offset<T>(slots: &[Slot<T>], offset: u32) -> Option<&T> {
+    match slots.get(offset as usize) {
+        Some(Slot(entry_gen, entry)) if !entry_gen.is_removed() => {
+            // SAFETY: index is not marked as removed: item was occupied
+            Some(unsafe { &entry.occupied })
+        }
+        _ => None,
+    }
+}
+
+fn get_slot_mut_by_offset<T>(slots: &mut [Slot<T>], offset: u32) -> Option<&mut T> {
+    match slots.get_mut(offset as usize) {
+        Some(Slot(entry_gen, entry)) if !entry_gen.is_removed() => {
+            // SAFETY: index is not marked as removed: item was occupied
+            Some(unsafe { &mut entry.occupied })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(feature = "alloc")]
 impl<T> Storage<T> {
     #[inline]
     pub const fn new() -> Self {
@@ -316,7 +455,7 @@ impl<T> Storage<T> {
         let next_offset = self.data.len();
         if alloc || next_offset < self.data.capacity() {
             self.data
-                .push(Entry(Generation::NEW, EntryData { next_free }));
+                .push(Slot(Generation::NEW, EntryData { next_free }));
             // SAFETY: we just have created the element at next_offset
             let entry = unsafe { self.data.get_unchecked_mut(next_offset) };
             Some((next_offset as u32, &mut entry.0, &mut entry.1))
@@ -326,63 +465,34 @@ impl<T> Storage<T> {
     }
 
     pub fn remove(&mut self, index: Index, next_free: &mut u32) -> Option<T> {
-        let (offset, generation) = index.into_parts();
-        debug_assert!(!generation.is_removed());
-        match self.data.get_mut(offset as usize) {
-            Some(Entry(entry_gen, entry)) if *entry_gen == generation => {
-                entry_gen.remove();
-                self.len -= 1;
-                // SAFETY: user has an index with current generation: item was occupied
-                let value = unsafe { ManuallyDrop::take(&mut entry.occupied) };
-                entry.next_free = replace(next_free, offset);
-                Some(value)
-            }
-            _ => None,
-        }
+        let value = remove_slot(&mut self.data, index, next_free)?;
+        self.len -= 1;
+        Some(value)
     }
 
+    #[inline]
     pub fn get(&self, index: Index) -> Option<&T> {
-        let (offset, generation) = index.into_parts();
-        debug_assert!(!generation.is_removed());
-        match self.data.get(offset as usize) {
-            Some(Entry(entry_gen, entry)) if *entry_gen == generation => {
-                // SAFETY: user has an index with current generation: item was occupied
-                Some(unsafe { &entry.occupied })
-            }
-            _ => None,
-        }
+        get_slot(&self.data, index)
     }
 
+    #[inline]
     pub fn get_mut(&mut self, index: Index) -> Option<&mut T> {
-        let (offset, generation) = index.into_parts();
-        debug_assert!(!generation.is_removed());
-        match self.data.get_mut(offset as usize) {
-            Some(Entry(entry_gen, entry)) if *entry_gen == generation => {
-                // SAFETY: user has an index with current generation: item was occupied
-                Some(unsafe { &mut entry.occupied })
-            }
-            _ => None,
-        }
+        get_slot_mut(&mut self.data, index)
     }
 
+    #[inline]
+    pub fn get_disjoint_mut<const N: usize>(&mut self, indices: [Index; N]) -> Option<[&mut T; N]> {
+        get_disjoint_slots_mut(&mut self.data, indices)
+    }
+
+    #[inline]
     pub fn get_by_offset(&self, offset: u32) -> Option<&T> {
-        match self.data.get(offset as usize) {
-            Some(Entry(entry_gen, entry)) if !entry_gen.is_removed() => {
-                // SAFETY: index is not marked as removed: item was occupied
-                Some(unsafe { &entry.occupied })
-            }
-            _ => None,
-        }
+        get_slot_by_offset(&self.data, offset)
     }
 
+    #[inline]
     pub fn get_mut_by_offset(&mut self, offset: u32) -> Option<&mut T> {
-        match self.data.get_mut(offset as usize) {
-            Some(Entry(entry_gen, entry)) if !entry_gen.is_removed() => {
-                // SAFETY: index is not marked as removed: item was occupied
-                Some(unsafe { &mut entry.occupied })
-            }
-            _ => None,
-        }
+        get_slot_mut_by_offset(&mut self.data, offset)
     }
 
     pub fn drain(&mut self) -> Drain<'_, T> {
@@ -408,6 +518,7 @@ impl<T> Storage<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> Default for Arena<T> {
     #[inline]
     fn default() -> Self {
@@ -415,6 +526,7 @@ impl<T> Default for Arena<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> Arena<T> {
     /// Constructs a new, empty `Arena<T>`.
     ///
@@ -753,7 +865,7 @@ impl<T> Arena<T> {
         let next_free = *next_free_head as usize;
         if next_free < storage.data.len() {
             // SAFETY: we have checked for next_free<len
-            let Entry(generation, entry) = unsafe { storage.data.get_unchecked_mut(next_free) };
+            let Slot(generation, entry) = unsafe { storage.data.get_unchecked_mut(next_free) };
             // SAFETY: entry was in the free-list: so we can use `next_free`
             *next_free_head = unsafe { entry.next_free };
             return Some((next_free as u32, generation, entry));
@@ -857,6 +969,50 @@ impl<T> Arena<T> {
         self.storage.get_mut(index)
     }
 
+    /// Get exclusive references to the elements at multiple distinct `indices` at once.
+    ///
+    /// Returns `None`, when one of the `indices` is not in the arena, or when
+    /// an index is contained more than once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pulz_arena::Arena;
+    /// let mut arena = Arena::new();
+    /// let index0 = arena.insert(1);
+    /// let index1 = arena.insert(2);
+    /// let [a, b] = arena.get_disjoint_mut([index0, index1]).unwrap();
+    /// std::mem::swap(a, b);
+    /// assert_eq!(2, arena[index0]);
+    /// assert_eq!(1, arena[index1]);
+    /// assert!(arena.get_disjoint_mut([index0, index0]).is_none());
+    /// ```
+    #[inline]
+    pub fn get_disjoint_mut<const N: usize>(&mut self, indices: [Index; N]) -> Option<[&mut T; N]> {
+        self.storage.get_disjoint_mut(indices)
+    }
+
+    /// Get exclusive references to the elements at two distinct indices.
+    ///
+    /// This is a shortcut for [`Self::get_disjoint_mut`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pulz_arena::Arena;
+    /// let mut arena = Arena::new();
+    /// let index0 = arena.insert(1);
+    /// let index1 = arena.insert(2);
+    /// let (a, b) = arena.get2_mut(index0, index1).unwrap();
+    /// *a += *b;
+    /// assert_eq!(3, arena[index0]);
+    /// ```
+    #[inline]
+    pub fn get2_mut(&mut self, index0: Index, index1: Index) -> Option<(&mut T, &mut T)> {
+        let [a, b] = self.storage.get_disjoint_mut([index0, index1])?;
+        Some((a, b))
+    }
+
     /// Get a shared reference to the element at the given `offset` (the offset into the arena).
     ///
     /// If there is no element at the given `offset`, None is returned.
@@ -954,8 +1110,87 @@ impl<T> Arena<T> {
     pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         self.storage.iter_mut()
     }
+
+    /// Moves all elements into the lowest free spots of this arena.
+    ///
+    /// For each moved element, `remap` is called with its old and its new
+    /// `Index`, so references to it can be updated. The old indices are
+    /// invalidated. Elements that are not moved keep their `Index`.
+    /// `remap` is only called after all elements have been moved, so the
+    /// arena stays valid when it panics.
+    ///
+    /// The free spots at the end of the arena are kept (with their
+    /// generations), so old indices don't resolve to new elements; they are
+    /// re-used by later inserts in ascending order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pulz_arena::Arena;
+    /// let mut arena = Arena::new();
+    /// let index0 = arena.insert("test");
+    /// let index1 = arena.insert("foo");
+    /// let index2 = arena.insert("bar");
+    /// arena.remove(index0);
+    /// let mut moved = Vec::new();
+    /// arena.compact(|old, new| moved.push((old, new)));
+    /// assert_eq!(1, moved.len());
+    /// assert_eq!(index2, moved[0].0);
+    /// assert_eq!(0, moved[0].1.offset());
+    /// assert_eq!(None, arena.get(index2));
+    /// assert_eq!("bar", arena[moved[0].1]);
+    /// assert_eq!("foo", arena[index1]);
+    /// ```
+    pub fn compact<F>(&mut self, mut remap: F)
+    where
+        F: FnMut(Index, Index),
+    {
+        let data = &mut self.storage.data;
+        let mut moved = Vec::new();
+        let mut free = 0;
+        let mut occupied = data.len();
+        loop {
+            while free < occupied && !data[free].is_removed() {
+                free += 1;
+            }
+            while free < occupied && data[occupied - 1].is_removed() {
+                occupied -= 1;
+            }
+            if free >= occupied {
+                break;
+            }
+            occupied -= 1;
+            let (head, tail) = data.split_at_mut(occupied);
+            let Slot(old_gen, old_entry) = &mut tail[0];
+            let Slot(new_gen, new_entry) = &mut head[free];
+            let old_index = Index(occupied as u32, *old_gen);
+            old_gen.remove();
+            new_gen.increment();
+            // SAFETY: entry at `occupied` is not removed: so it is occupied.
+            // It is marked as removed now, so the value is not dropped twice.
+            new_entry.occupied =
+                unsafe { ManuallyDrop::new(ManuallyDrop::take(&mut old_entry.occupied)) };
+            moved.push((old_index, Index(free as u32, *new_gen)));
+            free += 1;
+        }
+
+        // rebuild the free-list in ascending order
+        let len = self.storage.len;
+        let mut next_free = u32::MAX;
+        for offset in (len..data.len()).rev() {
+            data[offset].1.next_free = next_free;
+            next_free = offset as u32;
+        }
+        self.next_free = next_free;
+
+        // the arena is consistent again, so `remap` is allowed to panic
+        for (old_index, new_index) in moved {
+            remap(old_index, new_index);
+        }
+    }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> core::ops::Index<Index> for Arena<T> {
     type Output = T;
     #[inline]
@@ -964,6 +1199,7 @@ impl<T> core::ops::Index<Index> for Arena<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> core::ops::IndexMut<Index> for Arena<T> {
     #[inline]
     fn index_mut(&mut self, index: Index) -> &mut T {
@@ -971,6 +1207,7 @@ impl<T> core::ops::IndexMut<Index> for Arena<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> core::ops::Index<u32> for Arena<T> {
     type Output = T;
     #[inline]
@@ -979,6 +1216,7 @@ impl<T> core::ops::Index<u32> for Arena<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> core::ops::IndexMut<u32> for Arena<T> {
     #[inline]
     fn index_mut(&mut self, index: u32) -> &mut T {
@@ -986,6 +1224,7 @@ impl<T> core::ops::IndexMut<u32> for Arena<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> Extend<T> for Arena<T> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         for t in iter {
@@ -994,6 +1233,7 @@ impl<T> Extend<T> for Arena<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> FromIterator<T> for Arena<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let iter = iter.into_iter();
@@ -1007,6 +1247,7 @@ impl<T> FromIterator<T> for Arena<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> Drop for Arena<T> {
     fn drop(&mut self) {
         self.clear();
@@ -1014,11 +1255,13 @@ impl<T> Drop for Arena<T> {
 }
 
 /// A draining iterator for `Arena<T>` created by [`Arena::drain`].
+#[cfg(feature = "alloc")]
 pub struct Drain<'a, T> {
     len: usize,
-    inner: core::iter::Enumerate<alloc::vec::Drain<'a, Entry<T>>>,
+    inner: core::iter::Enumerate<alloc::vec::Drain<'a, Slot<T>>>,
 }
 
+#[cfg(feature = "alloc")]
 impl<'a, T> Iterator for Drain<'a, T> {
     type Item = (Index, T);
 
@@ -1026,7 +1269,7 @@ impl<'a, T> Iterator for Drain<'a, T> {
         loop {
             match self.inner.next() {
                 Some((_, entry)) if entry.is_removed() => continue,
-                Some((offset, Entry(gen, mut entry))) => {
+                Some((offset, Slot(gen, mut entry))) => {
                     let idx = Index(offset as u32, gen);
                     // SAFETY: entry was not marked as removed, so it is occupied
                     let value = unsafe { ManuallyDrop::take(&mut entry.occupied) };
@@ -1046,12 +1289,13 @@ impl<'a, T> Iterator for Drain<'a, T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a, T> DoubleEndedIterator for Drain<'a, T> {
     fn next_back(&mut self) -> Option<Self::Item> {
         loop {
             match self.inner.next_back() {
                 Some((_, entry)) if entry.is_removed() => continue,
-                Some((offset, Entry(gen, mut entry))) => {
+                Some((offset, Slot(gen, mut entry))) => {
                     let idx = Index(offset as u32, gen);
                     // SAFETY: entry was not marked as removed, so it is occupied
                     let value = unsafe { ManuallyDrop::take(&mut entry.occupied) };
@@ -1067,14 +1311,17 @@ impl<'a, T> DoubleEndedIterator for Drain<'a, T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a, T> FusedIterator for Drain<'a, T> {}
 
+#[cfg(feature = "alloc")]
 impl<'a, T> ExactSizeIterator for Drain<'a, T> {
     fn len(&self) -> usize {
         self.len
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a, T> Drop for Drain<'a, T> {
     fn drop(&mut self) {
         for item in self {
@@ -1086,17 +1333,21 @@ impl<'a, T> Drop for Drain<'a, T> {
 /// An immutable iterator for `Arena<T>` created by [`Arena::iter`].
 pub struct Iter<'a, T> {
     len: usize,
-    inner: core::iter::Enumerate<core::slice::Iter<'a, Entry<T>>>,
+    inner: core::iter::Enumerate<core::slice::Iter<'a, Slot<T>>>,
 }
 
 impl<'a, T> Iterator for Iter<'a, T> {
     type Item = (Index, &'a T);
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            // skip the remaining free entries
+            return None;
+        }
         loop {
             match self.inner.next() {
                 Some((_, entry)) if entry.is_removed() => continue,
-                Some((offset, Entry(gen, entry))) => {
+                Some((offset, Slot(gen, entry))) => {
                     let idx = Index(offset as u32, *gen);
                     // SAFETY: entry was not removed: so it is occupied
                     let value = unsafe { &entry.occupied };
@@ -1121,7 +1372,7 @@ impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
         loop {
             match self.inner.next_back() {
                 Some((_, entry)) if entry.is_removed() => continue,
-                Some((offset, Entry(gen, entry))) => {
+                Some((offset, Slot(gen, entry))) => {
                     let idx = Index(offset as u32, *gen);
                     // SAFETY: entry was not removed: so it is occupied
                     let value = unsafe { &entry.occupied };
@@ -1148,17 +1399,21 @@ impl<'a, T> FusedIterator for Iter<'a, T> {}
 /// An mutable iterator for `Arena<T>` created by [`Arena::iter_mut`].
 pub struct IterMut<'a, T> {
     len: usize,
-    inner: core::iter::Enumerate<core::slice::IterMut<'a, Entry<T>>>,
+    inner: core::iter::Enumerate<core::slice::IterMut<'a, Slot<T>>>,
 }
 
 impl<'a, T> Iterator for IterMut<'a, T> {
     type Item = (Index, &'a mut T);
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            // skip the remaining free entries
+            return None;
+        }
         loop {
             match self.inner.next() {
                 Some((_, entry)) if entry.is_removed() => continue,
-                Some((offset, Entry(gen, entry))) => {
+                Some((offset, Slot(gen, entry))) => {
                     let idx = Index(offset as u32, *gen);
                     // SAFETY: entry was not removed: so it is occupied
                     let value = unsafe { &mut entry.occupied };
@@ -1183,7 +1438,7 @@ impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
         loop {
             match self.inner.next_back() {
                 Some((_, entry)) if entry.is_removed() => continue,
-                Some((offset, Entry(gen, entry))) => {
+                Some((offset, Slot(gen, entry))) => {
                     let idx = Index(offset as u32, *gen);
                     // SAFETY: entry was not removed: so it is occupied
                     let value = unsafe { &mut entry.occupied };
@@ -1228,9 +1483,11 @@ impl<'a, T> FusedIterator for IterMut<'a, T> {}
 /// assert_eq!("test", arena[index]);
 /// assert_eq!(123, mirror[index]);
 /// ```
+#[cfg(feature = "alloc")]
 #[derive(Clone)]
 pub struct Mirror<T>(Storage<T>);
 
+#[cfg(feature = "alloc")]
 impl<T> Mirror<T> {
     /// Constructs a new, empty `Mirror<T>`.
     ///
@@ -1431,14 +1688,7 @@ impl<T> Mirror<T> {
         let offset = index.offset() as usize;
         // allocate free entries until offset
         if self.0.data.len() <= offset {
-            self.0.data.resize_with(offset + 1, || {
-                Entry(
-                    Generation::NEW,
-                    EntryData {
-                        next_free: u32::MAX,
-                    },
-                )
-            });
+            self.0.data.resize_with(offset + 1, || Slot::EMPTY);
         }
 
         // SAFETY: we allocated free entries until offset
@@ -1677,6 +1927,7 @@ impl<T> Mirror<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> Default for Mirror<T> {
     #[inline]
     fn default() -> Self {
@@ -1684,6 +1935,7 @@ impl<T> Default for Mirror<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> core::ops::Index<Index> for Mirror<T> {
     type Output = T;
     #[inline]
@@ -1692,6 +1944,7 @@ impl<T> core::ops::Index<Index> for Mirror<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> core::ops::IndexMut<Index> for Mirror<T> {
     #[inline]
     fn index_mut(&mut self, index: Index) -> &mut T {
@@ -1699,6 +1952,7 @@ impl<T> core::ops::IndexMut<Index> for Mirror<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> core::ops::Index<u32> for Mirror<T> {
     type Output = T;
     #[inline]
@@ -1707,6 +1961,7 @@ impl<T> core::ops::Index<u32> for Mirror<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> core::ops::IndexMut<u32> for Mirror<T> {
     #[inline]
     fn index_mut(&mut self, index: u32) -> &mut T {
@@ -1714,6 +1969,7 @@ impl<T> core::ops::IndexMut<u32> for Mirror<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> Extend<(Index, T)> for Mirror<T> {
     fn extend<I: IntoIterator<Item = (Index, T)>>(&mut self, iter: I) {
         for (index, value) in iter {
@@ -1722,6 +1978,7 @@ impl<T> Extend<(Index, T)> for Mirror<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> FromIterator<(Index, T)> for Mirror<T> {
     fn from_iter<I: IntoIterator<Item = (Index, T)>>(iter: I) -> Self {
         let iter = iter.into_iter();
@@ -1735,7 +1992,7 @@ impl<T> FromIterator<(Index, T)> for Mirror<T> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
     use alloc::{format, sync::Arc, vec};
 
@@ -2017,6 +2274,106 @@ mod tests {
         assert!(arena.get_mut(index0).is_none())
     }
 
+    #[test]
+    fn test_arena_get_disjoint_mut() {
+        let mut arena = Arena::new();
+        let index0 = arena.insert(0);
+        let index1 = arena.insert(1);
+        let index2 = arena.insert(2);
+        let [a, b, c] = arena.get_disjoint_mut([index2, index0, index1]).unwrap();
+        *a += 10;
+        *b += 20;
+        *c += 30;
+        assert_eq!(20, arena[index0]);
+        assert_eq!(31, arena[index1]);
+        assert_eq!(12, arena[index2]);
+        assert!(arena.get_disjoint_mut([index0, index1, index0]).is_none());
+        assert_eq!(Some(31), arena.remove(index1));
+        assert!(arena.get_disjoint_mut([index0, index1]).is_none());
+        // re-used slot with a new generation
+        let index3 = arena.insert(3);
+        assert_eq!(index1.offset(), index3.offset());
+        assert!(arena.get2_mut(index1, index0).is_none());
+        let (a, b) = arena.get2_mut(index3, index0).unwrap();
+        core::mem::swap(a, b);
+        assert_eq!(20, arena[index3]);
+        assert_eq!(3, arena[index0]);
+    }
+
+    #[test]
+    fn test_arena_compact() {
+        let mut arena = Arena::new();
+        let indices: Vec<_> = (0..10).map(|i| arena.insert(Arc::new(i))).collect();
+        for i in [0, 2, 3, 7, 8] {
+            arena.remove(indices[i]);
+        }
+        let mut moved = Vec::new();
+        arena.compact(|old, new| moved.push((old, new)));
+        assert_eq!(5, arena.len());
+        assert_eq!(
+            vec![(9, 0), (6, 2), (5, 3)],
+            moved
+                .iter()
+                .map(|(old, new)| (old.offset(), new.offset()))
+                .collect::<Vec<_>>()
+        );
+        for (old, new) in &moved {
+            assert!(!arena.contains(*old));
+            assert_eq!(old.offset() as usize, *arena[*new]);
+        }
+        let values: Vec<_> = arena.iter().map(|(i, v)| (i.offset(), **v)).collect();
+        assert_eq!(vec![(0, 9), (1, 1), (2, 6), (3, 5), (4, 4)], values);
+        assert_eq!(
+            vec![4, 5, 6, 1, 9],
+            arena.iter().rev().map(|(_, v)| **v).collect::<Vec<_>>()
+        );
+
+        // free spots are re-used in ascending order, with new generations
+        let index = arena.insert(Arc::new(10));
+        assert_eq!(5, index.offset());
+        assert!(!arena.contains(indices[5]));
+        assert_eq!(6, arena.insert(Arc::new(11)).offset());
+        assert_eq!(7, arena.len());
+
+        // values are dropped exactly once
+        let value = Arc::new(99);
+        arena.insert(value.clone());
+        arena.remove(indices[1]);
+        arena.compact(|_, _| {});
+        assert_eq!(2, Arc::strong_count(&value));
+        drop(arena);
+        assert_eq!(1, Arc::strong_count(&value));
+    }
+
+    #[test]
+    fn test_arena_compact_panic_in_remap() {
+        extern crate std;
+        use alloc::string::{String, ToString};
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut arena: Arena<String> = Arena::new();
+        let index0 = arena.insert("a".to_string());
+        let index1 = arena.insert("b".to_string());
+        let index2 = arena.insert("c".to_string());
+        arena.remove(index0);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            arena.compact(|_, _| panic!("remap failed"));
+        }));
+        assert!(result.is_err());
+
+        // the moved value is still reachable at its new offset
+        assert_eq!(2, arena.len());
+        assert!(!arena.contains(index2));
+        assert_eq!("b", arena[index1]);
+        assert_eq!(Some(&"c".to_string()), arena.get_by_offset(0));
+
+        // the free-list points to the released spot at the end
+        let index3 = arena.insert("d".to_string());
+        assert_eq!(2, index3.offset());
+        assert_eq!("c", arena[0]);
+        assert_eq!("d", arena[index3]);
+    }
+
     #[test]
     fn test_arena_drain() {
         let mut arena = Arena::new();
@@ -116,6 +116,51 @@ impl BitSet {
         }
     }
 
+    /// Returns the word-indices and the masks of the bits in the range.
+    fn range_masks(range: Range<usize>) -> impl Iterator<Item = (usize, u64)> {
+        let start = range.start;
+        let end = range.end.max(start);
+        let words_from = start >> SHIFT_DIV64;
+        let words_to = end >> SHIFT_DIV64;
+        let words_to_rest = end & MASK_MOD64;
+        let words_end = if start == end {
+            words_from // empty range
+        } else if words_to_rest == 0 {
+            words_to
+        } else {
+            words_to + 1
+        };
+        (words_from..words_end).map(move |i| {
+            let mut mask = !0u64;
+            if i == words_from {
+                mask <<= start & MASK_MOD64;
+            }
+            if i == words_to {
+                mask &= !((!0u64) << words_to_rest);
+            }
+            (i, mask)
+        })
+    }
+
+    /// Removes all values in the given range.
+    pub fn remove_range(&mut self, range: Range<usize>) {
+        for (i, mask) in Self::range_masks(range) {
+            let Some(word) = self.0.get_mut(i) else {
+                break;
+            };
+            *word &= !mask;
+        }
+        self.normalize_after_remove();
+    }
+
+    /// Returns `true` when all values in the given range are contained.
+    ///
+    /// Returns `true` for an empty range.
+    pub fn contains_range(&self, range: Range<usize>) -> bool {
+        Self::range_masks(range)
+            .all(|(i, mask)| matches!(self.0.get(i), Some(word) if word & mask == mask))
+    }
+
     #[inline]
     fn split_value(value: usize) -> (usize, u64) {
         let index = value >> SHIFT_DIV64;
@@ -235,7 +280,7 @@ impl BitSet {
         let end = match range.end_bound() {
             std::ops::Bound::Included(i) => *i,
             std::ops::Bound::Excluded(i) => (*i).saturating_sub(1),
-            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Unbounded => !0,
         };
         (start, end)
     }
@@ -617,4 +662,64 @@ mod tests {
         assert_eq!(Some(1337), iter.next());
         assert_eq!(None, iter.next());
     }
+
+    #[test]
+    fn test_retain_unbounded() {
+        let mut subject = BitSet::new();
+        subject.insert(0);
+        subject.insert(1);
+        subject.insert(64);
+        subject.insert(1337);
+
+        let mut visited = Vec::new();
+        subject.retain(.., |i| {
+            visited.push(i);
+            i != 64
+        });
+        assert_eq!(vec![0, 1, 64, 1337], visited);
+        assert_eq!(vec![0, 1, 1337], subject.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_remove_range() {
+        let mut subject = BitSet::from_range(10..300);
+        subject.remove_range(60..130);
+        assert!(subject.contains(59));
+        assert!(!subject.contains(60));
+        assert!(!subject.contains(64));
+        assert!(!subject.contains(128));
+        assert!(!subject.contains(129));
+        assert!(subject.contains(130));
+        assert!(subject.contains(299));
+
+        subject.remove_range(100..100);
+        #[allow(clippy::reversed_empty_ranges)]
+        subject.remove_range(200..150);
+        assert!(subject.contains(200));
+
+        // removing the end shrinks the set (for Eq)
+        subject.remove_range(130..1000);
+        assert_eq!(BitSet::from_range(10..60), subject);
+        subject.remove_range(0..64);
+        assert!(subject.is_empty());
+        assert_eq!(BitSet::new(), subject);
+    }
+
+    #[test]
+    fn test_contains_range() {
+        let subject = BitSet::from_range(10..300);
+        assert!(subject.contains_range(10..300));
+        assert!(subject.contains_range(64..128));
+        assert!(subject.contains_range(20..21));
+        assert!(!subject.contains_range(9..20));
+        assert!(!subject.contains_range(290..301));
+        assert!(!subject.contains_range(500..600));
+        assert!(subject.contains_range(500..500));
+
+        let mut subject = subject;
+        subject.remove(200);
+        assert!(!subject.contains_range(10..300));
+        assert!(subject.contains_range(10..200));
+        assert!(subject.contains_range(201..300));
+    }
 }
@@ -0,0 +1,334 @@
+use std::{
+    backtrace::Backtrace,
+    collections::VecDeque,
+    fs::File,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use pulz_schedule::{label::CoreSystemPhase, prelude::*};
+
+use crate::{Diagnostics, DiagnosticsModule};
+
+/// The default number of breadcrumbs kept by the [`CrashReporter`].
+pub const DEFAULT_MAX_BREADCRUMBS: usize = 32;
+
+/// Installs a panic hook, that writes a crash report into a folder before
+/// unwinding.
+///
+/// The report contains the panic message and location, a backtrace, the
+/// frame number, the latest values of all [`Diagnostics`], the most recent
+/// breadcrumbs (see [`CrashReporter::add_breadcrumb`]) and the schedule (as
+/// a `.dot` graph, when captured with [`CrashReporter::capture_schedule`]).
+/// The previously installed panic hook is called afterwards.
+///
+/// No message box is shown by default, because that requires a platform GUI
+/// dependency. Desktop applications can show one from the callback passed to
+/// [`CrashReportModule::with_message_box`] (e.g. with a dialog crate like
+/// `rfd`).
+pub struct CrashReportModule {
+    /// The folder, where the crash reports are written to.
+    pub directory: PathBuf,
+    /// The number of breadcrumbs included in the report.
+    pub max_breadcrumbs: usize,
+    message_box: Option<MessageBoxFn>,
+}
+
+/// Called by the panic hook with the panic message and the path of the
+/// written crash report.
+type MessageBoxFn = Arc<dyn Fn(&str, &Path) + Send + Sync>;
+
+impl Default for CrashReportModule {
+    #[inline]
+    fn default() -> Self {
+        Self::new("crashes")
+    }
+}
+
+impl CrashReportModule {
+    #[inline]
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            max_breadcrumbs: DEFAULT_MAX_BREADCRUMBS,
+            message_box: None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_max_breadcrumbs(mut self, max_breadcrumbs: usize) -> Self {
+        self.max_breadcrumbs = max_breadcrumbs;
+        self
+    }
+
+    /// Sets a function, that is called by the panic hook with the panic
+    /// message and the path of the crash report, after the report was
+    /// written (e.g. for showing a message box).
+    #[inline]
+    #[must_use]
+    pub fn with_message_box<F>(mut self, message_box: F) -> Self
+    where
+        F: Fn(&str, &Path) + Send + Sync + 'static,
+    {
+        self.message_box = Some(Arc::new(message_box));
+        self
+    }
+}
+
+#[derive(Default)]
+struct CrashContext {
+    frame: u64,
+    diagnostics: Vec<(&'static str, f64, String)>,
+    breadcrumbs: VecDeque<String>,
+    max_breadcrumbs: usize,
+    schedule_dot: Option<String>,
+}
+
+/// Resource that collects the state, that is written into crash reports.
+///
+/// The state is shared with the panic hook, so the reporter can be cloned
+/// and used from other threads. The directory of the reports is fixed when
+/// the reporter is created, so the panic hook doesn't need to lock the state
+/// for it.
+#[derive(Clone)]
+pub struct CrashReporter {
+    context: Arc<Mutex<CrashContext>>,
+    directory: Arc<PathBuf>,
+    message_box: Option<MessageBoxFn>,
+}
+
+impl CrashReporter {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            context: Arc::new(Mutex::new(CrashContext {
+                max_breadcrumbs: DEFAULT_MAX_BREADCRUMBS,
+                ..Default::default()
+            })),
+            directory: Arc::new(directory.into()),
+            message_box: None,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CrashContext> {
+        self.context.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[inline]
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    pub fn set_max_breadcrumbs(&self, max_breadcrumbs: usize) {
+        let mut context = self.lock();
+        context.max_breadcrumbs = max_breadcrumbs;
+        while context.breadcrumbs.len() > max_breadcrumbs {
+            context.breadcrumbs.pop_front();
+        }
+    }
+
+    /// The number of the current frame (starting at 1).
+    #[inline]
+    pub fn frame(&self) -> u64 {
+        self.lock().frame
+    }
+
+    /// Records a message (e.g. a recent event), that is included in the
+    /// crash report.
+    ///
+    /// Only the most recent breadcrumbs are kept.
+    pub fn add_breadcrumb(&self, message: impl Into<String>) {
+        let mut context = self.lock();
+        if context.max_breadcrumbs == 0 {
+            return;
+        }
+        if context.breadcrumbs.len() >= context.max_breadcrumbs {
+            context.breadcrumbs.pop_front();
+        }
+        context.breadcrumbs.push_back(message.into());
+    }
+
+    /// Captures the `.dot` graph of the given schedule for the crash report.
+    ///
+    /// This should be called after the schedule was initialized (e.g. after
+    /// it was run for the first time).
+    pub fn capture_schedule(&self, schedule: &Schedule) {
+        let mut dot = Vec::new();
+        if schedule.write_dot(&mut dot, None).is_ok() {
+            self.lock().schedule_dot = Some(String::from_utf8_lossy(&dot).into_owned());
+        }
+    }
+
+    fn update(&self, diagnostics: &Diagnostics) {
+        let mut context = self.lock();
+        context.frame += 1;
+        context.diagnostics.clear();
+        context.diagnostics.extend(
+            diagnostics
+                .iter()
+                .filter_map(|d| Some((d.name(), d.value()?, d.suffix().to_owned()))),
+        );
+    }
+
+    /// Writes the collected state (frame number, diagnostics, breadcrumbs,
+    /// schedule).
+    pub fn write_context(&self, w: &mut dyn Write) -> io::Result<()> {
+        // don't block, when the panic happened while the context was locked
+        let context = match self.context.try_lock() {
+            Ok(context) => context,
+            Err(std::sync::TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(std::sync::TryLockError::WouldBlock) => {
+                return writeln!(w, "(state not available: locked)");
+            }
+        };
+        writeln!(w, "Frame: {}", context.frame)?;
+        writeln!(w, "\nDiagnostics\n===========")?;
+        for (name, value, suffix) in &context.diagnostics {
+            writeln!(w, "{name:<32}: {value:>12.4}{suffix}")?;
+        }
+        writeln!(w, "\nBreadcrumbs\n===========")?;
+        for message in &context.breadcrumbs {
+            writeln!(w, "{message}")?;
+        }
+        if let Some(dot) = &context.schedule_dot {
+            writeln!(w, "\nSchedule\n========\n{dot}")?;
+        }
+        Ok(())
+    }
+
+    fn write_report_file(&self, message: &str, backtrace: &Backtrace) -> io::Result<PathBuf> {
+        let directory = &*self.directory;
+        std::fs::create_dir_all(directory)?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis());
+        let path = directory.join(format!("crash-{timestamp}.txt"));
+        let mut file = File::create(&path)?;
+        writeln!(file, "{message}\n\nBacktrace\n=========\n{backtrace}\n")?;
+        self.write_context(&mut file)?;
+        file.flush()?;
+        Ok(path)
+    }
+
+    /// Installs the panic hook, that writes the crash reports.
+    ///
+    /// The message box function (see [`CrashReportModule::with_message_box`])
+    /// and then the previous panic hook are called after the report was
+    /// written.
+    pub fn install_panic_hook(&self) {
+        let reporter = self.clone();
+        let previous_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let payload = info
+                .payload()
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("Box<dyn Any>");
+            let message = match info.location() {
+                Some(location) => format!("panicked at {location}:\n{payload}"),
+                None => format!("panicked:\n{payload}"),
+            };
+            let backtrace = Backtrace::force_capture();
+            match reporter.write_report_file(&message, &backtrace) {
+                Ok(path) => {
+                    eprintln!("crash report written to {}", path.display());
+                    if let Some(message_box) = &reporter.message_box {
+                        message_box(&message, &path);
+                    }
+                }
+                Err(e) => eprintln!("unable to write crash report: {e}"),
+            }
+            previous_hook(info);
+        }));
+    }
+}
+
+impl Module for CrashReportModule {
+    fn install_modules(&self, resources: &mut Resources) {
+        resources.install(DiagnosticsModule);
+    }
+
+    fn install_resources(self, resources: &mut Resources) {
+        if let Some(reporter) = resources.get_mut::<CrashReporter>() {
+            if reporter.directory() != self.directory {
+                log::warn!(
+                    "crash reports are already written to {}",
+                    reporter.directory().display()
+                );
+            }
+            reporter.set_max_breadcrumbs(self.max_breadcrumbs);
+            return;
+        }
+        let mut reporter = CrashReporter::new(self.directory);
+        reporter.message_box = self.message_box;
+        reporter.set_max_breadcrumbs(self.max_breadcrumbs);
+        reporter.install_panic_hook();
+        resources.insert(reporter);
+    }
+
+    fn install_systems(schedule: &mut Schedule) {
+        schedule
+            .add_system(CrashReporter::update)
+            .into_phase(CoreSystemPhase::First);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Diagnostic;
+
+    pulz_schedule::define_label_enum! {
+        enum TestDiagnostic: crate::DiagnosticLabel {
+            DrawCalls,
+        }
+    }
+
+    #[test]
+    fn test_write_context() {
+        let reporter = CrashReporter::new("crashes");
+        reporter.set_max_breadcrumbs(2);
+        for message in ["loaded level", "spawned player", "opened menu"] {
+            reporter.add_breadcrumb(message);
+        }
+        let mut diagnostics = Diagnostics::new();
+        diagnostics
+            .add(Diagnostic::new(TestDiagnostic::DrawCalls).with_suffix(" calls"))
+            .add_measurement(12.0);
+        reporter.update(&diagnostics);
+        reporter.update(&diagnostics);
+        assert_eq!(2, reporter.frame());
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(|| {});
+        reporter.capture_schedule(&schedule);
+
+        let mut report = Vec::new();
+        reporter.write_context(&mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.starts_with("Frame: 2\n"), "{report}");
+        assert!(report.contains("TestDiagnostic::DrawCalls"), "{report}");
+        assert!(report.contains("12.0000 calls"), "{report}");
+        assert!(!report.contains("loaded level"), "{report}");
+        assert!(report.contains("spawned player\nopened menu\n"), "{report}");
+        assert!(report.contains("digraph system {"), "{report}");
+    }
+
+    #[test]
+    fn test_write_report_while_locked() {
+        let directory = std::env::temp_dir().join(format!("pulz-crashes-{}", std::process::id()));
+        let reporter = CrashReporter::new(&directory);
+        let context = reporter.lock();
+        let path = reporter
+            .write_report_file("panicked", &Backtrace::disabled())
+            .unwrap();
+        drop(context);
+        assert!(path.starts_with(&directory));
+        let report = std::fs::read_to_string(&path).unwrap();
+        assert!(report.contains("(state not available: locked)"), "{report}");
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
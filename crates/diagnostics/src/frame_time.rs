@@ -0,0 +1,105 @@
+use std::time::Instant;
+
+use pulz_schedule::{define_label_enum, label::CoreSystemPhase, local::Local, prelude::*};
+
+use crate::{Diagnostic, DiagnosticLabel, Diagnostics, DiagnosticsModule};
+
+define_label_enum! {
+    /// Labels of the diagnostics recorded by [`FrameTimeDiagnosticsModule`].
+    ///
+    /// * `FrameTime`: duration of the previous frame in milliseconds
+    /// * `Fps`: frames per second, derived from the average frame time
+    /// * `FrameCount`: number of frames since the module was installed
+    pub enum FrameTimeDiagnostic: DiagnosticLabel {
+        FrameTime,
+        Fps,
+        FrameCount,
+    }
+}
+
+/// Records frame time, FPS and the frame count at the start of every frame.
+pub struct FrameTimeDiagnosticsModule;
+
+#[derive(Default)]
+struct FrameTimeState {
+    last_frame: Option<Instant>,
+    frame_count: u64,
+}
+
+impl FrameTimeDiagnosticsModule {
+    fn update(diagnostics: &mut Diagnostics, mut state: Local<'_, FrameTimeState>) {
+        let now = Instant::now();
+        state.frame_count += 1;
+        diagnostics.add_measurement(FrameTimeDiagnostic::FrameCount, state.frame_count as f64);
+
+        if let Some(last_frame) = state.last_frame.replace(now) {
+            let delta = now.saturating_duration_since(last_frame).as_secs_f64();
+            if delta > 0.0 {
+                diagnostics.add_measurement(FrameTimeDiagnostic::FrameTime, delta * 1000.0);
+                // derived from the average frame time (averaging the FPS of
+                // individual frames over-weights short frames)
+                let fps = match diagnostics
+                    .get(FrameTimeDiagnostic::FrameTime)
+                    .and_then(Diagnostic::average)
+                {
+                    Some(average) if average > 0.0 => 1000.0 / average,
+                    _ => 1.0 / delta,
+                };
+                diagnostics.add_measurement(FrameTimeDiagnostic::Fps, fps);
+            }
+        }
+    }
+}
+
+impl Module for FrameTimeDiagnosticsModule {
+    fn install_modules(&self, resources: &mut Resources) {
+        resources.install(DiagnosticsModule);
+    }
+
+    fn install_once(&self, resources: &mut Resources) {
+        let mut diagnostics = resources.borrow_res_mut::<Diagnostics>().unwrap();
+        diagnostics.add(Diagnostic::new(FrameTimeDiagnostic::FrameTime).with_suffix("ms"));
+        diagnostics.add(Diagnostic::new(FrameTimeDiagnostic::Fps));
+        diagnostics
+            .add(Diagnostic::new(FrameTimeDiagnostic::FrameCount).with_max_history_length(1));
+    }
+
+    fn install_systems(schedule: &mut Schedule) {
+        schedule
+            .add_system(Self::update)
+            .into_phase(CoreSystemPhase::First);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_frame_time_diagnostics() {
+        let mut resources = Resources::new();
+        resources.install(FrameTimeDiagnosticsModule);
+        let mut schedule = resources.remove::<Schedule>().unwrap();
+        for _ in 0..3 {
+            schedule.run(&mut resources);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        resources.insert_again(schedule);
+
+        let diagnostics = resources.borrow_res::<Diagnostics>().unwrap();
+        let frame_count = diagnostics.get(FrameTimeDiagnostic::FrameCount).unwrap();
+        assert_eq!(Some(3.0), frame_count.value());
+        assert_eq!(1, frame_count.history_len());
+
+        let frame_time = diagnostics.get(FrameTimeDiagnostic::FrameTime).unwrap();
+        assert_eq!("ms", frame_time.suffix());
+        assert_eq!(2, frame_time.history_len());
+        assert!(frame_time.values().all(|ms| ms >= 5.0));
+
+        let fps = diagnostics.get(FrameTimeDiagnostic::Fps).unwrap();
+        assert_eq!(2, fps.history_len());
+        assert_eq!(Some(1000.0 / frame_time.average().unwrap()), fps.value());
+    }
+}
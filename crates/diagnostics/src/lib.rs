@@ -0,0 +1,402 @@
+#![warn(
+    // missing_docs,
+    // rustdoc::missing_doc_code_examples,
+    future_incompatible,
+    rust_2018_idioms,
+    unused,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_qualifications,
+    unused_crate_dependencies,
+    clippy::cargo,
+    clippy::multiple_crate_versions,
+    clippy::empty_line_after_outer_attr,
+    clippy::fallible_impl_from,
+    clippy::redundant_pub_crate,
+    clippy::use_self,
+    clippy::suspicious_operation_groupings,
+    clippy::useless_let_if_seq,
+    // clippy::missing_errors_doc,
+    // clippy::missing_panics_doc,
+    clippy::wildcard_imports
+)]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/HellButcher/pulz/master/docs/logo.png")]
+#![doc(html_no_source)]
+#![doc = include_str!("../README.md")]
+
+use std::{
+    borrow::Cow,
+    collections::{btree_map, BTreeMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+use pulz_schedule::{
+    define_label_enum, define_label_type,
+    label::{CoreSystemPhase, SystemPhase},
+    prelude::*,
+};
+
+pub mod crash;
+pub mod frame_time;
+pub mod logging;
+
+pub mod prelude {
+    pub use crate::{
+        crash::{CrashReportModule, CrashReporter},
+        frame_time::{FrameTimeDiagnostic, FrameTimeDiagnosticsModule},
+        logging::LogDiagnosticsModule,
+        Diagnostic, DiagnosticId, DiagnosticLabel, Diagnostics, DiagnosticsModule,
+    };
+}
+
+define_label_type!(
+    /// A label that identifies a [`Diagnostic`].
+    DiagnosticLabel,
+    DiagnosticId
+);
+
+define_label_enum! {
+    /// Phase in which the diagnostics of the previous frame are reported.
+    ///
+    /// This phase runs before [`CoreSystemPhase::Update`].
+    pub enum DiagnosticsPhase: SystemPhase {
+        Output,
+    }
+}
+
+/// The default number of measurements kept in the history of a [`Diagnostic`].
+pub const DEFAULT_MAX_HISTORY_LENGTH: usize = 120;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DiagnosticMeasurement {
+    pub time: Instant,
+    pub value: f64,
+}
+
+/// A single diagnostic value with a limited history of measurements.
+#[derive(Debug)]
+pub struct Diagnostic {
+    id: DiagnosticId,
+    suffix: Cow<'static, str>,
+    history: VecDeque<DiagnosticMeasurement>,
+    max_history_length: usize,
+    pending: Option<f64>,
+    enabled: bool,
+}
+
+impl Diagnostic {
+    pub fn new(label: impl DiagnosticLabel) -> Self {
+        Self {
+            id: label.as_label(),
+            suffix: Cow::Borrowed(""),
+            history: VecDeque::with_capacity(DEFAULT_MAX_HISTORY_LENGTH),
+            max_history_length: DEFAULT_MAX_HISTORY_LENGTH,
+            pending: None,
+            enabled: true,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_suffix(mut self, suffix: impl Into<Cow<'static, str>>) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_history_length(mut self, max_history_length: usize) -> Self {
+        let max_history_length = max_history_length.max(1);
+        self.max_history_length = max_history_length;
+        while self.history.len() > max_history_length {
+            self.pop_oldest();
+        }
+        self
+    }
+
+    #[inline]
+    pub fn id(&self) -> DiagnosticId {
+        self.id
+    }
+
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.id.as_str()
+    }
+
+    #[inline]
+    pub fn suffix(&self) -> &str {
+        &self.suffix
+    }
+
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables this diagnostic.
+    ///
+    /// Measurements of disabled diagnostics are discarded.
+    #[inline]
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    #[inline]
+    pub fn add_measurement(&mut self, value: f64) {
+        self.add_measurement_at(Instant::now(), value)
+    }
+
+    pub fn add_measurement_at(&mut self, time: Instant, value: f64) {
+        if !self.enabled {
+            return;
+        }
+        if self.history.len() >= self.max_history_length {
+            self.pop_oldest();
+        }
+        self.history
+            .push_back(DiagnosticMeasurement { time, value });
+    }
+
+    fn pop_oldest(&mut self) {
+        self.history.pop_front();
+    }
+
+    /// Accumulates `delta` into a counter that is recorded as a single
+    /// measurement in [`CoreSystemPhase::Last`].
+    #[inline]
+    pub fn increment(&mut self, delta: f64) {
+        if self.enabled {
+            *self.pending.get_or_insert(0.0) += delta;
+        }
+    }
+
+    fn flush(&mut self, time: Instant) {
+        if let Some(value) = self.pending.take() {
+            self.add_measurement_at(time, value);
+        }
+    }
+
+    /// Returns the most recent measurement.
+    #[inline]
+    pub fn measurement(&self) -> Option<&DiagnosticMeasurement> {
+        self.history.back()
+    }
+
+    /// Returns the value of the most recent measurement.
+    #[inline]
+    pub fn value(&self) -> Option<f64> {
+        self.measurement().map(|m| m.value)
+    }
+
+    /// Returns the average of all measurements in the history.
+    pub fn average(&self) -> Option<f64> {
+        if self.history.is_empty() {
+            None
+        } else {
+            // re-computed over the window (a running sum accumulates
+            // rounding errors)
+            Some(self.values().sum::<f64>() / self.history.len() as f64)
+        }
+    }
+
+    /// Returns the time between the oldest and the most recent measurement.
+    pub fn duration(&self) -> Option<Duration> {
+        let oldest = self.history.front()?;
+        let newest = self.history.back()?;
+        Some(newest.time.saturating_duration_since(oldest.time))
+    }
+
+    #[inline]
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    #[inline]
+    pub fn max_history_length(&self) -> usize {
+        self.max_history_length
+    }
+
+    #[inline]
+    pub fn measurements(&self) -> impl DoubleEndedIterator<Item = &DiagnosticMeasurement> + '_ {
+        self.history.iter()
+    }
+
+    #[inline]
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = f64> + '_ {
+        self.history.iter().map(|m| m.value)
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+}
+
+/// Resource that stores all registered [`Diagnostic`]s.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    diagnostics: BTreeMap<DiagnosticId, Diagnostic>,
+}
+
+impl Diagnostics {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a new diagnostic.
+    ///
+    /// An existing diagnostic with the same label is replaced.
+    pub fn add(&mut self, diagnostic: Diagnostic) -> &mut Diagnostic {
+        match self.diagnostics.entry(diagnostic.id) {
+            btree_map::Entry::Occupied(mut entry) => {
+                entry.insert(diagnostic);
+                entry.into_mut()
+            }
+            btree_map::Entry::Vacant(entry) => entry.insert(diagnostic),
+        }
+    }
+
+    /// Returns the diagnostic with the given label, or registers a new one
+    /// with default settings.
+    pub fn get_or_add(&mut self, label: impl DiagnosticLabel) -> &mut Diagnostic {
+        let id = label.as_label();
+        self.diagnostics
+            .entry(id)
+            .or_insert_with(|| Diagnostic::new(id))
+    }
+
+    #[inline]
+    pub fn get(&self, label: impl DiagnosticLabel) -> Option<&Diagnostic> {
+        self.diagnostics.get(&label.as_label())
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, label: impl DiagnosticLabel) -> Option<&mut Diagnostic> {
+        self.diagnostics.get_mut(&label.as_label())
+    }
+
+    #[inline]
+    pub fn remove(&mut self, label: impl DiagnosticLabel) -> Option<Diagnostic> {
+        self.diagnostics.remove(&label.as_label())
+    }
+
+    /// Records a measurement (e.g. a gauge like the number of entities).
+    ///
+    /// The diagnostic is registered with default settings, when it doesn't
+    /// exist.
+    #[inline]
+    pub fn add_measurement(&mut self, label: impl DiagnosticLabel, value: f64) {
+        self.get_or_add(label).add_measurement(value)
+    }
+
+    /// Increments a counter (e.g. the number of draw calls).
+    ///
+    /// All increments of a frame are recorded as a single measurement.
+    /// The diagnostic is registered with default settings, when it doesn't
+    /// exist.
+    #[inline]
+    pub fn increment(&mut self, label: impl DiagnosticLabel, delta: f64) {
+        self.get_or_add(label).increment(delta)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> + '_ {
+        self.diagnostics.values()
+    }
+
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Diagnostic> + '_ {
+        self.diagnostics.values_mut()
+    }
+
+    /// Records the accumulated counters as measurements.
+    pub fn flush(&mut self) {
+        let now = Instant::now();
+        for diagnostic in self.diagnostics.values_mut() {
+            diagnostic.flush(now);
+        }
+    }
+}
+
+/// Installs the [`Diagnostics`] resource and the system that flushes counters.
+pub struct DiagnosticsModule;
+
+impl Module for DiagnosticsModule {
+    fn install_resources(self, resources: &mut Resources) {
+        resources.init::<Diagnostics>();
+    }
+
+    fn install_systems(schedule: &mut Schedule) {
+        schedule.add_phase_dependency(DiagnosticsPhase::Output, CoreSystemPhase::Update);
+        schedule
+            .add_system(Diagnostics::flush)
+            .into_phase(CoreSystemPhase::Last);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    define_label_enum! {
+        enum TestDiagnostic: DiagnosticLabel {
+            Gauge,
+            Counter,
+        }
+    }
+
+    #[test]
+    fn test_history() {
+        let mut diagnostic = Diagnostic::new(TestDiagnostic::Gauge).with_max_history_length(3);
+        assert_eq!(None, diagnostic.value());
+        assert_eq!(None, diagnostic.average());
+        for value in [1.0, 2.0, 3.0, 4.0] {
+            diagnostic.add_measurement(value);
+        }
+        assert_eq!(3, diagnostic.history_len());
+        assert_eq!(Some(4.0), diagnostic.value());
+        assert_eq!(Some(3.0), diagnostic.average());
+        assert_eq!(vec![2.0, 3.0, 4.0], diagnostic.values().collect::<Vec<_>>());
+
+        diagnostic.set_enabled(false);
+        diagnostic.add_measurement(10.0);
+        assert_eq!(Some(4.0), diagnostic.value());
+
+        diagnostic.clear_history();
+        assert_eq!(None, diagnostic.average());
+    }
+
+    #[test]
+    fn test_average_does_not_drift() {
+        let mut diagnostic = Diagnostic::new(TestDiagnostic::Gauge).with_max_history_length(2);
+        for value in [1e16, 1.0, 1.0] {
+            diagnostic.add_measurement(value);
+        }
+        assert_eq!(Some(1.0), diagnostic.average());
+    }
+
+    #[test]
+    fn test_counter() {
+        let mut resources = Resources::new();
+        resources.install(DiagnosticsModule);
+        let mut schedule = resources.remove::<Schedule>().unwrap();
+        schedule
+            .add_system(|diagnostics: &mut Diagnostics| {
+                diagnostics.increment(TestDiagnostic::Counter, 1.0);
+                diagnostics.increment(TestDiagnostic::Counter, 2.0);
+                diagnostics.add_measurement(TestDiagnostic::Gauge, 5.0);
+            })
+            .into_phase(CoreSystemPhase::Update);
+        schedule.run(&mut resources);
+        schedule.run(&mut resources);
+        resources.insert_again(schedule);
+
+        let diagnostics = resources.borrow_res::<Diagnostics>().unwrap();
+        let counter = diagnostics.get(TestDiagnostic::Counter).unwrap();
+        assert_eq!(vec![3.0, 3.0], counter.values().collect::<Vec<_>>());
+        let gauge = diagnostics.get(TestDiagnostic::Gauge).unwrap();
+        assert_eq!(Some(5.0), gauge.average());
+        assert_eq!(2, gauge.history_len());
+    }
+}
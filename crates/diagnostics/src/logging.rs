@@ -0,0 +1,185 @@
+use std::time::{Duration, Instant};
+
+use pulz_schedule::prelude::*;
+
+use crate::{
+    Diagnostic, DiagnosticId, DiagnosticLabel, Diagnostics, DiagnosticsModule, DiagnosticsPhase,
+};
+
+/// Periodically writes the averages of all enabled diagnostics to the log.
+pub struct LogDiagnosticsModule {
+    /// The interval between two log outputs
+    pub wait_duration: Duration,
+    /// Only log these diagnostics. All diagnostics are logged when `None`.
+    pub filter: Option<Vec<DiagnosticId>>,
+}
+
+impl Default for LogDiagnosticsModule {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            wait_duration: Duration::from_secs(1),
+            filter: None,
+        }
+    }
+}
+
+impl LogDiagnosticsModule {
+    #[inline]
+    pub fn new(wait_duration: Duration) -> Self {
+        Self {
+            wait_duration,
+            filter: None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_filter(mut self, labels: impl IntoIterator<Item = impl DiagnosticLabel>) -> Self {
+        self.filter = Some(labels.into_iter().map(|l| l.as_label()).collect());
+        self
+    }
+}
+
+/// Resource that controls the log output of [`LogDiagnosticsModule`].
+pub struct LogDiagnosticsState {
+    pub wait_duration: Duration,
+    pub filter: Option<Vec<DiagnosticId>>,
+    last_output: Option<Instant>,
+}
+
+impl LogDiagnosticsState {
+    fn format_diagnostic(diagnostic: &Diagnostic) -> Option<String> {
+        let value = diagnostic.value()?;
+        let average = diagnostic.average().unwrap_or(value);
+        Some(format!(
+            "{:<32}: {:>12.4}{:<4} (avg {:.4}{})",
+            diagnostic.name(),
+            value,
+            diagnostic.suffix(),
+            average,
+            diagnostic.suffix(),
+        ))
+    }
+
+    /// Returns `true`, when the next output is due at the time `now`.
+    fn is_due(&mut self, now: Instant) -> bool {
+        match self.last_output {
+            Some(last_output)
+                if now.saturating_duration_since(last_output) < self.wait_duration =>
+            {
+                false
+            }
+            None => {
+                // don't log in the first frame; there is nothing to report yet
+                self.last_output = Some(now);
+                false
+            }
+            _ => {
+                self.last_output = Some(now);
+                true
+            }
+        }
+    }
+
+    /// The enabled diagnostics, that pass the filter.
+    fn selected<'a>(&'a self, diagnostics: &'a Diagnostics) -> Vec<&'a Diagnostic> {
+        if let Some(filter) = &self.filter {
+            filter
+                .iter()
+                .filter_map(|&id| diagnostics.get(id))
+                .filter(|d| d.is_enabled())
+                .collect()
+        } else {
+            diagnostics.iter().filter(|d| d.is_enabled()).collect()
+        }
+    }
+
+    fn update(&mut self, diagnostics: &Diagnostics) {
+        if !self.is_due(Instant::now()) {
+            return;
+        }
+        for diagnostic in self.selected(diagnostics) {
+            if let Some(line) = Self::format_diagnostic(diagnostic) {
+                log::info!(target: "pulz_diagnostics", "{line}");
+            }
+        }
+    }
+}
+
+impl Module for LogDiagnosticsModule {
+    fn install_modules(&self, resources: &mut Resources) {
+        resources.install(DiagnosticsModule);
+    }
+
+    fn install_resources(self, resources: &mut Resources) {
+        resources.insert(LogDiagnosticsState {
+            wait_duration: self.wait_duration,
+            filter: self.filter,
+            last_output: None,
+        });
+    }
+
+    fn install_systems(schedule: &mut Schedule) {
+        schedule
+            .add_system(LogDiagnosticsState::update)
+            .into_phase(DiagnosticsPhase::Output);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pulz_schedule::define_label_enum;
+
+    use super::*;
+
+    define_label_enum! {
+        enum TestDiagnostic: DiagnosticLabel {
+            Logged,
+            Filtered,
+            Disabled,
+        }
+    }
+
+    #[test]
+    fn test_log_diagnostics_state() {
+        let mut resources = Resources::new();
+        resources.install(
+            LogDiagnosticsModule::new(Duration::from_millis(100))
+                .with_filter([TestDiagnostic::Logged, TestDiagnostic::Disabled]),
+        );
+        {
+            let mut diagnostics = resources.borrow_res_mut::<Diagnostics>().unwrap();
+            diagnostics.add(Diagnostic::new(TestDiagnostic::Logged).with_suffix("ms"));
+            diagnostics.add_measurement(TestDiagnostic::Logged, 1.5);
+            diagnostics.add_measurement(TestDiagnostic::Logged, 2.5);
+            diagnostics.add_measurement(TestDiagnostic::Filtered, 1.0);
+            let disabled = diagnostics.get_or_add(TestDiagnostic::Disabled);
+            disabled.add_measurement(1.0);
+            disabled.set_enabled(false);
+        }
+        let diagnostics = resources.borrow_res::<Diagnostics>().unwrap();
+        let mut state = resources.borrow_res_mut::<LogDiagnosticsState>().unwrap();
+
+        let selected: Vec<_> = state
+            .selected(&diagnostics)
+            .iter()
+            .map(|d| d.id())
+            .collect();
+        assert_eq!(vec![TestDiagnostic::Logged.as_label()], selected);
+
+        let logged = diagnostics.get(TestDiagnostic::Logged).unwrap();
+        let line = LogDiagnosticsState::format_diagnostic(logged).unwrap();
+        assert!(line.starts_with("TestDiagnostic::Logged "), "{line}");
+        assert!(line.contains("2.5000ms"), "{line}");
+        assert!(line.ends_with("(avg 2.0000ms)"), "{line}");
+
+        // not logged in the first frame, then once per `wait_duration`
+        let start = Instant::now();
+        assert!(!state.is_due(start));
+        assert!(!state.is_due(start + Duration::from_millis(50)));
+        assert!(state.is_due(start + Duration::from_millis(100)));
+        assert!(!state.is_due(start + Duration::from_millis(150)));
+        assert!(state.is_due(start + Duration::from_millis(200)));
+    }
+}
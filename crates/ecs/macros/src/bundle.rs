@@ -1,19 +1,79 @@
-use darling::Result;
+use darling::{Error, Result};
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::DeriveInput;
+use syn::{parse_quote, Data, DeriveInput, Index, Member};
 
 use crate::utils::resolve_crate;
 
 pub fn derive_bundle(input: DeriveInput) -> Result<TokenStream> {
-    let ident = input.ident;
-    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::custom("Bundle can only be derived for structs").with_span(&input));
+    };
 
+    let ident = &input.ident;
     let crate_ecs = resolve_crate("pulz-ecs")?;
 
+    let members: Vec<Member> = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        })
+        .collect();
+    let field_types: Vec<_> = data.fields.iter().map(|field| &field.ty).collect();
+
+    let mut generics = input.generics.clone();
+    {
+        let where_clause = generics.make_where_clause();
+        for ty in &field_types {
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: #crate_ecs::component::Bundle));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let contains_impls = field_types.iter().enumerate().map(|(i, ty)| {
+        let mut generics = generics.clone();
+        generics.params.push(parse_quote!(__T));
+        generics.params.push(parse_quote!(__I));
+        {
+            let where_clause = generics.make_where_clause();
+            where_clause
+                .predicates
+                .push(parse_quote!(__T: #crate_ecs::component::Component));
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: #crate_ecs::component::Contains<__T, __I>));
+        }
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        quote! {
+            impl #impl_generics #crate_ecs::component::Contains<__T, (#crate_ecs::component::BundleIndex<#i>, __I)>
+                for #ident #ty_generics #where_clause
+            {
+            }
+        }
+    });
+
     Ok(quote! {
         impl #impl_generics #crate_ecs::component::Bundle for #ident #ty_generics #where_clause {
-            // TODO: implement
+            #[inline]
+            fn insert_into(self, entity: &mut #crate_ecs::entity::EntityMut<'_>) {
+                #(
+                    #crate_ecs::component::Bundle::insert_into(self.#members, entity);
+                )*
+            }
+
+            #[inline]
+            fn is_contained_in(entity: &#crate_ecs::entity::EntityRef<'_>) -> bool {
+                true #(
+                    && <#field_types as #crate_ecs::component::Bundle>::is_contained_in(entity)
+                )*
+            }
         }
+
+        #(#contains_impls)*
     })
 }
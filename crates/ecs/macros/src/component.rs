@@ -1,10 +1,10 @@
 use darling::{
-    util::{Flag, SpannedValue},
-    Error, FromDeriveInput, Result,
+    util::{Flag, PathList, SpannedValue},
+    Error, FromDeriveInput, FromMeta, Result,
 };
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{parse_quote, DeriveInput, Path};
+use syn::{parse_quote, DeriveInput, Meta, Path};
 
 use crate::utils::resolve_crate;
 
@@ -24,6 +24,8 @@ pub fn derive_component(input: DeriveInput) -> Result<TokenStream> {
         }
     } else if args.sparse.is_present() {
         parse_quote!(#crate_ecs::storage::SparseStorage)
+    } else if args.tag.is_present() {
+        parse_quote!(#crate_ecs::storage::TagStorage)
     } else {
         parse_quote!(#crate_ecs::storage::ArchetypeStorage)
     };
@@ -31,9 +33,48 @@ pub fn derive_component(input: DeriveInput) -> Result<TokenStream> {
     if args.tracked.is_present() {
         storage = parse_quote!(#crate_ecs::storage::Tracked<#storage>);
     }
+    let insert_required = if args.requires.is_empty() {
+        quote!()
+    } else {
+        let requires = args.requires.iter();
+        quote! {
+            fn insert_required(entity: &mut #crate_ecs::entity::EntityMut<'_>) {
+                #(
+                    if !entity.contains::<#requires>() {
+                        entity.insert(<#requires as ::std::default::Default>::default());
+                    }
+                )*
+            }
+        }
+    };
+    let on_despawn = match &args.on_despawn {
+        None => quote!(),
+        Some(path) if path.is_ident("despawn_referenced") => quote! {
+            const ON_DESPAWN: ::std::option::Option<#crate_ecs::component::OnDespawnFn> =
+                ::std::option::Option::Some(#crate_ecs::component::despawn_referenced::<Self>);
+        },
+        Some(path) => quote! {
+            const ON_DESPAWN: ::std::option::Option<#crate_ecs::component::OnDespawnFn> =
+                ::std::option::Option::Some(#path);
+        },
+    };
+    let on_clone = match &args.clone {
+        None => quote!(),
+        Some(CloneArg::Derived) => quote! {
+            const ON_CLONE: ::std::option::Option<#crate_ecs::component::OnCloneFn<Self>> =
+                ::std::option::Option::Some(<Self as ::std::clone::Clone>::clone);
+        },
+        Some(CloneArg::Custom(path)) => quote! {
+            const ON_CLONE: ::std::option::Option<#crate_ecs::component::OnCloneFn<Self>> =
+                ::std::option::Option::Some(#path);
+        },
+    };
     Ok(quote! {
         impl #impl_generics #crate_ecs::component::Component for #ident #ty_generics #where_clause {
             type Storage = #storage;
+            #insert_required
+            #on_despawn
+            #on_clone
         }
     })
 }
@@ -47,14 +88,42 @@ pub fn derive_component(input: DeriveInput) -> Result<TokenStream> {
 )]
 pub struct ComponentStructArgs {
     sparse: Flag,
+    tag: Flag,
     tracked: Flag,
     storage: SpannedValue<Option<Path>>,
+    requires: PathList,
+    on_despawn: Option<Path>,
+    clone: Option<CloneArg>,
+}
+
+/// `#[component(clone)]` or `#[component(clone = path)]`
+pub enum CloneArg {
+    Derived,
+    Custom(Path),
+}
+
+impl FromMeta for CloneArg {
+    fn from_word() -> Result<Self> {
+        Ok(Self::Derived)
+    }
+
+    fn from_meta(item: &Meta) -> Result<Self> {
+        match item {
+            Meta::Path(_) => Self::from_word(),
+            _ => Path::from_meta(item).map(Self::Custom),
+        }
+    }
 }
 
 impl ComponentStructArgs {
     fn validate(self) -> Result<Self> {
-        if self.sparse.is_present() && self.storage.is_some() {
-            const MSG: &str = "either provide `sparse` or `storage`, but not both!";
+        let storages = [
+            self.sparse.is_present(),
+            self.tag.is_present(),
+            self.storage.is_some(),
+        ];
+        if storages.into_iter().filter(|s| *s).count() > 1 {
+            const MSG: &str = "provide only one of `sparse`, `tag` or `storage`!";
             return Err(Error::custom(MSG));
         }
         Ok(self)
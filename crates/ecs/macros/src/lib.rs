@@ -6,6 +6,20 @@ mod utils;
 mod bundle;
 mod component;
 
+/// Implements `Component` for a struct or enum.
+///
+/// Supported attributes:
+///
+/// * `#[component(sparse)]`, `#[component(tag)]` or
+///   `#[component(storage = "...")]`: selects the storage of the component
+/// * `#[component(tracked)]`: tracks removals of the component
+/// * `#[component(requires(A, B))]`: inserts `A::default()` and
+///   `B::default()`, when they are missing
+/// * `#[component(on_despawn = ...)]`: sets a cleanup policy
+/// * `#[component(clone)]` or `#[component(clone = ...)]`: copies the
+///   component when the entity is cloned. Cloning is opt-in: components
+///   without this attribute are skipped by `clone_entity` and
+///   `copy_entity`, even when they implement `Clone`.
 #[proc_macro_derive(Component, attributes(component))]
 pub fn derive_component(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
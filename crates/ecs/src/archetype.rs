@@ -68,6 +68,7 @@ impl Archetype {
 pub struct Archetypes {
     archetypes: Vec<Archetype>,
     archetype_ids: BTreeMap<ComponentSet, ArchetypeId>,
+    generation: usize,
 }
 
 impl Default for Archetypes {
@@ -75,6 +76,7 @@ impl Default for Archetypes {
         let mut archetypes = Self {
             archetypes: Vec::new(),
             archetype_ids: BTreeMap::new(),
+            generation: 0,
         };
 
         // always add the EMPTY archetype at index 0
@@ -104,6 +106,13 @@ impl Archetypes {
         self.archetypes.is_empty()
     }
 
+    /// Incremented every time empty archetypes were removed and the remaining
+    /// archetypes got new ids.
+    #[inline]
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
     #[inline]
     pub fn iter(&self) -> impl Iterator<Item = &Archetype> {
         self.archetypes.iter()
@@ -134,6 +143,47 @@ impl Archetypes {
         slice_get_disjoint_array_mut(&mut self.archetypes, indices)
     }
 
+    /// Releases the unused capacity of all archetypes.
+    pub(crate) fn shrink_to_fit(&mut self) {
+        for archetype in &mut self.archetypes {
+            archetype.entities.shrink_to_fit();
+        }
+    }
+
+    /// Removes all empty archetypes (except the [`ArchetypeId::EMPTY`]
+    /// archetype) and assigns new ids to the remaining ones.
+    ///
+    /// Returns the new id for every old archetype id (`None` for the removed
+    /// archetypes), or `None` when there was nothing to remove.
+    pub(crate) fn remove_empty(&mut self) -> Option<Vec<Option<ArchetypeId>>> {
+        if !self.archetypes.iter().skip(1).any(Archetype::is_empty) {
+            return None;
+        }
+        let mut remap = Vec::with_capacity(self.archetypes.len());
+        let mut next_index = 0;
+        self.archetypes.retain_mut(|archetype| {
+            if archetype.id != ArchetypeId::EMPTY && archetype.is_empty() {
+                remap.push(None);
+                false
+            } else {
+                archetype.id = ArchetypeId::new(next_index);
+                next_index += 1;
+                remap.push(Some(archetype.id));
+                true
+            }
+        });
+        self.archetype_ids.retain(|_, id| {
+            if let Some(new_id) = remap[id.index()] {
+                *id = new_id;
+                true
+            } else {
+                false
+            }
+        });
+        self.generation += 1;
+        Some(remap)
+    }
+
     pub(crate) fn get_or_insert(&mut self, dense_ids: ComponentSet) -> ArchetypeId {
         let archetypes = &mut self.archetypes;
         *self
@@ -277,6 +327,17 @@ fn slice_get_disjoint_array_mut<const N: usize, T>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::prelude::*;
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+    struct A(usize);
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+    #[component(sparse)]
+    struct B(usize);
+
+    #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Component)]
+    struct Visibility(bool);
 
     #[test]
     fn empty_archetype_should_have_empty_id() {
@@ -287,4 +348,82 @@ mod tests {
         );
         assert_eq!(ArchetypeId::EMPTY, archetypes[ArchetypeId::EMPTY].id);
     }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut resources = Resources::new();
+        let mut world = resources.world_mut();
+        let entities: Vec<_> = (0..1000)
+            .map(|i| world.spawn().insert(A(i)).insert(B(i)).id())
+            .collect();
+        let kept = world.spawn().insert(A(1000)).insert(B(1000)).id();
+        for &entity in &entities {
+            assert!(world.despawn(entity));
+        }
+
+        let archetype = world.entities().get(kept).unwrap().archetype_id;
+        assert!(world.archetypes()[archetype].entities.capacity() >= 1000);
+        world.shrink_to_fit();
+        assert!(world.archetypes()[archetype].entities.capacity() < 1000);
+
+        let ent = world.entity(kept).unwrap();
+        assert_eq!(Some(A(1000)), ent.borrow::<A>().as_deref().copied());
+
+        // storages are still usable after shrinking
+        let e = world.spawn().insert(A(1)).insert(B(1)).id();
+        let ent = world.entity(e).unwrap();
+        assert_eq!(Some(A(1)), ent.borrow::<A>().as_deref().copied());
+        assert_eq!(Some(B(1)), ent.borrow::<B>().as_deref().copied());
+    }
+
+    #[test]
+    fn test_shrink_to_fit_removes_empty_archetypes() {
+        let mut resources = Resources::new();
+        let mut world = resources.world_mut();
+        let e1 = world.spawn().insert(Visibility(true)).id();
+        let e2 = world.spawn().insert(A(2)).insert(Visibility(false)).id();
+        let e3 = world.spawn().insert(A(3)).id();
+        drop(world);
+
+        let values = |resources: &mut Resources| {
+            let mut query = resources.query::<&A>();
+            let mut values: Vec<_> = query.iter().map(|a| a.0).collect();
+            values.sort_unstable();
+            values
+        };
+        assert_eq!(vec![2, 3], values(&mut resources));
+
+        let mut world = resources.world_mut();
+        assert!(world.despawn(e2));
+        let num_archetypes = world.archetypes().len();
+        let generation = world.archetypes().generation();
+        world.shrink_to_fit();
+        assert_eq!(num_archetypes - 1, world.archetypes().len());
+        assert_ne!(generation, world.archetypes().generation());
+
+        // a second call has nothing to remove
+        world.shrink_to_fit();
+        assert_eq!(num_archetypes - 1, world.archetypes().len());
+
+        for archetype in world.archetypes().iter() {
+            for &entity in archetype.entities() {
+                assert_eq!(archetype.id(), world.entities()[entity].archetype_id);
+            }
+        }
+        let ent1 = world.entity(e1).unwrap();
+        assert_eq!(
+            Some(Visibility(true)),
+            ent1.borrow::<Visibility>().as_deref().copied()
+        );
+        let ent3 = world.entity(e3).unwrap();
+        assert_eq!(Some(A(3)), ent3.borrow::<A>().as_deref().copied());
+        drop(world);
+
+        // the cached query state picks up the new archetype ids
+        assert_eq!(vec![3], values(&mut resources));
+        let mut world = resources.world_mut();
+        world.spawn().insert(A(4)).insert(Visibility(true));
+        drop(world);
+        assert_eq!(vec![3, 4], values(&mut resources));
+    }
 }
@@ -8,6 +8,11 @@ use std::{
 };
 
 use crate::{
+    entity::Entity,
+    entity_ref::{
+        clone_component, transfer_component, CloneComponentFn, DespawnContext, EntityMut,
+        EntityRef, TransferComponentFn,
+    },
     resource::{Res, ResMut, ResourceId},
     storage::{AnyStorage, Storage},
 };
@@ -16,14 +21,156 @@ pub type Ref<'w, T> = Res<'w, T>;
 pub type RefMut<'w, T> = ResMut<'w, T>;
 
 use pulz_bitset::BitSet;
-pub use pulz_ecs_macros::Component;
+pub use pulz_ecs_macros::{Bundle, Component};
 use pulz_schedule::meta::any_cast_mut_unchecked;
 
 pub trait Component: Send + Sync + 'static {
     type Storage: Storage<Component = Self>;
+
+    /// Inserts the components required by this component, when they are
+    /// missing on the entity.
+    ///
+    /// Called by [`EntityMut::insert`] after this component was inserted.
+    /// The derive-macro implements this for `#[component(requires(A, B))]`
+    /// by inserting `A::default()` and `B::default()`.
+    #[inline]
+    fn insert_required(_entity: &mut EntityMut<'_>) {}
+
+    /// The cleanup policy, that is executed before an entity with this
+    /// component is despawned.
+    ///
+    /// The derive-macro sets this with `#[component(on_despawn = ...)]`:
+    /// `despawn_referenced` despawns all entities referenced by this component
+    /// (see [`despawn_referenced`]), any other path is used as a custom
+    /// cleanup function.
+    const ON_DESPAWN: Option<OnDespawnFn> = None;
+
+    /// The function, that copies this component, when an entity is cloned
+    /// (see [`WorldMut::clone_entity`](crate::world::WorldMut::clone_entity)).
+    ///
+    /// Components without a clone function are skipped. The derive-macro
+    /// sets this with `#[component(clone)]` (using the `Clone` impl), or with
+    /// `#[component(clone = ...)]` for a custom function (e.g. for handles,
+    /// that need a deep copy).
+    const ON_CLONE: Option<OnCloneFn<Self>> = None;
+}
+
+/// A cleanup function (see [`Component::ON_DESPAWN`]).
+pub type OnDespawnFn = fn(&mut DespawnContext<'_>);
+
+/// A clone function (see [`Component::ON_CLONE`]).
+pub type OnCloneFn<T> = fn(&T) -> T;
+
+/// A component, that references other entities (e.g. the children of an
+/// entity).
+pub trait EntityReferences {
+    fn visit_entities(&self, visitor: &mut dyn FnMut(Entity));
+}
+
+/// Cleanup policy, that despawns all entities referenced by the component
+/// `T` of the despawned entity (for example the children of an entity).
+///
+/// The cleanup policies of the referenced entities are executed too.
+pub fn despawn_referenced<T>(context: &mut DespawnContext<'_>)
+where
+    T: Component + EntityReferences,
+{
+    let mut referenced = Vec::new();
+    if let Some(value) = context.entity().borrow::<T>() {
+        value.visit_entities(&mut |e| referenced.push(e));
+    }
+    for entity in referenced {
+        context.despawn(entity);
+    }
+}
+
+/// A set of components, that are inserted into an entity together.
+///
+/// Every component is a bundle, and so are tuples of bundles. Structs can
+/// derive this trait with `#[derive(Bundle)]`, when all their fields are
+/// bundles. Bundles are spawned with
+/// [`WorldMut::spawn_bundle`](crate::world::WorldMut::spawn_bundle), which
+/// returns a [`TypedEntity`](crate::entity::TypedEntity).
+pub trait Bundle: Send + Sync + 'static {
+    /// Inserts all components of this bundle into the entity.
+    fn insert_into(self, entity: &mut EntityMut<'_>);
+
+    /// Returns `true`, when the entity contains all components of this bundle.
+    fn is_contained_in(entity: &EntityRef<'_>) -> bool;
+}
+
+/// Implemented by bundles, that contain the component `T`.
+///
+/// `I` describes the path to the component inside of the bundle (see
+/// [`BundleIndex`]). It is always inferred, and only exists for keeping the
+/// implementations for the different fields of a bundle apart.
+pub trait Contains<T: Component, I>: Bundle {}
+
+/// The position of a field inside of a bundle (see [`Contains`]).
+#[doc(hidden)]
+pub struct BundleIndex<const N: usize>;
+
+impl<T: Component> Bundle for T {
+    #[inline]
+    fn insert_into(self, entity: &mut EntityMut<'_>) {
+        entity.insert(self);
+    }
+
+    #[inline]
+    fn is_contained_in(entity: &EntityRef<'_>) -> bool {
+        entity.contains::<T>()
+    }
+}
+
+impl<T: Component> Contains<T, ()> for T {}
+
+impl Bundle for () {
+    #[inline]
+    fn insert_into(self, _entity: &mut EntityMut<'_>) {}
+
+    #[inline]
+    fn is_contained_in(_entity: &EntityRef<'_>) -> bool {
+        true
+    }
+}
+
+macro_rules! impl_bundle_tuple {
+    ($($name:ident : $index:tt),+) => {
+        impl<$($name: Bundle),+> Bundle for ($($name,)+) {
+            #[inline]
+            fn insert_into(self, entity: &mut EntityMut<'_>) {
+                $(self.$index.insert_into(entity);)+
+            }
+
+            #[inline]
+            fn is_contained_in(entity: &EntityRef<'_>) -> bool {
+                $($name::is_contained_in(entity))&&+
+            }
+        }
+
+        impl_bundle_tuple!(@contains [$($name),+] $($name : $index),+);
+    };
+    (@contains $all:tt $($name:ident : $index:tt),+) => {
+        $(impl_bundle_tuple!(@contains_one $all $name : $index);)+
+    };
+    (@contains_one [$($all:ident),+] $name:ident : $index:tt) => {
+        impl<__T, __I, $($all: Bundle),+> Contains<__T, (BundleIndex<$index>, __I)> for ($($all,)+)
+        where
+            __T: Component,
+            $name: Contains<__T, __I>,
+        {
+        }
+    };
 }
 
-pub trait Bundle {}
+impl_bundle_tuple!(A: 0);
+impl_bundle_tuple!(A: 0, B: 1);
+impl_bundle_tuple!(A: 0, B: 1, C: 2);
+impl_bundle_tuple!(A: 0, B: 1, C: 2, D: 3);
+impl_bundle_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4);
+impl_bundle_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+impl_bundle_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+impl_bundle_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
 
 #[repr(transparent)]
 pub struct ComponentId<T = crate::Void>(usize, PhantomData<fn() -> T>);
@@ -55,7 +202,7 @@ impl<T> PartialEq<Self> for ComponentId<T> {
 impl<T> PartialOrd<Self> for ComponentId<T> {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.0.partial_cmp(&other.0)
+        Some(self.cmp(other))
     }
 }
 impl<T> Hash for ComponentId<T> {
@@ -99,6 +246,9 @@ pub struct ComponentDetails {
     pub(crate) archetype_component: bool,
     pub(crate) storage_id: ResourceId,
     pub(crate) storage_downcast_mut: unsafe fn(&mut dyn Any) -> &mut dyn AnyStorage,
+    pub(crate) transfer: TransferComponentFn,
+    pub(crate) on_despawn: Option<OnDespawnFn>,
+    pub(crate) clone: Option<CloneComponentFn>,
 }
 
 impl ComponentDetails {
@@ -121,6 +271,8 @@ impl ComponentDetails {
 pub struct Components {
     pub(crate) components: Vec<ComponentDetails>,
     by_type_id: BTreeMap<TypeId, ComponentId>,
+    // components with an `on_despawn` hook
+    pub(crate) despawn_hooks: Vec<ComponentId>,
 }
 
 impl Components {
@@ -129,6 +281,7 @@ impl Components {
         Self {
             components: Vec::new(),
             by_type_id: BTreeMap::new(),
+            despawn_hooks: Vec::new(),
         }
     }
 
@@ -137,7 +290,7 @@ impl Components {
     where
         T: Component,
     {
-        let type_id = std::any::TypeId::of::<T>();
+        let type_id = TypeId::of::<T>();
         self.by_type_id
             .get(&type_id)
             .copied()
@@ -167,7 +320,7 @@ impl Components {
     where
         T: Component,
     {
-        let type_id = std::any::TypeId::of::<T>();
+        let type_id = TypeId::of::<T>();
         let components = &mut self.components;
         match self.by_type_id.entry(type_id) {
             Entry::Vacant(entry) => {
@@ -180,7 +333,17 @@ impl Components {
                     archetype_component: !<T::Storage as Storage>::SPARSE,
                     storage_id: storage_id.untyped().typed(),
                     storage_downcast_mut: any_cast_mut_unchecked::<dyn AnyStorage, T::Storage>,
+                    transfer: transfer_component::<T>,
+                    on_despawn: T::ON_DESPAWN,
+                    clone: if T::ON_CLONE.is_some() {
+                        Some(clone_component::<T>)
+                    } else {
+                        None
+                    },
                 });
+                if T::ON_DESPAWN.is_some() {
+                    self.despawn_hooks.push(id);
+                }
                 entry.insert(id);
                 Ok(id.typed())
             }
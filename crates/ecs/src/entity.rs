@@ -1,12 +1,147 @@
-use slotmap::{new_key_type, SlotMap};
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
 
-use crate::archetype::ArchetypeId;
-pub use crate::entity_ref::{EntityMut, EntityRef};
+use slotmap::{new_key_type, Key, KeyData, SlotMap};
+
+pub use crate::entity_ref::{DespawnContext, EntityMut, EntityRef};
+use crate::{archetype::ArchetypeId, component::Bundle};
 
 new_key_type! {
     pub struct Entity;
 }
 
+impl Entity {
+    /// The index of the slot of this entity.
+    #[inline]
+    pub fn index(self) -> u32 {
+        split_key(self.data()).0
+    }
+
+    /// The generation of the slot of this entity.
+    ///
+    /// The generation changes, when the slot of a despawned entity is reused.
+    #[inline]
+    pub fn generation(self) -> u32 {
+        split_key(self.data()).1
+    }
+
+    /// Encodes this entity into a stable `u64` (generation in the high and
+    /// index in the low 32 bits).
+    ///
+    /// Can be decoded with [`Entity::from_bits`] (e.g. for networking).
+    #[inline]
+    pub fn to_bits(self) -> u64 {
+        let (index, generation) = split_key(self.data());
+        (u64::from(generation) << 32) | u64::from(index)
+    }
+
+    /// Decodes an entity, that was encoded with [`Entity::to_bits`].
+    ///
+    /// Returns `None`, when the value can not be produced by `to_bits`.
+    #[inline]
+    pub fn from_bits(bits: u64) -> Option<Self> {
+        let index = bits as u32;
+        let generation = (bits >> 32) as u32;
+        // slot 0 is never used, and the generation of an existing entity is
+        // always odd
+        if index == 0 || generation & 1 == 0 {
+            return None;
+        }
+        Some(join_key(index, generation).into())
+    }
+}
+
+// `slotmap` has no accessors for the index and the version of a key, so these
+// two functions are the only place, that knows how they are stored in
+// `KeyData::as_ffi` (checked by `tests::test_entity_bits_layout`).
+#[inline]
+fn split_key(key: KeyData) -> (u32, u32) {
+    let ffi = key.as_ffi();
+    (ffi as u32, (ffi >> 32) as u32)
+}
+
+#[inline]
+fn join_key(index: u32, generation: u32) -> KeyData {
+    KeyData::from_ffi((u64::from(generation) << 32) | u64::from(index))
+}
+
+impl From<Entity> for u64 {
+    #[inline]
+    fn from(entity: Entity) -> Self {
+        entity.to_bits()
+    }
+}
+
+/// An [`Entity`], that was spawned with the bundle `B`.
+///
+/// The components of the bundle can be accessed without unwrapping with
+/// [`World::component`](crate::world::World::component) and
+/// [`WorldMut::component_mut`](crate::world::WorldMut::component_mut).
+/// Created by [`WorldMut::spawn_bundle`](crate::world::WorldMut::spawn_bundle),
+/// or checked with [`WorldMut::typed_entity`](crate::world::WorldMut::typed_entity).
+pub struct TypedEntity<B> {
+    entity: Entity,
+    _phantom: PhantomData<fn() -> B>,
+}
+
+impl<B: Bundle> TypedEntity<B> {
+    #[inline]
+    pub(crate) fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the untyped id of this entity.
+    #[inline]
+    pub fn id(self) -> Entity {
+        self.entity
+    }
+}
+
+impl<B> Copy for TypedEntity<B> {}
+impl<B> Clone for TypedEntity<B> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<B> Eq for TypedEntity<B> {}
+impl<B> PartialEq for TypedEntity<B> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.entity == other.entity
+    }
+}
+impl<B> PartialEq<Entity> for TypedEntity<B> {
+    #[inline]
+    fn eq(&self, other: &Entity) -> bool {
+        self.entity == *other
+    }
+}
+impl<B> Hash for TypedEntity<B> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.entity.hash(state)
+    }
+}
+impl<B> fmt::Debug for TypedEntity<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TypedEntity").field(&self.entity).finish()
+    }
+}
+
+impl<B> From<TypedEntity<B>> for Entity {
+    #[inline]
+    fn from(entity: TypedEntity<B>) -> Self {
+        entity.entity
+    }
+}
+
 pub type Iter<'a> = slotmap::basic::Keys<'a, Entity, EntityLocation>;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -91,3 +226,47 @@ impl std::ops::Index<Entity> for Entities {
         &self.0[entity]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::mem::size_of;
+
+    use super::*;
+
+    #[test]
+    fn test_entity_bits() {
+        assert_eq!(size_of::<Entity>(), size_of::<Option<Entity>>());
+
+        let mut entities = Entities::new();
+        let a = entities.create();
+        entities.remove(a);
+        let b = entities.create();
+        assert_eq!(a.index(), b.index());
+        assert_ne!(a.generation(), b.generation());
+
+        for entity in [a, b, Entity::null()] {
+            let bits = entity.to_bits();
+            assert_eq!(Some(entity), Entity::from_bits(bits));
+            assert_eq!(bits, u64::from(entity));
+        }
+        assert_eq!(None, Entity::from_bits(0));
+        assert_eq!(None, Entity::from_bits(1 << 32));
+        assert_eq!(None, Entity::from_bits(b.to_bits() + (1 << 32)));
+    }
+
+    #[test]
+    fn test_entity_bits_layout() {
+        let mut entities = Entities::new();
+        let a = entities.create();
+        let b = entities.create();
+        // slot 0 is never used; the generation of occupied slots is odd
+        assert_eq!((1, 1), (a.index(), a.generation()));
+        assert_eq!((2, 1), (b.index(), b.generation()));
+        assert_eq!((1 << 32) | 2, b.to_bits());
+        entities.remove(a);
+        let c = entities.create();
+        assert_eq!((1, 3), (c.index(), c.generation()));
+        assert_eq!((3 << 32) | 1, c.to_bits());
+        assert_eq!(Some(c), Entity::from_bits((3 << 32) | 1));
+    }
+}
@@ -1,13 +1,18 @@
-use std::any::TypeId;
+use std::{
+    any::{Any, TypeId},
+    collections::VecDeque,
+};
 
 use crate::{
     archetype::{Archetype, ArchetypeId},
-    component::{Component, ComponentDetails, ComponentId, Ref, RefMut},
-    entity::{Entity, EntityLocation},
+    component::{
+        Bundle, Component, ComponentDetails, ComponentId, Contains, OnDespawnFn, Ref, RefMut,
+    },
+    entity::{Entity, EntityLocation, TypedEntity},
     get_or_init_component,
     resource::{Res, ResMut, ResourceId, Resources},
     storage::{AnyStorage, Storage},
-    world::{World, WorldMut},
+    world::{ClonedEntity, World, WorldMut},
     WorldInner,
 };
 
@@ -91,6 +96,23 @@ impl<'w> EntityRef<'w> {
             storage.get(self.entity, self.location.archetype_id, self.location.index)
         })
     }
+
+    /// Returns the ids of all components of this entity.
+    pub fn component_ids(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        self.world
+            .components
+            .components
+            .iter()
+            .filter(|c| contains_dyn(self.res, self.world, self.entity, self.location, c))
+            .map(ComponentDetails::id)
+    }
+
+    /// Returns a type-erased shared reference to the component with the given
+    /// id.
+    pub fn borrow_dyn(&self, component_id: ComponentId) -> Option<Ref<'_, dyn Any>> {
+        let component = self.world.components.get(component_id)?;
+        borrow_dyn(self.res, component, self.entity, self.location)
+    }
 }
 
 /// An exclusive reference to a entity of a world.
@@ -205,15 +227,64 @@ impl<'w> EntityMut<'w> {
         })
     }
 
+    /// Returns the ids of all components of this entity.
+    pub fn component_ids(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        let world = &*self.world;
+        world
+            .components
+            .components
+            .iter()
+            .filter(move |c| {
+                let id = c.id();
+                if world.tmp_removed.contains(id) {
+                    false
+                } else if world.tmp_inserted.contains(id) {
+                    true
+                } else {
+                    contains_dyn(self.res, world, self.entity, self.location, c)
+                }
+            })
+            .map(ComponentDetails::id)
+    }
+
+    /// Returns a type-erased shared reference to the component with the given
+    /// id.
+    pub fn borrow_dyn(&self, component_id: ComponentId) -> Option<Ref<'_, dyn Any>> {
+        let component = self.world.components.get(component_id)?;
+        borrow_dyn(self.res, component, self.entity, self.location)
+    }
+
+    /// Returns a type-erased exclusive reference to the component with the
+    /// given id, if not already borrowed.
+    pub fn borrow_mut_dyn(&self, component_id: ComponentId) -> Option<RefMut<'_, dyn Any>> {
+        let component = self.world.components.get(component_id)?;
+        let storage = self
+            .res
+            .borrow_res_mut_meta::<dyn AnyStorage>(component.storage_id.typed())?;
+        RefMut::filter_map(storage, |storage| {
+            storage.get_any_mut(self.entity, self.location.archetype_id, self.location.index)
+        })
+    }
+
     #[inline]
     pub fn insert<T>(&mut self, value: T) -> &mut Self
     where
         T: Component,
     {
-        let (_, component_id) = get_or_init_component::<T>(self.res, &mut self.world.components);
+        let (_, component_id) = get_or_init_component::<T>(self.res, self.world);
         self.insert_by_id(component_id, value)
     }
 
+    /// Inserts all components of the bundle.
+    #[inline]
+    pub fn insert_bundle<B>(&mut self, bundle: B) -> &mut Self
+    where
+        B: Bundle,
+    {
+        bundle.insert_into(self);
+        self
+    }
+
     pub fn insert_by_id<T>(&mut self, component_id: ComponentId<T>, value: T) -> &mut Self
     where
         T: Component,
@@ -225,6 +296,7 @@ impl<'w> EntityMut<'w> {
             let mut storage = storage_mut::<T>(self.res, component).expect("storage");
             storage.insert(self.entity, value);
         }
+        T::insert_required(self);
         self
     }
 
@@ -239,6 +311,70 @@ impl<'w> EntityMut<'w> {
         self
     }
 
+    /// Inserts the component and returns the previous value.
+    ///
+    /// Unlike [`insert`](Self::insert), the previous value is not dropped.
+    /// Pending inserts and removals of this entity are applied first.
+    #[inline]
+    pub fn replace<T>(&mut self, value: T) -> Option<T>
+    where
+        T: Component,
+    {
+        let (_, component_id) = get_or_init_component::<T>(self.res, self.world);
+        self.replace_by_id(component_id, value)
+    }
+
+    pub fn replace_by_id<T>(&mut self, component_id: ComponentId<T>, value: T) -> Option<T>
+    where
+        T: Component,
+    {
+        self.flush(None);
+        if let Some(mut current) = self.borrow_mut_by_id(component_id) {
+            return Some(std::mem::replace(&mut *current, value));
+        }
+        self.insert_by_id(component_id, value);
+        None
+    }
+
+    /// Removes the component from this entity and returns it.
+    ///
+    /// Unlike [`remove`](Self::remove), the value is not dropped.
+    /// Pending inserts and removals of this entity are applied first.
+    #[inline]
+    pub fn take<T>(&mut self) -> Option<T>
+    where
+        T: Component,
+    {
+        let component_id = self.world.components.id::<T>()?;
+        self.take_by_id(component_id)
+    }
+
+    pub fn take_by_id<T>(&mut self, component_id: ComponentId<T>) -> Option<T>
+    where
+        T: Component,
+    {
+        self.flush(None);
+        let component = self.world.components.get(component_id)?;
+        let archetype_component = component.archetype_component;
+        let value = {
+            let mut storage = storage_mut::<T>(self.res, component)?;
+            let location = self.location;
+            Storage::swap_remove(
+                &mut *storage,
+                self.entity,
+                location.archetype_id,
+                location.index,
+            )?
+        };
+        if archetype_component {
+            // the storage already swapped the last entry into this place,
+            // so move the other components of this entity to the new archetype
+            self.world.tmp_removed.insert(component_id);
+            self.flush(Some(component_id.offset()));
+        }
+        Some(value)
+    }
+
     pub fn remove_by_id<X>(&mut self, component_id: ComponentId<X>) -> &mut Self {
         self.world.tmp_inserted.remove(component_id);
         self.world.tmp_removed.insert(component_id);
@@ -259,11 +395,53 @@ impl<'w> EntityMut<'w> {
     /// Removes the entity and all its components from the world.
     ///
     /// Like `clear`, but also removes the entity from the world.
+    ///
+    /// The cleanup policies ([`Component::ON_DESPAWN`]) of the components are
+    /// executed before the entity is removed. Entities that are despawned by
+    /// these policies are despawned afterwards, in the order they were queued.
     pub fn despawn(mut self) {
+        let mut queue = VecDeque::new();
+        self.despawn_and_cleanup(&mut queue);
+        while let Some(entity) = queue.pop_front() {
+            let Some(location) = self.world.entities.get(entity) else {
+                continue; // already despawned
+            };
+            EntityMut::new(self.res, self.world, entity, location).despawn_and_cleanup(&mut queue);
+        }
+    }
+
+    fn despawn_and_cleanup(&mut self, queue: &mut VecDeque<Entity>) {
         // clear open operations
         self.world.tmp_removed.clear();
         self.world.tmp_inserted.clear();
 
+        let components = &self.world.components;
+        let hooks: Vec<OnDespawnFn> = if components.despawn_hooks.is_empty() {
+            Vec::new()
+        } else {
+            components
+                .despawn_hooks
+                .iter()
+                .map(|&id| &components.components[id.offset()])
+                .filter(|c| contains_dyn(self.res, self.world, self.entity, self.location, c))
+                .filter_map(|c| c.on_despawn)
+                .collect()
+        };
+        if !hooks.is_empty() {
+            for hook in hooks {
+                hook(&mut DespawnContext {
+                    res: self.res,
+                    world: self.world,
+                    entity: self.entity,
+                    queue,
+                });
+            }
+            // the location may have changed, when other entities were modified
+            self.location = self.world.entities[self.entity];
+            self.world.tmp_removed.clear();
+            self.world.tmp_inserted.clear();
+        }
+
         let location = self.location;
 
         // remove components and track removal
@@ -279,6 +457,41 @@ impl<'w> EntityMut<'w> {
             }
         }
 
+        self.remove_from_world();
+        self.flush(None);
+    }
+
+    /// Moves the entity with all its components into another world.
+    ///
+    /// The entity is removed from this world and a new entity is spawned in
+    /// the `target` world. Returns the id of the new entity.
+    pub fn move_to(self, target: &mut WorldMut<'_>) -> Entity {
+        let components = self.take_components();
+        target.spawn_with(components)
+    }
+
+    /// Removes the entity from the world, and returns its components.
+    pub(crate) fn take_components(mut self) -> Vec<InsertComponentFn> {
+        // clear open operations
+        self.world.tmp_removed.clear();
+        self.world.tmp_inserted.clear();
+
+        let location = self.location;
+        let components = self
+            .world
+            .components
+            .components
+            .iter()
+            .filter_map(|c| (c.transfer)(self.res, c, self.entity, location))
+            .collect();
+
+        self.remove_from_world();
+        components
+    }
+
+    fn remove_from_world(&mut self) {
+        let location = self.location;
+
         // remove entity from archetype by swapping
         if location.is_occupied() {
             let archetype = self
@@ -301,8 +514,70 @@ impl<'w> EntityMut<'w> {
     }
 }
 
+/// Passed to the cleanup policies ([`Component::ON_DESPAWN`]) of the
+/// components of a despawned entity.
+pub struct DespawnContext<'w> {
+    res: &'w mut Resources,
+    world: &'w mut WorldInner,
+    entity: Entity,
+    queue: &'w mut VecDeque<Entity>,
+}
+
+impl DespawnContext<'_> {
+    /// Returns the id of the despawned entity.
+    #[inline]
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+
+    /// Returns the despawned entity (with all its components).
+    #[inline]
+    pub fn entity(&self) -> EntityRef<'_> {
+        let location = self.world.entities[self.entity];
+        EntityRef::new(self.res, self.world, self.entity, location)
+    }
+
+    /// Returns an other entity for modification.
+    ///
+    /// Returns `None` for the despawned entity, or when the entity doesn't
+    /// exist.
+    pub fn entity_mut(&mut self, entity: Entity) -> Option<EntityMut<'_>> {
+        if entity == self.entity {
+            return None;
+        }
+        let location = self.world.entities.get(entity)?;
+        Some(EntityMut::new(self.res, self.world, entity, location))
+    }
+
+    /// Queues an other entity for despawning.
+    ///
+    /// The entity is despawned after the despawned entity was removed.
+    #[inline]
+    pub fn despawn(&mut self, entity: Entity) {
+        if entity != self.entity {
+            self.queue.push_back(entity);
+        }
+    }
+}
+
 impl<'w> Drop for EntityMut<'w> {
     fn drop(&mut self) {
+        self.flush(None);
+    }
+}
+
+impl EntityMut<'_> {
+    /// Applies all pending inserts and removals.
+    ///
+    /// `taken` is a component (offset), that was already removed from its
+    /// storage (and is part of `tmp_removed`).
+    fn flush(&mut self, taken: Option<usize>) {
+        self.apply_pending(taken);
+        self.world.tmp_removed.clear();
+        self.world.tmp_inserted.clear();
+    }
+
+    fn apply_pending(&mut self, taken: Option<usize>) {
         let old = self.location;
         let old_archetype = self
             .world
@@ -316,6 +591,10 @@ impl<'w> Drop for EntityMut<'w> {
         // TODO: track_removed
         self.world.tmp_removed.retain(|index| {
             let component = &self.world.components.components[index];
+            if taken == Some(index) {
+                needs_update_archetype |= component.archetype_component;
+                return true;
+            }
             if let Some(storage) = storage_mut_dyn(self.res, component) {
                 if storage.swap_remove(self.entity, old.archetype_id, old.index) {
                     if component.archetype_component {
@@ -439,6 +718,167 @@ where
     res.borrow_res_mut_id(storage_id)
 }
 
+/// Inserts a (moved or cloned) component into the target entity.
+pub type InsertComponentFn = Box<dyn FnOnce(&mut EntityMut<'_>)>;
+
+/// Removes the component from an entity.
+pub type TransferComponentFn = fn(
+    res: &Resources,
+    component: &ComponentDetails,
+    entity: Entity,
+    location: EntityLocation,
+) -> Option<InsertComponentFn>;
+
+/// Removes the component from the entity, so it can be inserted into another
+/// entity (possibly of another world).
+pub fn transfer_component<T>(
+    res: &Resources,
+    component: &ComponentDetails,
+    entity: Entity,
+    location: EntityLocation,
+) -> Option<InsertComponentFn>
+where
+    T: Component,
+{
+    let mut storage = storage_mut::<T>(res, component)?;
+    let value = Storage::swap_remove(&mut *storage, entity, location.archetype_id, location.index)?;
+    Some(Box::new(move |target| {
+        target.insert(value);
+    }))
+}
+
+/// Clones the component of an entity.
+pub type CloneComponentFn = fn(
+    res: &Resources,
+    component: &ComponentDetails,
+    entity: Entity,
+    location: EntityLocation,
+) -> Option<InsertComponentFn>;
+
+/// Clones the component with the clone function of the component
+/// ([`Component::ON_CLONE`]).
+pub fn clone_component<T>(
+    res: &Resources,
+    component: &ComponentDetails,
+    entity: Entity,
+    location: EntityLocation,
+) -> Option<InsertComponentFn>
+where
+    T: Component,
+{
+    let clone = T::ON_CLONE?;
+    let storage = storage::<T>(res, component)?;
+    let value = clone(Storage::get(
+        &*storage,
+        entity,
+        location.archetype_id,
+        location.index,
+    )?);
+    Some(Box::new(move |target| {
+        target.insert(value);
+    }))
+}
+
+// returns the cloned components, and the ids of the components without a
+// clone function
+fn clone_components(
+    res: &Resources,
+    world: &WorldInner,
+    entity: Entity,
+) -> Option<(Vec<InsertComponentFn>, Vec<ComponentId>)> {
+    let location = world.entities.get(entity)?;
+    let mut components = Vec::new();
+    let mut skipped = Vec::new();
+    for component in &world.components.components {
+        if let Some(clone) = component.clone {
+            components.extend(clone(res, component, entity, location));
+        } else if contains_dyn(res, world, entity, location, component) {
+            skipped.push(component.id());
+        }
+    }
+    Some((components, skipped))
+}
+
+fn contains_dyn(
+    res: &Resources,
+    world: &WorldInner,
+    entity: Entity,
+    location: EntityLocation,
+    component: &ComponentDetails,
+) -> bool {
+    if component.archetype_component {
+        world.archetypes[location.archetype_id]
+            .components
+            .contains(component.id())
+    } else {
+        matches!(
+            res.borrow_res_meta::<dyn AnyStorage>(component.storage_id.typed()),
+            Some(s) if s.contains(entity, location.archetype_id, location.index)
+        )
+    }
+}
+
+fn borrow_dyn<'a>(
+    res: &'a Resources,
+    component: &ComponentDetails,
+    entity: Entity,
+    location: EntityLocation,
+) -> Option<Ref<'a, dyn Any>> {
+    let storage = res.borrow_res_meta::<dyn AnyStorage>(component.storage_id.typed())?;
+    Ref::filter_map(storage, |storage| {
+        storage.get_any(entity, location.archetype_id, location.index)
+    })
+}
+
+fn borrow_typed<'a, T>(res: &'a Resources, world: &WorldInner, entity: Entity) -> Ref<'a, T>
+where
+    T: Component,
+{
+    let location = world.entities.get(entity);
+    let storage = world
+        .components
+        .id::<T>()
+        .and_then(|id| world.components.get(id))
+        .and_then(|component| storage::<T>(res, component));
+    location
+        .zip(storage)
+        .and_then(|(location, storage)| {
+            Ref::filter_map(storage, |s| {
+                s.get(entity, location.archetype_id, location.index)
+            })
+        })
+        .unwrap_or_else(|| missing_typed_component::<T>(entity))
+}
+
+fn borrow_typed_mut<'a, T>(res: &'a Resources, world: &WorldInner, entity: Entity) -> RefMut<'a, T>
+where
+    T: Component,
+{
+    let location = world.entities.get(entity);
+    let storage = world
+        .components
+        .id::<T>()
+        .and_then(|id| world.components.get(id))
+        .and_then(|component| storage_mut::<T>(res, component));
+    location
+        .zip(storage)
+        .and_then(|(location, storage)| {
+            RefMut::filter_map(storage, |s| {
+                s.get_mut(entity, location.archetype_id, location.index)
+            })
+        })
+        .unwrap_or_else(|| missing_typed_component::<T>(entity))
+}
+
+#[cold]
+#[track_caller]
+fn missing_typed_component<T>(entity: Entity) -> ! {
+    panic!(
+        "entity {entity:?} was despawned, or component `{}` was removed or is already borrowed",
+        std::any::type_name::<T>()
+    )
+}
+
 fn storage_mut_dyn<'a>(
     res: &'a mut Resources,
     component: &ComponentDetails,
@@ -456,6 +896,34 @@ impl World<'_> {
             .get(entity)
             .map(|location| EntityRef::new(self.res, &self.world, entity, location))
     }
+
+    /// Returns a [`TypedEntity`] for the given id, when the entity contains all
+    /// components of the bundle `B`.
+    pub fn typed_entity<B: Bundle>(&self, entity: Entity) -> Option<TypedEntity<B>> {
+        let ent = self.entity(entity)?;
+        B::is_contained_in(&ent).then(|| TypedEntity::new(entity))
+    }
+
+    /// Returns a shared reference to the component `T` of a typed entity.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the entity was despawned, when the component was removed
+    /// from the entity, or when the component is already borrowed exclusively.
+    #[track_caller]
+    pub fn component<T, I>(&self, entity: TypedEntity<impl Contains<T, I>>) -> Ref<'_, T>
+    where
+        T: Component,
+    {
+        borrow_typed(self.res, &self.world, entity.id())
+    }
+
+    pub(crate) fn clone_components(
+        &self,
+        entity: Entity,
+    ) -> Option<(Vec<InsertComponentFn>, Vec<ComponentId>)> {
+        clone_components(self.res, &self.world, entity)
+    }
 }
 impl WorldMut<'_> {
     /// Returns a shared reference ([`EntityRef`]) to the entity with the given
@@ -470,13 +938,129 @@ impl WorldMut<'_> {
     /// Returns an exclusive reference ([`EntityMut`]) to the entity with the
     /// given id.
     pub fn entity_mut(&mut self, entity: Entity) -> Option<EntityMut<'_>> {
-        let Some(location) = self.world.entities.get_mut(entity) else {
-            return None;
-        };
+        let location = self.world.entities.get_mut(entity)?;
         let location = *location;
         Some(EntityMut::new(self.res, &mut self.world, entity, location))
     }
 
+    /// Returns a [`TypedEntity`] for the given id, when the entity contains all
+    /// components of the bundle `B`.
+    pub fn typed_entity<B: Bundle>(&self, entity: Entity) -> Option<TypedEntity<B>> {
+        let ent = self.entity(entity)?;
+        B::is_contained_in(&ent).then(|| TypedEntity::new(entity))
+    }
+
+    /// Returns a shared reference to the component `T` of a typed entity.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the entity was despawned, when the component was removed
+    /// from the entity, or when the component is already borrowed exclusively.
+    #[track_caller]
+    pub fn component<T, I>(&self, entity: TypedEntity<impl Contains<T, I>>) -> Ref<'_, T>
+    where
+        T: Component,
+    {
+        borrow_typed(self.res, &self.world, entity.id())
+    }
+
+    /// Returns an exclusive reference to the component `T` of a typed entity.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the entity was despawned, when the component was removed
+    /// from the entity, or when the component is already borrowed.
+    #[track_caller]
+    pub fn component_mut<T, I>(&self, entity: TypedEntity<impl Contains<T, I>>) -> RefMut<'_, T>
+    where
+        T: Component,
+    {
+        borrow_typed_mut(self.res, &self.world, entity.id())
+    }
+
+    /// Spawns a copy of the entity with the given id.
+    ///
+    /// Only components with a clone function ([`Component::ON_CLONE`], set
+    /// with `#[component(clone)]`) are copied. Cloning is opt-in, so
+    /// components without a clone function are skipped, even when they
+    /// implement `Clone`; their ids are returned in
+    /// [`ClonedEntity::skipped`].
+    ///
+    /// Returns the new entity, or `None` when the entity doesn't exist.
+    pub fn clone_entity(&mut self, entity: Entity) -> Option<ClonedEntity> {
+        let (components, skipped) = clone_components(self.res, &self.world, entity)?;
+        Some(ClonedEntity {
+            id: self.spawn_with(components),
+            skipped,
+        })
+    }
+
+    /// Moves the entity with the given id and all its components into the
+    /// `target` world.
+    ///
+    /// Returns the id of the entity in the `target` world, or `None` when the
+    /// entity doesn't exist in this world.
+    pub fn move_entity_to(&mut self, entity: Entity, target: &mut WorldMut<'_>) -> Option<Entity> {
+        let ent = self.entity_mut(entity)?;
+        Some(ent.move_to(target))
+    }
+
+    /// Copies the entity with the given id into the `target` world.
+    ///
+    /// Like with [`Self::clone_entity`], only components with a clone function
+    /// ([`Component::ON_CLONE`]) are copied, the others are returned in
+    /// [`ClonedEntity::skipped`].
+    ///
+    /// Returns the new entity in the `target` world, or `None` when the
+    /// entity doesn't exist in this world.
+    pub fn copy_entity_to(
+        &self,
+        entity: Entity,
+        target: &mut WorldMut<'_>,
+    ) -> Option<ClonedEntity> {
+        let (components, skipped) = clone_components(self.res, &self.world, entity)?;
+        Some(ClonedEntity {
+            id: target.spawn_with(components),
+            skipped,
+        })
+    }
+
+    /// Releases the unused memory of archetypes and component storages.
+    ///
+    /// Archetypes that became empty (e.g. after unloading a level) are
+    /// removed, and the remaining archetypes get new ids. Queries notice the
+    /// change with [`Archetypes::generation`](crate::archetype::Archetypes::generation)
+    /// and re-collect their matching archetypes.
+    pub fn shrink_to_fit(&mut self) {
+        let remap = self.world.archetypes.remove_empty();
+        if remap.is_some() {
+            let world: &mut WorldInner = &mut self.world;
+            for archetype in world.archetypes.iter() {
+                for &entity in &archetype.entities {
+                    world.entities.get_mut(entity).expect("entity").archetype_id = archetype.id;
+                }
+            }
+        }
+        self.world.archetypes.shrink_to_fit();
+        for component in &self.world.components.components {
+            if let Some(storage) = storage_mut_dyn(self.res, component) {
+                if let Some(remap) = &remap {
+                    storage.remap_archetypes(remap);
+                }
+                storage.shrink_to_fit();
+            }
+        }
+    }
+
+    /// Removes the component from the entity and returns it.
+    #[inline]
+    pub fn take<T>(&mut self, entity: Entity) -> Option<T>
+    where
+        T: Component,
+    {
+        self.entity_mut(entity)?.take::<T>()
+    }
+
     /// Spawns/creates an new empty [`Entity`] in this `World` and returns a handle
     /// for modifying it.
     #[must_use]
@@ -493,4 +1077,22 @@ impl WorldMut<'_> {
         let location = self.world.entities[entity];
         EntityMut::new(self.res, &mut self.world, entity, location)
     }
+
+    /// Spawns a new [`Entity`] with the components of the given bundle.
+    ///
+    /// The returned [`TypedEntity`] provides access to the components of the
+    /// bundle without unwrapping (see [`WorldMut::component`]).
+    pub fn spawn_bundle<B: Bundle>(&mut self, bundle: B) -> TypedEntity<B> {
+        let mut entity = self.spawn();
+        bundle.insert_into(&mut entity);
+        TypedEntity::new(entity.id())
+    }
+
+    pub(crate) fn spawn_with(&mut self, components: Vec<InsertComponentFn>) -> Entity {
+        let mut entity = self.spawn();
+        for insert in components {
+            insert(&mut entity);
+        }
+        entity.id()
+    }
 }
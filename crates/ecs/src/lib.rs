@@ -8,7 +8,6 @@
     trivial_numeric_casts,
     unused_lifetimes,
     unused_qualifications,
-    unused_crate_dependencies,
     clippy::cargo,
     clippy::multiple_crate_versions,
     clippy::empty_line_after_outer_attr,
@@ -21,10 +20,14 @@
     //clippy::missing_panics_doc,
     clippy::wildcard_imports
 )]
+// the dev-dependencies are only used by the integration tests
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
 #![doc(html_logo_url = "https://raw.githubusercontent.com/HellButcher/pulz/master/docs/logo.png")]
 #![doc(html_no_source)]
 #![doc = include_str!("../README.md")]
 
+use std::{any::TypeId, collections::BTreeMap};
+
 use component::ComponentSet;
 pub use pulz_schedule::*;
 
@@ -38,13 +41,17 @@ pub mod query;
 pub mod entity;
 mod entity_ref;
 pub mod removed;
+#[cfg(feature = "serde")]
+pub mod scene;
+pub mod snapshot;
+pub mod state_scoped;
 pub mod storage;
 pub mod world;
 
 pub use component::Component;
-pub use entity::{Entity, EntityMut, EntityRef};
+pub use entity::{Entity, EntityMut, EntityRef, TypedEntity};
 use pulz_schedule::schedule::Schedule;
-pub use world::WorldExt;
+pub use world::{WorldExt, WorldId};
 
 use crate::storage::AnyStorage;
 
@@ -52,10 +59,10 @@ pub mod prelude {
     pub use pulz_schedule::prelude::*;
 
     pub use crate::{
-        component::Component,
-        entity::{Entity, EntityMut, EntityRef},
+        component::{Bundle, Component},
+        entity::{Entity, EntityMut, EntityRef, TypedEntity},
         query::Query,
-        world::{World, WorldExt},
+        world::{World, WorldExt, WorldId},
     };
 }
 
@@ -66,8 +73,11 @@ struct WorldInner {
 
     tmp_removed: ComponentSet,
     tmp_inserted: ComponentSet,
-    // tracks removed components
-    //removed: component::ComponentMap<Vec<Entity>>,
+
+    // `false` for worlds created with `WorldExt::add_world`: their storages
+    // (and query-states) are anonymous resources
+    main: bool,
+    query_states: BTreeMap<TypeId, resource::ResourceId>,
 }
 
 impl Default for WorldInner {
@@ -79,29 +89,27 @@ impl Default for WorldInner {
 
             tmp_removed: ComponentSet::new(),
             tmp_inserted: ComponentSet::new(),
-            //removed: component::ComponentMap::new(),
-        }
-    }
-}
 
-fn insert_sorted<T: Ord>(vec: &mut Vec<T>, value: T) {
-    if let Err(pos) = vec.binary_search(&value) {
-        vec.insert(pos, value);
+            main: true,
+            query_states: BTreeMap::new(),
+        }
     }
 }
 
-fn get_or_init_component<'a, T>(
-    res: &'a mut resource::Resources,
-    comps: &'a mut component::Components,
+fn get_or_init_component<T>(
+    res: &mut resource::Resources,
+    world: &mut WorldInner,
 ) -> (resource::ResourceId<T::Storage>, component::ComponentId<T>)
 where
     T: Component,
 {
+    use resource::FromResourcesMut;
     use storage::Storage;
+    let comps = &mut world.components;
     if let Some(component_id) = comps.id::<T>() {
         let component = comps.get(component_id).unwrap();
         (component.storage_id.typed(), component_id)
-    } else {
+    } else if world.main {
         let storage_id = res.init::<T::Storage>();
         res.init_meta_id::<dyn AnyStorage, _>(storage_id);
         let component_id = comps.insert(storage_id, T::Storage::SPARSE).unwrap();
@@ -110,6 +118,14 @@ where
             <T::Storage as Storage>::install_systems(schedule);
         }
 
+        (storage_id, component_id)
+    } else {
+        // additional worlds are maintained by `world::maintain_worlds`
+        let storage = T::Storage::from_resources_mut(res);
+        let storage_id = res.insert_anonymous(storage);
+        res.init_meta_id::<dyn AnyStorage, _>(storage_id);
+        let component_id = comps.insert(storage_id, T::Storage::SPARSE).unwrap();
+
         (storage_id, component_id)
     }
 }
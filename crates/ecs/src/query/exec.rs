@@ -1,21 +1,32 @@
-use std::pin::Pin;
+use std::{any::TypeId, pin::Pin, sync::PoisonError};
 
 use pulz_schedule::system::data::SystemDataFetch;
 
 use super::QueryParamState;
 use crate::{
     archetype::{Archetype, ArchetypeId, ArchetypeSet, ArchetypeSetIter},
+    component::Component,
     entity::Entity,
-    query::{QueryItem, QueryParam, QueryParamFetch, QueryState},
+    query::{
+        QueryItem, QueryParam, QueryParamFetch, QueryState, ReadOnlyQueryParam, SortedScratch,
+    },
     resource::{Res, ResourceAccess, ResourceId, Resources},
+    storage::Storage,
     system::data::{SystemData, SystemDataState},
     WorldInner,
 };
 
+/// Iterates the entities of a world, that match the [`QueryParam`] `Q`.
+///
+/// As a system parameter, a `Query` always accesses the main world of the
+/// [`Resources`]. Additional worlds (see
+/// [`WorldExt::add_world`](crate::WorldExt::add_world)) are queried with
+/// [`WorldExt::query_by_world_id`](crate::WorldExt::query_by_world_id).
 pub struct Query<'w, Q>
 where
     Q: QueryParam + 'w,
 {
+    res: &'w Resources,
     world: Res<'w, WorldInner>,
     state: Res<'w, QueryState<Q::State>>,
     fetch: Q::Fetch<'w>,
@@ -41,6 +52,66 @@ where
     cursor: Cursor<'w>,
 }
 
+pub struct QuerySortedIter<'w, 'a, Q>
+where
+    Q: QueryParam + 'a,
+{
+    world: &'a WorldInner,
+    state: &'a QueryState<Q::State>,
+    fetch: &'a mut Q::Fetch<'w>,
+    entries: Vec<(ArchetypeId, usize)>,
+    next_entry: usize,
+    current_archetype_id: Option<ArchetypeId>,
+}
+
+/// Error returned by [`Query::iter_sorted_by_key`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuerySortError {
+    /// The query doesn't read the key component.
+    KeyNotInQuery(&'static str),
+    /// The query accesses the key component mutably.
+    MutableKey(&'static str),
+}
+
+impl std::fmt::Display for QuerySortError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::KeyNotInQuery(name) => {
+                write!(f, "the sort key `{name}` is not accessed by the query")
+            }
+            Self::MutableKey(name) => {
+                write!(f, "the sort key `{name}` is accessed mutably by the query")
+            }
+        }
+    }
+}
+
+impl std::error::Error for QuerySortError {}
+
+pub struct QueryCombinationIter<'w, 'a, Q, const K: usize>
+where
+    Q: QueryParam + 'a,
+{
+    world: &'a WorldInner,
+    state: &'a QueryState<Q::State>,
+    fetch: &'a mut Q::Fetch<'w>,
+    combinations: Combinations<K>,
+    current_archetype_id: Option<ArchetypeId>,
+}
+
+/// Iterates over all `K`-combinations of the matching entities of a query,
+/// represented as `(archetype, index)` pairs.
+///
+/// The entities are numbered consecutively over the matching archetypes, and
+/// the cursor holds the numbers of the current combination.
+struct Combinations<const K: usize> {
+    // non-empty matching archetypes with the number of their first entity
+    archetypes: Vec<(ArchetypeId, usize)>,
+    len: usize,
+    cursor: [usize; K],
+    done: bool,
+}
+
 struct Cursor<'a> {
     matching_archetypes: ArchetypeSetIter<'a>,
     current_archetype_id: ArchetypeId,
@@ -57,12 +128,38 @@ where
         Self::new_id(res, state_resource_id)
     }
 
+    pub(crate) fn new_in_world(res: &'w mut Resources, world_id: ResourceId<WorldInner>) -> Self
+    where
+        Q: 'static,
+    {
+        let world = res.borrow_res_id(world_id).expect("world not available");
+        if world.main {
+            drop(world);
+            return Self::new(res);
+        }
+        // the query-states of additional worlds are anonymous resources
+        let type_id = TypeId::of::<QueryState<Q::State>>();
+        let state_resource_id = if let Some(&id) = world.query_states.get(&type_id) {
+            drop(world);
+            id.typed()
+        } else {
+            let state = QueryState::<Q::State>::from_world(res, &world, world_id);
+            drop(world);
+            let id = res.insert_anonymous(state);
+            let world = res.get_mut_id(world_id).unwrap();
+            world.query_states.insert(type_id, id.untyped());
+            id
+        };
+        Self::new_id(res, state_resource_id)
+    }
+
     fn new_id(res: &'w Resources, resource_id: ResourceId<QueryState<Q::State>>) -> Self {
         let state = res.borrow_res_id(resource_id).expect("query-state");
         let world = res.borrow_res_id(state.world_resource_id).unwrap();
         state.update_archetypes(&world);
         let fetch = Q::Fetch::fetch(res.as_send(), &state.param_state);
         Self {
+            res,
             state,
             world,
             fetch,
@@ -93,6 +190,151 @@ where
         }
     }
 
+    /// Iterates over the matching entities, ordered by the value of their
+    /// component `K` (e.g. a depth or priority).
+    ///
+    /// Entities without a `K` component are yielded last. Entities with equal
+    /// keys are yielded in the order of [`Self::iter`]. The buffers used for
+    /// sorting are kept in the query-state and re-used by later calls.
+    ///
+    /// The storage of `K` is borrowed while sorting, so the query must read
+    /// `K` (e.g. by containing `&K` or `Option<&K>`), so the access to `K` is
+    /// known to the schedule. An error is returned, when the query doesn't
+    /// access `K`, or accesses it mutably.
+    pub fn iter_sorted_by_key<'a, K>(
+        &'a mut self,
+    ) -> Result<QuerySortedIter<'w, 'a, Q>, QuerySortError>
+    where
+        K: Component + Ord,
+    {
+        let world: &'a WorldInner = &self.world;
+        let state: &'a QueryState<Q::State> = &self.state;
+
+        let component = world
+            .components
+            .id::<K>()
+            .and_then(|id| world.components.get(id))
+            .ok_or_else(|| QuerySortError::KeyNotInQuery(std::any::type_name::<K>()))?;
+        let mut access = ResourceAccess::new();
+        state.param_state.update_access(&mut access);
+        if access.is_exclusive(component.storage_id) {
+            return Err(QuerySortError::MutableKey(std::any::type_name::<K>()));
+        } else if !access.is_shared(component.storage_id) {
+            return Err(QuerySortError::KeyNotInQuery(std::any::type_name::<K>()));
+        }
+        let storage = self
+            .res
+            .borrow_res_id(component.storage_id.typed::<K::Storage>())
+            .ok_or_else(|| QuerySortError::MutableKey(std::any::type_name::<K>()))?;
+
+        // the buffers are taken out of the state, so the lock is not held
+        // while `K::cmp` runs
+        let (mut keyed, mut entries) = {
+            let mut scratch = state
+                .sorted_scratch
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let SortedScratch { keyed, entries } = &mut *scratch;
+            (std::mem::take(keyed), std::mem::take(entries))
+        };
+
+        // the keys are looked up once per entity, and not on every comparison
+        for archetype_id in state.matching_archetypes().iter() {
+            let archetype = &world.archetypes[archetype_id];
+            keyed.extend(
+                archetype
+                    .entities
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &entity)| {
+                        let key: *const K = Storage::get(&*storage, entity, archetype_id, index)
+                            .map_or(std::ptr::null(), |key| key);
+                        (key.cast::<()>(), archetype_id, index)
+                    }),
+            );
+        }
+        // SAFETY: the pointers were created from `&K` above, and `storage`
+        // is still borrowed
+        let key = |ptr: *const ()| unsafe { ptr.cast::<K>().as_ref() };
+        // unstable sort is faster; entries are unique, so the tie-breaker
+        // makes it stable
+        keyed.sort_unstable_by(|a, b| {
+            match (key(a.0), key(b.0)) {
+                (Some(key_a), Some(key_b)) => key_a.cmp(key_b),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            }
+            .then_with(|| a.1.index().cmp(&b.1.index()))
+            .then_with(|| a.2.cmp(&b.2))
+        });
+
+        entries.clear();
+        entries.extend(
+            keyed
+                .drain(..)
+                .map(|(_, archetype_id, index)| (archetype_id, index)),
+        );
+        drop(storage);
+        // only the drained buffer is given back (on unwind, it is dropped)
+        state
+            .sorted_scratch
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keyed = keyed;
+
+        Ok(QuerySortedIter {
+            world,
+            state,
+            fetch: &mut self.fetch,
+            entries,
+            next_entry: 0,
+            current_archetype_id: None,
+        })
+    }
+
+    /// Iterates over all unique combinations of `K` matching entities (e.g.
+    /// pairs for `K = 2`).
+    ///
+    /// Every combination is yielded once, regardless of the order (when
+    /// `[a, b]` is yielded, `[b, a]` is not). Because an entity is part of
+    /// multiple combinations, this is only available for read-only queries;
+    /// use [`Self::iter_combinations_mut`] for mutable access.
+    pub fn iter_combinations<'a, const K: usize>(&'a mut self) -> QueryCombinationIter<'w, 'a, Q, K>
+    where
+        Q: ReadOnlyQueryParam,
+    {
+        let world: &'a WorldInner = &self.world;
+        let state: &'a QueryState<Q::State> = &self.state;
+        QueryCombinationIter {
+            world,
+            state,
+            fetch: &mut self.fetch,
+            combinations: Combinations::new(world, state.matching_archetypes()),
+            current_archetype_id: None,
+        }
+    }
+
+    /// Like [`Self::iter_combinations`], but allows mutable access.
+    ///
+    /// The returned value is not an [`Iterator`]: use
+    /// [`QueryCombinationIter::fetch_next`] for getting the combinations one
+    /// after the other. This ensures, that the items of an entity are not
+    /// accessed by multiple combinations at the same time.
+    pub fn iter_combinations_mut<'a, const K: usize>(
+        &'a mut self,
+    ) -> QueryCombinationIter<'w, 'a, Q, K> {
+        let world: &'a WorldInner = &self.world;
+        let state: &'a QueryState<Q::State> = &self.state;
+        QueryCombinationIter {
+            world,
+            state,
+            fetch: &mut self.fetch,
+            combinations: Combinations::new(world, state.matching_archetypes()),
+            current_archetype_id: None,
+        }
+    }
+
     pub fn get<'a>(&'a mut self, entity: Entity) -> Option<QueryItem<'w, 'a, Q>> {
         let location = self.world.entities.get(entity)?;
         if !self
@@ -109,6 +351,108 @@ where
     }
 }
 
+impl<const K: usize> Combinations<K> {
+    fn new(world: &WorldInner, matching_archetypes: &ArchetypeSet) -> Self {
+        let mut archetypes = Vec::new();
+        let mut len = 0;
+        for archetype_id in matching_archetypes.iter() {
+            let archetype_len = world.archetypes[archetype_id].len();
+            if archetype_len > 0 {
+                archetypes.push((archetype_id, len));
+                len += archetype_len;
+            }
+        }
+        Self {
+            archetypes,
+            len,
+            cursor: std::array::from_fn(|i| i),
+            done: K == 0 || K > len,
+        }
+    }
+
+    #[inline]
+    fn locate(&self, entry: usize) -> (ArchetypeId, usize) {
+        let i = self
+            .archetypes
+            .partition_point(|&(_, start)| start <= entry)
+            - 1;
+        let (archetype_id, start) = self.archetypes[i];
+        (archetype_id, entry - start)
+    }
+
+    fn next(&mut self) -> Option<[(ArchetypeId, usize); K]> {
+        if self.done {
+            return None;
+        }
+        let result = self.cursor.map(|i| self.locate(i));
+        // advance to the next combination (in lexicographic order)
+        let len = self.len;
+        if let Some(i) = (0..K).rev().find(|&i| self.cursor[i] < len - K + i) {
+            self.cursor[i] += 1;
+            for j in i + 1..K {
+                self.cursor[j] = self.cursor[j - 1] + 1;
+            }
+        } else {
+            self.done = true;
+        }
+        Some(result)
+    }
+
+    fn remaining(&self) -> usize {
+        if self.done {
+            return 0;
+        }
+        // count the combinations that are lexicographically greater or equal
+        // than the cursor (combinatorial number system)
+        let n = self.len;
+        let total = binomial(n, K);
+        let mut before = 0;
+        let mut prev = 0;
+        for (i, &c) in self.cursor.iter().enumerate() {
+            for skipped in prev..c {
+                before += binomial(n - skipped - 1, K - i - 1);
+            }
+            prev = c + 1;
+        }
+        total - before
+    }
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    let k = k.min(n - k);
+    let mut result = 1usize;
+    for i in 0..k {
+        result = result.saturating_mul(n - i) / (i + 1);
+    }
+    result
+}
+
+/// # Safety
+/// The items must not alias with other items that are alive.
+unsafe fn get_combination<'w, 'a, Q, const K: usize>(
+    world: &'a WorldInner,
+    state: &'a QueryState<Q::State>,
+    fetch: *mut Q::Fetch<'w>,
+    current_archetype_id: &mut Option<ArchetypeId>,
+    combination: [(ArchetypeId, usize); K],
+) -> [QueryItem<'w, 'a, Q>; K]
+where
+    Q: QueryParam + 'a,
+{
+    combination.map(|(archetype_id, index)| {
+        let fetch = unsafe { &mut *fetch }; // found no better way to deal with the lifetimes
+        let archetype = &world.archetypes[archetype_id];
+        if *current_archetype_id != Some(archetype_id) {
+            *current_archetype_id = Some(archetype_id);
+            fetch.set_archetype(&state.param_state, archetype);
+        }
+        fetch.get(archetype, index)
+    })
+}
+
 impl<'a> Cursor<'a> {
     #[inline]
     fn new(matching_archetypes: &'a ArchetypeSet) -> Self {
@@ -161,6 +505,7 @@ where
     #[inline]
     fn into_iter(self) -> Self::IntoIter {
         let Self {
+            res: _,
             world,
             state,
             fetch,
@@ -188,9 +533,7 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         let fetch: *mut _ = self.fetch;
         let fetch = unsafe { &mut *fetch }; // found no better way to deal with the lifetimes
-        let Some((archetype, index)) = self.cursor.next(self.world) else {
-            return None;
-        };
+        let (archetype, index) = self.cursor.next(self.world)?;
         if index == 0 {
             fetch.set_archetype(&self.state.param_state, archetype);
         }
@@ -211,9 +554,7 @@ where
         let world = unsafe { &*world }; // found no better way to deal with the lifetimes
         let fetch: *mut _ = &mut self.fetch;
         let fetch = unsafe { &mut *fetch }; // found no better way to deal with the lifetimes
-        let Some((archetype, index)) = self.cursor.next(world) else {
-            return None;
-        };
+        let (archetype, index) = self.cursor.next(world)?;
         if index == 0 {
             fetch.set_archetype(&self.state.param_state, archetype);
         }
@@ -222,6 +563,105 @@ where
     }
 }
 
+impl<'w: 'a, 'a, Q> Iterator for QuerySortedIter<'w, 'a, Q>
+where
+    Q: QueryParam + 'a,
+{
+    type Item = QueryItem<'w, 'a, Q>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let fetch: *mut _ = self.fetch;
+        let fetch = unsafe { &mut *fetch }; // found no better way to deal with the lifetimes
+        let (archetype_id, index) = *self.entries.get(self.next_entry)?;
+        self.next_entry += 1;
+        let archetype = &self.world.archetypes[archetype_id];
+        if self.current_archetype_id != Some(archetype_id) {
+            self.current_archetype_id = Some(archetype_id);
+            fetch.set_archetype(&self.state.param_state, archetype);
+        }
+        let item = fetch.get(archetype, index);
+        Some(item)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.entries.len() - self.next_entry;
+        (len, Some(len))
+    }
+}
+
+impl<'w: 'a, 'a, Q, const K: usize> QueryCombinationIter<'w, 'a, Q, K>
+where
+    Q: QueryParam + 'a,
+{
+    /// Returns the next combination.
+    ///
+    /// The items of a combination always belong to different entities.
+    #[inline]
+    pub fn fetch_next(&mut self) -> Option<[QueryItem<'w, '_, Q>; K]> {
+        let combination = self.combinations.next()?;
+        let fetch: *mut _ = &mut *self.fetch;
+        // SAFETY: the entities of a combination are distinct, and the items
+        // borrow `self`, so no other combination can be alive
+        Some(unsafe {
+            get_combination::<Q, K>(
+                self.world,
+                self.state,
+                fetch,
+                &mut self.current_archetype_id,
+                combination,
+            )
+        })
+    }
+}
+
+impl<'w: 'a, 'a, Q, const K: usize> Iterator for QueryCombinationIter<'w, 'a, Q, K>
+where
+    Q: ReadOnlyQueryParam + 'a,
+{
+    type Item = [QueryItem<'w, 'a, Q>; K];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let combination = self.combinations.next()?;
+        let fetch: *mut _ = self.fetch;
+        // SAFETY: read-only query
+        Some(unsafe {
+            get_combination::<Q, K>(
+                self.world,
+                self.state,
+                fetch,
+                &mut self.current_archetype_id,
+                combination,
+            )
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.combinations.remaining();
+        (len, Some(len))
+    }
+}
+
+impl<'w, 'a, Q> Drop for QuerySortedIter<'w, 'a, Q>
+where
+    Q: QueryParam + 'a,
+{
+    fn drop(&mut self) {
+        // give the buffer back for re-use
+        let mut scratch = self
+            .state
+            .sorted_scratch
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if scratch.entries.capacity() < self.entries.capacity() {
+            scratch.entries = std::mem::take(&mut self.entries);
+        }
+    }
+}
+
 #[doc(hidden)]
 pub struct QuerySystemParamState<S: QueryParamState>(ResourceId<QueryState<S>>);
 
@@ -4,7 +4,7 @@ use crate::{
     archetype::Archetype,
     component::{Component, ComponentId, Components},
     entity::Entity,
-    query::{QueryParam, QueryParamFetch, QueryParamState},
+    query::{QueryParam, QueryParamFetch, QueryParamState, ReadOnlyQueryParam},
     resource::{Res, ResMut, Resources, ResourcesSend},
     storage::Storage,
 };
@@ -14,6 +14,8 @@ impl<T: Component> QueryParam for &'_ T {
     type Fetch<'w> = QryRefFetch<'w, T>;
 }
 
+unsafe impl<T: Component> ReadOnlyQueryParam for &'_ T {}
+
 #[doc(hidden)]
 pub struct QryRefState<T: Component> {
     storage_id: ResourceId<T::Storage>,
@@ -134,6 +136,8 @@ impl QueryParam for Entity {
     type Fetch<'w> = QryEntityFetch;
 }
 
+unsafe impl ReadOnlyQueryParam for Entity {}
+
 #[doc(hidden)]
 pub struct QryEntityFetch;
 
@@ -163,6 +167,8 @@ where
     type Fetch<'w> = QryOptionFetch<Q::Fetch<'w>>;
 }
 
+unsafe impl<Q> ReadOnlyQueryParam for Option<Q> where Q: ReadOnlyQueryParam {}
+
 #[doc(hidden)]
 #[repr(transparent)]
 pub struct QryOptionState<S>(S);
@@ -226,6 +232,8 @@ impl QueryParam for () {
     type Fetch<'w> = ();
 }
 
+unsafe impl ReadOnlyQueryParam for () {}
+
 unsafe impl QueryParamState for () {
     #[inline]
     fn init(_res: &Resources, _components: &Components) -> Self {}
@@ -265,6 +273,12 @@ macro_rules! impl_query_param {
             type Fetch<'w> = ($($name::Fetch<'w>,)+);
         }
 
+        unsafe impl<$($name),+> ReadOnlyQueryParam for ($($name,)+)
+        where
+            $($name: ReadOnlyQueryParam,)+
+        {
+        }
+
         unsafe impl<$($name),+> QueryParamState for ($($name,)+)
         where
             $($name: QueryParamState,)+
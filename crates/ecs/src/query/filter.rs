@@ -5,7 +5,7 @@ use pulz_schedule::resource::ResourceAccess;
 use crate::{
     archetype::Archetype,
     component::{Component, Components},
-    query::{QryRefState, QueryParam, QueryParamFetch, QueryParamState},
+    query::{QryRefState, QueryParam, QueryParamFetch, QueryParamState, ReadOnlyQueryParam},
     resource::{Resources, ResourcesSend},
 };
 
@@ -61,6 +61,13 @@ where
     type Fetch<'w> = QryWithoutFilterFetch<F, Q::Fetch<'w>>;
 }
 
+unsafe impl<F, Q> ReadOnlyQueryParam for Without<F, Q>
+where
+    F: Filter,
+    Q: ReadOnlyQueryParam,
+{
+}
+
 #[doc(hidden)]
 pub struct QryWithoutFilterState<F, Q> {
     filter: F,
@@ -135,6 +142,13 @@ where
     type Fetch<'w> = QryWithFilterFetch<F, Q::Fetch<'w>>;
 }
 
+unsafe impl<F, Q> ReadOnlyQueryParam for With<F, Q>
+where
+    F: Filter,
+    Q: ReadOnlyQueryParam,
+{
+}
+
 #[doc(hidden)]
 pub struct QryWithFilterState<F, S> {
     filter: F,
@@ -20,6 +20,13 @@ pub trait QueryParam {
     type Fetch<'w>: QueryParamFetch<'w, State = Self::State>;
 }
 
+/// Marker for query parameters, that only provide shared access.
+///
+/// # Safety
+/// The items of the query must only borrow data immutably, so multiple items
+/// for the same entity can exist at the same time.
+pub unsafe trait ReadOnlyQueryParam: QueryParam {}
+
 /// # Safety
 /// update_access should mark all used resources with ther usage.
 pub unsafe trait QueryParamState: Send + Sync + Sized + 'static {
@@ -68,17 +75,32 @@ where
     world_resource_id: ResourceId<WorldInner>,
     param_state: S,
 
+    archetype_generation: AtomicUsize,
     last_archetype_index: AtomicUsize,
     updating_archetypes: Mutex<()>,
     matching_archetypes_p: AtomicPtr<ArchetypeSet>,
+
+    // re-used by sorted iterations
+    sorted_scratch: Mutex<SortedScratch>,
 }
 
+#[derive(Default)]
+struct SortedScratch {
+    // (type-erased `&K` or null, archetype, index)
+    keyed: Vec<(*const (), ArchetypeId, usize)>,
+    entries: Vec<(ArchetypeId, usize)>,
+}
+
+// SAFETY: `keyed` is always empty while it is stored here: it is taken out
+// by `iter_sorted_by_key`, and only given back after it was drained.
+unsafe impl Send for SortedScratch {}
+
 impl<S> QueryState<S>
 where
     S: QueryParamState,
 {
     pub fn new(resources: &mut Resources) -> Self {
-        let world_id = resources.init::<WorldInner>();
+        let world_id = crate::world::init_main_world(resources);
         let world = resources.borrow_res_id(world_id).unwrap();
         Self::from_world(resources, &world, world_id)
     }
@@ -94,9 +116,11 @@ where
         let query = Self {
             world_resource_id: resource_id,
             param_state: state,
+            archetype_generation: AtomicUsize::new(0),
             last_archetype_index: AtomicUsize::new(0),
             updating_archetypes: Mutex::new(()),
             matching_archetypes_p: AtomicPtr::new(std::ptr::null_mut()),
+            sorted_scratch: Mutex::new(SortedScratch::default()),
         };
         query.update_archetypes(world);
         query
@@ -105,14 +129,25 @@ where
     fn update_archetypes(&self, world: &WorldInner) {
         let archetypes = &world.archetypes;
         let last_archetype_index = archetypes.len();
-        let old_archetype_index = self.last_archetype_index.load(Ordering::Relaxed);
-        if old_archetype_index >= last_archetype_index {
+        let generation = archetypes.generation();
+        if self.archetype_generation.load(Ordering::Acquire) == generation
+            && self.last_archetype_index.load(Ordering::Acquire) >= last_archetype_index
+        {
             // no new archetypes
             return;
         }
         let lock = self.updating_archetypes.lock();
 
         let mut archetypes_scratch: Option<Box<ArchetypeSet>> = None;
+        let mut old_archetype_index = self.last_archetype_index.load(Ordering::Acquire);
+        if self.archetype_generation.load(Ordering::Acquire) != generation {
+            // empty archetypes were removed, and the ids have changed: start over
+            archetypes_scratch = Some(Box::default());
+            old_archetype_index = 0;
+        } else if old_archetype_index >= last_archetype_index {
+            // another thread has updated the set while we were waiting
+            return;
+        }
 
         for index in old_archetype_index..last_archetype_index {
             let id = ArchetypeId::new(index);
@@ -136,21 +171,23 @@ where
             // replace
             let old = self
                 .matching_archetypes_p
-                .swap(Box::into_raw(new), Ordering::Relaxed);
+                .swap(Box::into_raw(new), Ordering::AcqRel);
             if !old.is_null() {
                 unsafe { drop(Box::from_raw(old)) }
             }
         }
 
+        self.archetype_generation
+            .store(generation, Ordering::Release);
         self.last_archetype_index
-            .store(last_archetype_index, Ordering::Relaxed);
+            .store(last_archetype_index, Ordering::Release);
 
         drop(lock);
     }
 
     fn matching_archetypes(&self) -> &ArchetypeSet {
         static EMPTY: ArchetypeSet = ArchetypeSet::new();
-        let ptr = self.matching_archetypes_p.load(Ordering::Relaxed);
+        let ptr = self.matching_archetypes_p.load(Ordering::Acquire);
         if ptr.is_null() {
             &EMPTY
         } else {
@@ -172,23 +209,15 @@ mod test {
 
     use pulz_schedule::resource::Resources;
 
-    use crate::{component::Component, prelude::Query, WorldExt};
+    use crate::{component::Component, prelude::Query, query::exec::QuerySortError, WorldExt};
 
-    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Component)]
     struct A(usize);
 
     #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
     #[component(storage = "crate::storage::DenseStorage")]
     struct B(usize);
 
-    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
-    #[component(sparse)]
-    struct C(usize);
-
-    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
-    #[component(storage = "DenseStorage")] // shortcut for `pulz_ecs::storage::DenseStorage`
-    struct D(usize);
-
     #[test]
     fn test_query() {
         let mut resources = Resources::new();
@@ -322,4 +351,131 @@ mod test {
         assert_eq!(750, counter3);
         assert_eq!(374750, sum3);
     }
+
+    #[test]
+    fn test_query_sorted_by_key() {
+        let mut resources = Resources::new();
+        {
+            let mut world = resources.world_mut();
+            for i in 0..100 {
+                let key = (i * 37) % 100;
+                match i % 3 {
+                    0 => world.spawn().insert(A(key)),
+                    1 => world.spawn().insert(A(key)).insert(B(i)),
+                    _ => world.spawn().insert(B(i)), // no key
+                };
+            }
+        }
+
+        let mut q = Query::<(Option<&A>, &B)>::new(&mut resources);
+        for _ in 0..2 {
+            let keys: Vec<Option<usize>> = q
+                .iter_sorted_by_key::<A>()
+                .unwrap()
+                .map(|(a, _)| a.map(|a| a.0))
+                .collect();
+            let mut expected: Vec<Option<usize>> = (0..100)
+                .filter(|i| i % 3 != 0)
+                .map(|i| (i % 3 == 1).then_some((i * 37) % 100))
+                .collect();
+            expected.sort_by_key(|k| (k.is_none(), *k));
+            assert_eq!(expected, keys);
+        }
+        drop(q);
+
+        let mut q = Query::<&B>::new(&mut resources);
+        assert!(matches!(
+            q.iter_sorted_by_key::<A>(),
+            Err(QuerySortError::KeyNotInQuery(_))
+        ));
+        drop(q);
+
+        let mut q = Query::<(&mut A, &B)>::new(&mut resources);
+        assert!(matches!(
+            q.iter_sorted_by_key::<A>(),
+            Err(QuerySortError::MutableKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_query_sorted_by_key_panicking_cmp() {
+        #[derive(PartialEq, Eq, Component)]
+        struct Panicky(bool);
+        impl PartialOrd for Panicky {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Panicky {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                assert!(!self.0 && !other.0, "cmp panicked");
+                std::cmp::Ordering::Equal
+            }
+        }
+
+        let mut resources = Resources::new();
+        let b = {
+            let mut world = resources.world_mut();
+            world.spawn().insert(Panicky(false));
+            let b = world.spawn().insert(Panicky(true)).id();
+            b
+        };
+
+        let mut q = Query::<&Panicky>::new(&mut resources);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            q.iter_sorted_by_key::<Panicky>().map(|i| i.count())
+        }));
+        assert!(result.is_err());
+        drop(q);
+
+        resources.world_mut().despawn(b);
+        // same query-state: not poisoned
+        let mut q = Query::<&Panicky>::new(&mut resources);
+        assert_eq!(1, q.iter_sorted_by_key::<Panicky>().unwrap().count());
+    }
+
+    #[test]
+    fn test_query_combinations() {
+        let mut resources = Resources::new();
+        {
+            let mut world = resources.world_mut();
+            for i in 0..6 {
+                // spread over two archetypes
+                if i % 2 == 0 {
+                    world.spawn().insert(A(i));
+                } else {
+                    world.spawn().insert(A(i)).insert(B(i));
+                }
+            }
+        }
+
+        let mut q = Query::<&A>::new(&mut resources);
+        let iter = q.iter_combinations::<2>();
+        assert_eq!((15, Some(15)), iter.size_hint());
+        let mut pairs: Vec<(usize, usize)> =
+            iter.map(|[a, b]| (a.0.min(b.0), a.0.max(b.0))).collect();
+        pairs.sort_unstable();
+        pairs.dedup();
+        assert_eq!(15, pairs.len());
+        assert!(pairs.iter().all(|(a, b)| a != b));
+
+        let mut iter = q.iter_combinations::<3>();
+        assert_eq!(20, iter.size_hint().0);
+        iter.next();
+        assert_eq!(19, iter.size_hint().0);
+        assert_eq!(19, iter.count());
+        assert_eq!(0, q.iter_combinations::<7>().count());
+        drop(q);
+
+        let mut q = Query::<&mut A>::new(&mut resources);
+        let mut combinations = q.iter_combinations_mut::<2>();
+        while let Some([a, b]) = combinations.fetch_next() {
+            a.0 += 10;
+            b.0 += 10;
+        }
+        let mut values: Vec<usize> = q.iter().map(|a| a.0).collect();
+        values.sort_unstable();
+        // every entity is part of 5 pairs
+        assert_eq!(vec![50, 51, 52, 53, 54, 55], values);
+    }
 }
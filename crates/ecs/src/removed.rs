@@ -8,14 +8,26 @@ use pulz_schedule::{
 
 use crate::{storage::Tracked, Component, Entity};
 
-// tracks removed components
+/// System parameter with the entities whose component `C` was removed since
+/// the last run of the system (including despawned entities).
+///
+/// Removals are only tracked for components with a
+/// [`Tracked`] storage (`#[component(tracked)]`). They are kept for two
+/// frames, so the system must run at least once per frame to not miss any.
+/// The entities are listed in the order of their removal.
 pub struct RemovedComponents<'a, C>(&'a [Entity], PhantomData<fn(C)>);
 
 #[doc(hidden)]
-pub struct RemovedComponentsState<C: Component>(ResourceId<C::Storage>);
+pub struct RemovedComponentsState<C: Component> {
+    storage_id: ResourceId<C::Storage>,
+    next_id: usize,
+}
 
 #[doc(hidden)]
-pub struct RemovedComponentsFetch<'a, C: Component>(Res<'a, C::Storage>);
+pub struct RemovedComponentsFetch<'a, C: Component> {
+    storage: Res<'a, C::Storage>,
+    next_id: usize,
+}
 
 impl<C: Component<Storage = Tracked<S>>, S: 'static> SystemData for RemovedComponents<'_, C> {
     type State = RemovedComponentsState<C>;
@@ -24,7 +36,8 @@ impl<C: Component<Storage = Tracked<S>>, S: 'static> SystemData for RemovedCompo
 
     #[inline]
     fn get<'a>(fetch: &'a mut Self::Fetch<'_>) -> Self::Item<'a> {
-        RemovedComponents(&fetch.0.removed, PhantomData)
+        let mut next_id = fetch.next_id;
+        RemovedComponents(fetch.storage.removed_since(&mut next_id), PhantomData)
     }
 }
 
@@ -34,11 +47,14 @@ unsafe impl<C: Component<Storage = Tracked<S>>, S: 'static> SystemDataState
 {
     #[inline]
     fn init(resources: &mut Resources) -> Self {
-        Self(resources.expect_id::<C::Storage>())
+        Self {
+            storage_id: resources.expect_id::<C::Storage>(),
+            next_id: 0,
+        }
     }
 
     fn update_access(&self, _resources: &Resources, access: &mut ResourceAccess) {
-        access.add_shared_checked(self.0);
+        access.add_shared_checked(self.storage_id);
     }
 }
 
@@ -48,7 +64,11 @@ impl<'r, C: Component<Storage = Tracked<S>>, S: 'static> SystemDataFetch<'r>
     type State = RemovedComponentsState<C>;
     #[inline]
     fn fetch(res: &'r Resources, state: &'r mut Self::State) -> Self {
-        Self(res.borrow_res_id(state.0).expect("storage"))
+        let storage = res.borrow_res_id(state.storage_id).expect("storage");
+        let next_id = state.next_id;
+        // the removals are marked as seen for the next run
+        storage.removed_since(&mut state.next_id);
+        Self { storage, next_id }
     }
 }
 
@@ -58,3 +78,47 @@ impl<C> Deref for RemovedComponents<'_, C> {
         self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{schedule::Schedule, WorldExt};
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+    #[component(tracked)]
+    struct A(usize);
+
+    #[test]
+    fn test_removed_components() {
+        let mut resources = Resources::new();
+        let (e1, e2, e3) = {
+            let mut world = resources.world_mut();
+            let e1 = world.spawn().insert(A(1)).id();
+            let e2 = world.spawn().insert(A(2)).id();
+            let e3 = world.spawn().insert(A(3)).id();
+            (e1, e2, e3)
+        };
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen2 = seen.clone();
+        let mut schedule = resources.remove::<Schedule>().unwrap();
+        schedule.add_system(move |removed: RemovedComponents<'_, A>| {
+            *seen2.lock().unwrap() = removed.to_vec();
+        });
+
+        resources.world_mut().entity_mut(e1).unwrap().remove::<A>();
+        resources.world_mut().despawn(e2);
+        schedule.run(&mut resources);
+        assert_eq!(vec![e1, e2], *seen.lock().unwrap());
+
+        // only removals since the last run
+        schedule.run(&mut resources);
+        assert!(seen.lock().unwrap().is_empty());
+
+        resources.world_mut().despawn(e3);
+        schedule.run(&mut resources);
+        assert_eq!(vec![e3], *seen.lock().unwrap());
+    }
+}
@@ -0,0 +1,585 @@
+//! Serializable scenes.
+//!
+//! A [`Scene`] is a list of entities with the values of their components.
+//! Only components registered in [`SceneComponents`] are written to, or
+//! spawned from a scene. The component values are serialized directly into
+//! the target format with [`SceneComponents::serializer`], and deserialized
+//! with [`SceneComponents::deserializer`], so a scene can be stored in any
+//! format supported by `serde` (e.g. RON or JSON).
+//!
+//! Requires the `serde` feature.
+
+use std::{
+    any::{type_name, Any, TypeId},
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+};
+
+use serde::{
+    de::{self, DeserializeOwned, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    ser::{self, SerializeMap, SerializeSeq, SerializeStruct},
+    Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    component::Component,
+    entity::Entity,
+    world::{World, WorldMut},
+};
+
+/// The value of a component in a [`Scene`].
+pub type SceneValue = Box<dyn Any + Send + Sync>;
+
+/// A list of entities with their components.
+#[derive(Debug, Default)]
+pub struct Scene {
+    pub entities: Vec<SceneEntity>,
+}
+
+pub struct SceneEntity {
+    /// The id of the entity at the time it was written to the scene.
+    ///
+    /// Entities are spawned with new ids; see [`EntityMap`].
+    pub entity: Entity,
+    /// The component values by the name they are registered with.
+    pub components: BTreeMap<Cow<'static, str>, SceneValue>,
+}
+
+impl fmt::Debug for SceneEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SceneEntity")
+            .field("entity", &self.entity)
+            .field("components", &self.components.keys())
+            .finish()
+    }
+}
+
+/// Maps the entity ids stored in a [`Scene`] to the entities spawned from it.
+#[derive(Clone, Debug, Default)]
+pub struct EntityMap(HashMap<Entity, Entity>);
+
+impl EntityMap {
+    #[inline]
+    pub fn get(&self, scene_entity: Entity) -> Option<Entity> {
+        self.0.get(&scene_entity).copied()
+    }
+
+    /// Returns the spawned entity for the given scene entity, or the entity
+    /// itself, if it is not part of the scene.
+    #[inline]
+    pub fn map(&self, entity: Entity) -> Entity {
+        self.get(entity).unwrap_or(entity)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.0.iter().map(|(k, v)| (*k, *v))
+    }
+}
+
+/// Components that reference other entities.
+///
+/// Register them with [`SceneComponents::add_mapped`], so the references
+/// are updated, when a scene is spawned.
+pub trait MapEntities {
+    fn map_entities(&mut self, entity_map: &EntityMap);
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    /// The scene contains a component that was not registered (with the
+    /// same type).
+    UnknownComponent(String),
+    /// The scene contains the same entity more than once.
+    DuplicateEntity(Entity),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownComponent(name) => write!(f, "unknown component `{name}`"),
+            Self::DuplicateEntity(entity) => write!(f, "duplicate scene entity {entity:?}"),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+type WriteFn = fn(&World<'_>, Entity) -> Option<SceneValue>;
+type SerializeFn = fn(&(dyn Any + Send + Sync)) -> Option<&dyn erased_serde::Serialize>;
+type DeserializeFn =
+    fn(&mut dyn erased_serde::Deserializer<'_>) -> Result<SceneValue, erased_serde::Error>;
+type SpawnFn = fn(&mut WorldMut<'_>, Entity, &(dyn Any + Send + Sync), &EntityMap);
+
+struct SceneComponent {
+    name: Cow<'static, str>,
+    type_id: TypeId,
+    write: WriteFn,
+    serialize: SerializeFn,
+    deserialize: DeserializeFn,
+    spawn: SpawnFn,
+}
+
+/// The components that are written to and spawned from a [`Scene`].
+#[derive(Default)]
+pub struct SceneComponents {
+    components: Vec<SceneComponent>,
+    by_name: BTreeMap<Cow<'static, str>, usize>,
+}
+
+impl SceneComponents {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a component using its type-name.
+    #[inline]
+    #[must_use]
+    pub fn with<T>(mut self) -> Self
+    where
+        T: Component + Clone + Serialize + DeserializeOwned,
+    {
+        self.add::<T>(type_name::<T>());
+        self
+    }
+
+    /// Registers a component under the given name.
+    pub fn add<T>(&mut self, name: impl Into<Cow<'static, str>>) -> &mut Self
+    where
+        T: Component + Clone + Serialize + DeserializeOwned,
+    {
+        self.insert::<T>(name.into(), spawn_component::<T>)
+    }
+
+    /// Registers a component, that references other entities, under the
+    /// given name.
+    pub fn add_mapped<T>(&mut self, name: impl Into<Cow<'static, str>>) -> &mut Self
+    where
+        T: Component + Clone + MapEntities + Serialize + DeserializeOwned,
+    {
+        self.insert::<T>(name.into(), spawn_mapped_component::<T>)
+    }
+
+    fn insert<T>(&mut self, name: Cow<'static, str>, spawn: SpawnFn) -> &mut Self
+    where
+        T: Component + Clone + Serialize + DeserializeOwned,
+    {
+        let type_id = TypeId::of::<T>();
+        let component = SceneComponent {
+            name: name.clone(),
+            type_id,
+            write: write_component::<T>,
+            serialize: serialize_component::<T>,
+            deserialize: deserialize_component::<T>,
+            spawn,
+        };
+        if let Some(index) = self.components.iter().position(|c| c.type_id == type_id) {
+            self.by_name.remove(&self.components[index].name);
+            self.components[index] = component;
+            self.by_name.insert(name, index);
+        } else {
+            self.by_name.insert(name, self.components.len());
+            self.components.push(component);
+        }
+        self
+    }
+
+    fn get(&self, name: &str) -> Option<&SceneComponent> {
+        self.by_name.get(name).map(|&index| &self.components[index])
+    }
+
+    /// Writes the registered components of the given entities into a new
+    /// [`Scene`].
+    ///
+    /// Entities that don't exist are skipped.
+    pub fn write_scene(
+        &self,
+        world: &World<'_>,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> Result<Scene, SceneError> {
+        let mut scene = Scene::default();
+        let mut written = HashSet::new();
+        for entity in entities {
+            if !world.entities().contains(entity) {
+                continue;
+            }
+            if !written.insert(entity) {
+                return Err(SceneError::DuplicateEntity(entity));
+            }
+            let mut components = BTreeMap::new();
+            for component in &self.components {
+                if let Some(value) = (component.write)(world, entity) {
+                    components.insert(component.name.clone(), value);
+                }
+            }
+            scene.entities.push(SceneEntity { entity, components });
+        }
+        Ok(scene)
+    }
+
+    /// Writes the registered components of all entities into a new [`Scene`].
+    #[inline]
+    pub fn write_world(&self, world: &World<'_>) -> Result<Scene, SceneError> {
+        self.write_scene(world, world.entities().iter())
+    }
+
+    /// Returns a serializable view of the `scene`, that serializes the
+    /// component values with their registered types.
+    #[inline]
+    pub fn serializer<'a>(&'a self, scene: &'a Scene) -> SceneSerializer<'a> {
+        SceneSerializer {
+            components: self,
+            scene,
+        }
+    }
+
+    /// Returns a [`DeserializeSeed`] for a [`Scene`], that deserializes the
+    /// component values with their registered types.
+    ///
+    /// Unknown components and duplicate entities are rejected.
+    #[inline]
+    pub fn deserializer(&self) -> SceneDeserializer<'_> {
+        SceneDeserializer { components: self }
+    }
+
+    /// Spawns new entities for all entities of the `scene`.
+    ///
+    /// Returns the mapping from the entity-ids of the scene to the spawned
+    /// entities.
+    pub fn spawn_scene(
+        &self,
+        world: &mut WorldMut<'_>,
+        scene: &Scene,
+    ) -> Result<EntityMap, SceneError> {
+        // check the scene first, so no entities are spawned on error
+        let mut entity_map = EntityMap::default();
+        for scene_entity in &scene.entities {
+            if entity_map
+                .0
+                .insert(scene_entity.entity, scene_entity.entity)
+                .is_some()
+            {
+                return Err(SceneError::DuplicateEntity(scene_entity.entity));
+            }
+            for (name, value) in &scene_entity.components {
+                if !matches!(self.get(name), Some(c) if c.type_id == (**value).type_id()) {
+                    return Err(SceneError::UnknownComponent(name.to_string()));
+                }
+            }
+        }
+
+        for scene_entity in &scene.entities {
+            let entity = world.spawn().id();
+            entity_map.0.insert(scene_entity.entity, entity);
+        }
+
+        for scene_entity in &scene.entities {
+            let entity = entity_map.map(scene_entity.entity);
+            for (name, value) in &scene_entity.components {
+                let component = self.get(name).unwrap();
+                (component.spawn)(world, entity, value.as_ref(), &entity_map);
+            }
+        }
+        Ok(entity_map)
+    }
+}
+
+fn write_component<T>(world: &World<'_>, entity: Entity) -> Option<SceneValue>
+where
+    T: Component + Clone,
+{
+    let entity = world.entity(entity)?;
+    let value = entity.borrow::<T>()?;
+    Some(Box::new(T::clone(&value)))
+}
+
+fn serialize_component<T>(value: &(dyn Any + Send + Sync)) -> Option<&dyn erased_serde::Serialize>
+where
+    T: Component + Serialize,
+{
+    let value: &dyn erased_serde::Serialize = value.downcast_ref::<T>()?;
+    Some(value)
+}
+
+fn deserialize_component<T>(
+    deserializer: &mut dyn erased_serde::Deserializer<'_>,
+) -> Result<SceneValue, erased_serde::Error>
+where
+    T: Component + DeserializeOwned,
+{
+    let value: T = erased_serde::deserialize(deserializer)?;
+    Ok(Box::new(value))
+}
+
+fn spawn_component<T>(
+    world: &mut WorldMut<'_>,
+    entity: Entity,
+    value: &(dyn Any + Send + Sync),
+    _entity_map: &EntityMap,
+) where
+    T: Component + Clone,
+{
+    if let (Some(value), Some(mut entity)) = (value.downcast_ref::<T>(), world.entity_mut(entity)) {
+        entity.insert(value.clone());
+    }
+}
+
+fn spawn_mapped_component<T>(
+    world: &mut WorldMut<'_>,
+    entity: Entity,
+    value: &(dyn Any + Send + Sync),
+    entity_map: &EntityMap,
+) where
+    T: Component + Clone + MapEntities,
+{
+    if let (Some(value), Some(mut entity)) = (value.downcast_ref::<T>(), world.entity_mut(entity)) {
+        let mut value = value.clone();
+        value.map_entities(entity_map);
+        entity.insert(value);
+    }
+}
+
+const SCENE_FIELDS: &[&str] = &["entities"];
+const ENTITY_FIELDS: &[&str] = &["entity", "components"];
+
+/// Serializes a [`Scene`]; see [`SceneComponents::serializer`].
+pub struct SceneSerializer<'a> {
+    components: &'a SceneComponents,
+    scene: &'a Scene,
+}
+
+struct EntitiesSerializer<'a>(&'a SceneComponents, &'a [SceneEntity]);
+struct EntitySerializer<'a>(&'a SceneComponents, &'a SceneEntity);
+struct ComponentsSerializer<'a>(
+    &'a SceneComponents,
+    &'a BTreeMap<Cow<'static, str>, SceneValue>,
+);
+
+impl Serialize for SceneSerializer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Scene", SCENE_FIELDS.len())?;
+        state.serialize_field(
+            "entities",
+            &EntitiesSerializer(self.components, &self.scene.entities),
+        )?;
+        state.end()
+    }
+}
+
+impl Serialize for EntitiesSerializer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.1.len()))?;
+        for entity in self.1 {
+            seq.serialize_element(&EntitySerializer(self.0, entity))?;
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for EntitySerializer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("SceneEntity", ENTITY_FIELDS.len())?;
+        state.serialize_field("entity", &self.1.entity)?;
+        state.serialize_field(
+            "components",
+            &ComponentsSerializer(self.0, &self.1.components),
+        )?;
+        state.end()
+    }
+}
+
+impl Serialize for ComponentsSerializer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.1.len()))?;
+        for (name, value) in self.1 {
+            let value = self
+                .0
+                .get(name)
+                .and_then(|component| (component.serialize)(value.as_ref()))
+                .ok_or_else(|| ser::Error::custom(format_args!("unknown component `{name}`")))?;
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Deserializes a [`Scene`]; see [`SceneComponents::deserializer`].
+#[derive(Copy, Clone)]
+pub struct SceneDeserializer<'a> {
+    components: &'a SceneComponents,
+}
+
+#[derive(Copy, Clone)]
+struct EntitiesSeed<'a>(&'a SceneComponents);
+#[derive(Copy, Clone)]
+struct EntitySeed<'a>(&'a SceneComponents);
+#[derive(Copy, Clone)]
+struct ComponentsSeed<'a>(&'a SceneComponents);
+struct ComponentSeed<'a>(&'a SceneComponent);
+
+impl<'de> DeserializeSeed<'de> for SceneDeserializer<'_> {
+    type Value = Scene;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Scene, D::Error> {
+        deserializer.deserialize_struct("Scene", SCENE_FIELDS, self)
+    }
+}
+
+impl<'de> Visitor<'de> for SceneDeserializer<'_> {
+    type Value = Scene;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a scene")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Scene, A::Error> {
+        let entities = seq
+            .next_element_seed(EntitiesSeed(self.components))?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        Ok(Scene { entities })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Scene, A::Error> {
+        let mut entities = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "entities" if entities.is_some() => {
+                    return Err(de::Error::duplicate_field("entities"));
+                }
+                "entities" => entities = Some(map.next_value_seed(EntitiesSeed(self.components))?),
+                _ => return Err(de::Error::unknown_field(&key, SCENE_FIELDS)),
+            }
+        }
+        let entities = entities.ok_or_else(|| de::Error::missing_field("entities"))?;
+        Ok(Scene { entities })
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for EntitiesSeed<'_> {
+    type Value = Vec<SceneEntity>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for EntitiesSeed<'_> {
+    type Value = Vec<SceneEntity>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a list of scene entities")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut entities = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        let mut seen = HashSet::new();
+        while let Some(entity) = seq.next_element_seed(EntitySeed(self.0))? {
+            if !seen.insert(entity.entity) {
+                return Err(de::Error::custom(SceneError::DuplicateEntity(
+                    entity.entity,
+                )));
+            }
+            entities.push(entity);
+        }
+        Ok(entities)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for EntitySeed<'_> {
+    type Value = SceneEntity;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<SceneEntity, D::Error> {
+        deserializer.deserialize_struct("SceneEntity", ENTITY_FIELDS, self)
+    }
+}
+
+impl<'de> Visitor<'de> for EntitySeed<'_> {
+    type Value = SceneEntity;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a scene entity")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<SceneEntity, A::Error> {
+        let entity = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let components = seq
+            .next_element_seed(ComponentsSeed(self.0))?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(SceneEntity { entity, components })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<SceneEntity, A::Error> {
+        let mut entity = None;
+        let mut components = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "entity" if entity.is_some() => return Err(de::Error::duplicate_field("entity")),
+                "entity" => entity = Some(map.next_value()?),
+                "components" if components.is_some() => {
+                    return Err(de::Error::duplicate_field("components"));
+                }
+                "components" => components = Some(map.next_value_seed(ComponentsSeed(self.0))?),
+                _ => return Err(de::Error::unknown_field(&key, ENTITY_FIELDS)),
+            }
+        }
+        Ok(SceneEntity {
+            entity: entity.ok_or_else(|| de::Error::missing_field("entity"))?,
+            components: components.ok_or_else(|| de::Error::missing_field("components"))?,
+        })
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for ComponentsSeed<'_> {
+    type Value = BTreeMap<Cow<'static, str>, SceneValue>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ComponentsSeed<'_> {
+    type Value = BTreeMap<Cow<'static, str>, SceneValue>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map of components")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut components = BTreeMap::new();
+        while let Some(name) = map.next_key::<String>()? {
+            let component = self
+                .0
+                .get(&name)
+                .ok_or_else(|| de::Error::custom(SceneError::UnknownComponent(name.clone())))?;
+            let value = map.next_value_seed(ComponentSeed(component))?;
+            if components.insert(component.name.clone(), value).is_some() {
+                return Err(de::Error::custom(format_args!(
+                    "duplicate component `{name}`"
+                )));
+            }
+        }
+        Ok(components)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for ComponentSeed<'_> {
+    type Value = SceneValue;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<SceneValue, D::Error> {
+        let mut deserializer = <dyn erased_serde::Deserializer<'_>>::erase(deserializer);
+        (self.0.deserialize)(&mut deserializer).map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,393 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::Arc,
+};
+
+use crate::{
+    archetype::{ArchetypeId, Archetypes},
+    component::{Component, Components},
+    entity::Entity,
+    resource::Resources,
+    storage::Storage,
+    world::{World, WorldMut},
+};
+
+type SnapshotData = Arc<dyn Any + Send + Sync>;
+type CaptureFn = fn(&World<'_>, Option<&SnapshotData>) -> SnapshotData;
+type RestoreFn = fn(&mut WorldMut<'_>, &SnapshotData);
+
+struct SnapshotComponent {
+    type_id: TypeId,
+    capture: CaptureFn,
+    restore: RestoreFn,
+}
+
+/// Describes the components that are captured in a [`Snapshot`].
+///
+/// Only components that were added with [`SnapshotComponents::add`] are
+/// captured and restored; all other components are ignored.
+///
+/// The captured values are stored per archetype, and are shared
+/// copy-on-write: [`SnapshotComponents::recapture`] only clones the values of
+/// archetypes that changed since a previous snapshot, and
+/// [`SnapshotComponents::restore`] only writes the values that differ from
+/// the snapshot.
+///
+/// ```
+/// # use pulz_ecs::{prelude::*, snapshot::SnapshotComponents, storage::ArchetypeStorage};
+/// #[derive(Clone, Debug, PartialEq)]
+/// struct Position(f32);
+///
+/// impl Component for Position {
+///     type Storage = ArchetypeStorage<Self>;
+/// }
+///
+/// let mut resources = Resources::new();
+/// let entity = resources.world_mut().spawn().insert(Position(1.0)).id();
+///
+/// let components = SnapshotComponents::new().with::<Position>();
+/// let snapshot = components.capture(&resources.world());
+///
+/// resources.world_mut().entity_mut(entity).unwrap().insert(Position(2.0));
+///
+/// components.restore(&mut resources.world_mut(), &snapshot);
+/// let world = resources.world();
+/// let position = world.entity(entity).unwrap().borrow::<Position>().map(|p| p.0);
+/// assert_eq!(Some(1.0), position);
+/// ```
+#[derive(Default)]
+pub struct SnapshotComponents {
+    components: Vec<SnapshotComponent>,
+}
+
+/// The captured state of the components described by [`SnapshotComponents`].
+///
+/// Cloning a snapshot is cheap, because the captured values are shared.
+#[derive(Clone)]
+pub struct Snapshot {
+    data: Vec<(TypeId, SnapshotData)>,
+}
+
+impl Snapshot {
+    fn get(&self, type_id: TypeId) -> Option<&SnapshotData> {
+        self.data
+            .iter()
+            .find(|(t, _)| *t == type_id)
+            .map(|(_, data)| data)
+    }
+}
+
+impl SnapshotComponents {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with<T>(mut self) -> Self
+    where
+        T: Component + Clone + PartialEq,
+    {
+        self.add::<T>();
+        self
+    }
+
+    pub fn add<T>(&mut self) -> &mut Self
+    where
+        T: Component + Clone + PartialEq,
+    {
+        let type_id = TypeId::of::<T>();
+        if !self.components.iter().any(|c| c.type_id == type_id) {
+            self.components.push(SnapshotComponent {
+                type_id,
+                capture: capture_component::<T>,
+                restore: restore_component::<T>,
+            });
+        }
+        self
+    }
+
+    /// Captures the current values of the registered components of all
+    /// entities in the `world`.
+    pub fn capture(&self, world: &World<'_>) -> Snapshot {
+        let data = self
+            .components
+            .iter()
+            .map(|c| (c.type_id, (c.capture)(world, None)))
+            .collect();
+        Snapshot { data }
+    }
+
+    /// Like [`Self::capture`], but the values of archetypes that didn't change
+    /// since the `previous` snapshot are shared with it instead of being
+    /// cloned.
+    pub fn recapture(&self, world: &World<'_>, previous: &Snapshot) -> Snapshot {
+        let data = self
+            .components
+            .iter()
+            .map(|c| (c.type_id, (c.capture)(world, previous.get(c.type_id))))
+            .collect();
+        Snapshot { data }
+    }
+
+    /// Restores the registered components from the `snapshot`.
+    ///
+    /// Components are inserted, replaced or removed, so every entity ends up
+    /// with the component values it had when the snapshot was captured.
+    /// Components that still have the captured value are left untouched.
+    /// Entities are neither spawned nor despawned: entities that were despawned
+    /// after the snapshot was captured are skipped.
+    pub fn restore(&self, world: &mut WorldMut<'_>, snapshot: &Snapshot) {
+        for component in &self.components {
+            if let Some(data) = snapshot.get(component.type_id) {
+                (component.restore)(world, data);
+            }
+        }
+    }
+}
+
+// The captured values of the component `T` in one archetype. Columns are
+// shared between snapshots, when they didn't change.
+struct Column<T> {
+    archetype: ArchetypeId,
+    entities: Arc<[Entity]>,
+    values: Arc<[T]>,
+}
+
+impl<T> Clone for Column<T> {
+    fn clone(&self) -> Self {
+        Self {
+            archetype: self.archetype,
+            entities: self.entities.clone(),
+            values: self.values.clone(),
+        }
+    }
+}
+
+// sorted by archetype
+type ComponentData<T> = Vec<Column<T>>;
+
+// Calls `f` for every archetype with the entities, that have the component
+// `T`, and their values. Only the archetypes that contain `T` are visited
+// (sparse components are not part of the archetype, so all archetypes are
+// visited for them).
+fn for_each_column<T>(
+    res: &Resources,
+    archetypes: &Archetypes,
+    components: &Components,
+    mut f: impl FnMut(ArchetypeId, &[Entity], &[&T]),
+) where
+    T: Component,
+{
+    let Some(component_id) = components.id::<T>() else {
+        return;
+    };
+    let component = components.get(component_id).unwrap();
+    let storage = res
+        .borrow_res_id(component.storage_id.typed::<T::Storage>())
+        .expect("storage");
+    let mut entities = Vec::new();
+    let mut values = Vec::new();
+    for archetype in archetypes.iter() {
+        if component.archetype_component && !archetype.contains_component_id(component_id) {
+            continue;
+        }
+        entities.clear();
+        values.clear();
+        for (index, &entity) in archetype.entities.iter().enumerate() {
+            if let Some(value) = Storage::get(&*storage, entity, archetype.id, index) {
+                entities.push(entity);
+                values.push(value);
+            }
+        }
+        if !entities.is_empty() {
+            f(archetype.id, &entities, &values);
+        }
+    }
+}
+
+fn capture_component<T>(world: &World<'_>, previous: Option<&SnapshotData>) -> SnapshotData
+where
+    T: Component + Clone + PartialEq,
+{
+    let previous = previous.and_then(|p| p.downcast_ref::<ComponentData<T>>());
+    let mut data: ComponentData<T> = Vec::new();
+    for_each_column::<T>(
+        world,
+        world.archetypes(),
+        world.components(),
+        |archetype, entities, values| {
+            let previous = previous
+                .and_then(|p| {
+                    let i = p
+                        .binary_search_by_key(&archetype.index(), |c| c.archetype.index())
+                        .ok()?;
+                    Some(&p[i])
+                })
+                .filter(|p| *p.entities == *entities);
+            let column = match previous {
+                Some(p) if p.values.iter().zip(values).all(|(p, v)| p == *v) => p.clone(),
+                _ => Column {
+                    archetype,
+                    entities: previous.map_or_else(|| entities.into(), |p| p.entities.clone()),
+                    values: values.iter().copied().cloned().collect(),
+                },
+            };
+            data.push(column);
+        },
+    );
+    Arc::new(data)
+}
+
+fn restore_component<T>(world: &mut WorldMut<'_>, data: &SnapshotData)
+where
+    T: Component + Clone + PartialEq,
+{
+    let data = data
+        .downcast_ref::<ComponentData<T>>()
+        .expect("snapshot data");
+
+    // the captured values, that differ from the current values
+    let mut changed: HashMap<Entity, &T> = data
+        .iter()
+        .flat_map(|c| c.entities.iter().copied().zip(c.values.iter()))
+        .collect();
+    let mut added = Vec::new();
+    for_each_column::<T>(
+        world,
+        world.archetypes(),
+        world.components(),
+        |_, entities, values| {
+            for (&entity, &current) in entities.iter().zip(values) {
+                match changed.get(&entity) {
+                    // added after the snapshot was captured
+                    None => added.push(entity),
+                    Some(&value) if *value == *current => {
+                        changed.remove(&entity);
+                    }
+                    Some(_) => {}
+                }
+            }
+        },
+    );
+
+    for entity in added {
+        if let Some(mut entity) = world.entity_mut(entity) {
+            entity.remove::<T>();
+        }
+    }
+    // in the captured order, so restoring is deterministic
+    for column in data {
+        for (entity, value) in column.entities.iter().zip(column.values.iter()) {
+            if changed.contains_key(entity) {
+                if let Some(mut entity) = world.entity_mut(*entity) {
+                    entity.insert(T::clone(value));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pulz_schedule::resource::Resources;
+
+    use super::*;
+    use crate::WorldExt;
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+    struct A(usize);
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+    #[component(sparse)]
+    struct B(usize);
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+    struct C;
+
+    #[test]
+    fn test_restore_inserted_and_removed() {
+        let mut resources = Resources::new();
+        let (e1, e2) = {
+            let mut world = resources.world_mut();
+            let e1 = world.spawn().insert(A(1)).insert(B(1)).id();
+            let e2 = world.spawn().insert(A(2)).id();
+            (e1, e2)
+        };
+
+        let components = SnapshotComponents::new().with::<A>().with::<B>();
+        let snapshot = components.capture(&resources.world());
+
+        for _ in 0..2 {
+            {
+                let mut world = resources.world_mut();
+                world.entity_mut(e1).unwrap().remove::<A>().remove::<B>();
+                world.entity_mut(e2).unwrap().insert(A(20)).insert(B(20));
+            }
+
+            components.restore(&mut resources.world_mut(), &snapshot);
+
+            let world = resources.world();
+            let ent1 = world.entity(e1).unwrap();
+            assert_eq!(Some(A(1)), ent1.borrow::<A>().as_deref().copied());
+            assert_eq!(Some(B(1)), ent1.borrow::<B>().as_deref().copied());
+            let ent2 = world.entity(e2).unwrap();
+            assert_eq!(Some(A(2)), ent2.borrow::<A>().as_deref().copied());
+            assert!(!ent2.contains::<B>());
+        }
+    }
+
+    #[test]
+    fn test_recapture_shares_unchanged_values() {
+        let mut resources = Resources::new();
+        let (e1, e2, e3) = {
+            let mut world = resources.world_mut();
+            let e1 = world.spawn().insert(A(1)).id();
+            let e2 = world.spawn().insert(A(2)).insert(C).id();
+            let e3 = world.spawn().insert(A(3)).insert(C).id();
+            (e1, e2, e3)
+        };
+
+        let components = SnapshotComponents::new().with::<A>();
+        let first = components.capture(&resources.world());
+        resources.world_mut().entity_mut(e3).unwrap().insert(A(30));
+        let second = components.recapture(&resources.world(), &first);
+
+        let columns = |snapshot: &Snapshot| {
+            snapshot
+                .get(TypeId::of::<A>())
+                .unwrap()
+                .downcast_ref::<ComponentData<A>>()
+                .unwrap()
+                .clone()
+        };
+        let (first_columns, second_columns) = (columns(&first), columns(&second));
+        assert_eq!(2, second_columns.len());
+        // the archetype of `e1` didn't change
+        assert_eq!(&[e1], &*second_columns[0].entities);
+        assert!(Arc::ptr_eq(
+            &first_columns[0].values,
+            &second_columns[0].values
+        ));
+        // the archetype of `e2` and `e3` was copied
+        assert_eq!(&[e2, e3], &*second_columns[1].entities);
+        assert!(Arc::ptr_eq(
+            &first_columns[1].entities,
+            &second_columns[1].entities
+        ));
+        assert!(!Arc::ptr_eq(
+            &first_columns[1].values,
+            &second_columns[1].values
+        ));
+        assert_eq!(&[A(2), A(30)], &*second_columns[1].values);
+
+        components.restore(&mut resources.world_mut(), &first);
+        let world = resources.world();
+        assert_eq!(
+            Some(A(3)),
+            world.entity(e3).unwrap().borrow::<A>().as_deref().copied()
+        );
+    }
+}
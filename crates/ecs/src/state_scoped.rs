@@ -0,0 +1,66 @@
+use pulz_schedule::{state::States, system::system_fn::ExclusiveResources};
+
+use crate::{Component, Entity, WorldExt};
+
+/// Marks an entity, that belongs to a state (see [`pulz_schedule::state`]).
+///
+/// The entities are despawned by [`despawn_state_scoped`], when the state is
+/// left.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Component)]
+pub struct StateScoped<S: States>(pub S);
+
+/// Returns an exclusive system, that despawns all entities with
+/// `StateScoped(state)`.
+///
+/// Add it to the [`on_exit`](pulz_schedule::state::StateSchedules::on_exit)
+/// schedule of the state:
+///
+/// ```
+/// use pulz_ecs::{
+///     prelude::*,
+///     state::{State, StateSchedules},
+///     state_scoped::{despawn_state_scoped, StateScoped},
+/// };
+///
+/// #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// enum GameState {
+///     Menu,
+///     Playing,
+/// }
+///
+/// let mut resources = Resources::new();
+/// State::install_into(&mut resources, GameState::Menu);
+/// resources
+///     .get_mut::<StateSchedules<GameState>>()
+///     .unwrap()
+///     .on_exit(GameState::Menu)
+///     .add_system(despawn_state_scoped(GameState::Menu));
+/// let button = resources
+///     .world_mut()
+///     .spawn()
+///     .insert(StateScoped(GameState::Menu))
+///     .id();
+///
+/// let mut schedule = resources.remove::<Schedule>().unwrap();
+/// resources.get_mut::<State<GameState>>().unwrap().set(GameState::Playing);
+/// schedule.run(&mut resources);
+/// assert!(resources.world().entity(button).is_none());
+/// ```
+pub fn despawn_state_scoped<S: States>(state: S) -> impl FnMut(ExclusiveResources<'_>) {
+    move |mut res: ExclusiveResources<'_>| {
+        if res.world().components().id::<StateScoped<S>>().is_none() {
+            // no entity was ever scoped to a state of this type
+            return;
+        }
+        let entities: Vec<Entity> = res
+            .query::<(Entity, &StateScoped<S>)>()
+            .iter()
+            .filter(|(_, scoped)| scoped.0 == state)
+            .map(|(entity, _)| entity)
+            .collect();
+        let mut world = res.world_mut();
+        for entity in entities {
+            world.despawn(entity);
+        }
+    }
+}
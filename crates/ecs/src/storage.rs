@@ -1,4 +1,9 @@
-use std::any::{Any, TypeId};
+use std::{
+    any::{Any, TypeId},
+    marker::PhantomData,
+    mem::size_of,
+    ptr::NonNull,
+};
 
 use pulz_schedule::{
     impl_any_cast, label::CoreSystemPhase, resource::Resources, schedule::Schedule,
@@ -8,7 +13,6 @@ use slotmap::{SecondaryMap, SparseSecondaryMap};
 use crate::{
     archetype::{Archetype, ArchetypeId},
     component::ComponentDetails,
-    insert_sorted,
     resource::FromResourcesMut,
     Entity,
 };
@@ -62,6 +66,26 @@ pub trait Storage: Send + Sync + Any + FromResourcesMut {
         archetype: ArchetypeId,
         index: usize,
     ) -> Option<&mut Self::Component>;
+
+    /// Releases unused capacity (e.g. after a large number of entities was
+    /// despawned).
+    #[inline]
+    fn shrink_to_fit(&mut self) {}
+
+    /// Moves the data of the archetypes to their new ids, after empty
+    /// archetypes were removed (see [`WorldMut::shrink_to_fit`](crate::WorldMut::shrink_to_fit)).
+    ///
+    /// `remap` contains the new id for every old archetype id, or `None` when
+    /// the (empty) archetype was removed.
+    #[inline]
+    fn remap_archetypes(&mut self, _remap: &[Option<ArchetypeId>]) {}
+
+    /// Per-frame maintenance of the storages of additional worlds (see
+    /// [`WorldExt::add_world`](crate::WorldExt::add_world)).
+    ///
+    /// The storages of the main world use [`Self::install_systems`] instead.
+    #[inline]
+    fn maintain(&mut self) {}
 }
 
 pub trait AnyStorage: Send + Sync + Any {
@@ -78,6 +102,18 @@ pub trait AnyStorage: Send + Sync + Any {
         remove_from_index: usize,
         insert_to_archetype: ArchetypeId,
     ) -> Option<usize>;
+
+    fn shrink_to_fit(&mut self);
+    fn remap_archetypes(&mut self, remap: &[Option<ArchetypeId>]);
+    fn maintain(&mut self);
+
+    fn get_any(&self, entity: Entity, archetype: ArchetypeId, index: usize) -> Option<&dyn Any>;
+    fn get_any_mut(
+        &mut self,
+        entity: Entity,
+        archetype: ArchetypeId,
+        index: usize,
+    ) -> Option<&mut dyn Any>;
 }
 
 impl_any_cast!(dyn AnyStorage);
@@ -87,6 +123,16 @@ pub struct ArchetypeStorage<T> {
     tmp: Option<T>,
 }
 
+/// Storage for zero-sized (tag/marker) components.
+///
+/// Only the number of components per archetype is stored; the values
+/// themselves don't occupy any memory.
+pub struct TagStorage<T> {
+    lens: Vec<usize>,
+    pending: bool,
+    _phantom: PhantomData<T>,
+}
+
 pub type SlotStorage<T> = SecondaryMap<Entity, T>;
 pub type SparseStorage<T> = SparseSecondaryMap<Entity, T>;
 
@@ -105,6 +151,66 @@ impl<T> Default for ArchetypeStorage<T> {
     }
 }
 
+impl<T> Default for TagStorage<T> {
+    #[inline]
+    fn default() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::ASSERT_ZERO_SIZED;
+        Self {
+            lens: Vec::new(),
+            pending: false,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> TagStorage<T> {
+    const ASSERT_ZERO_SIZED: () = assert!(
+        size_of::<T>() == 0,
+        "TagStorage can only be used for zero-sized types"
+    );
+
+    #[inline]
+    fn store(value: T) {
+        std::mem::forget(value);
+    }
+
+    #[inline]
+    fn take() -> T {
+        // SAFETY: T is zero-sized, and there was a value of T that was
+        // forgotten in `store`.
+        unsafe { std::ptr::read(NonNull::<T>::dangling().as_ptr()) }
+    }
+
+    #[inline]
+    fn get_ref<'a>() -> &'a mut T {
+        // SAFETY: T is zero-sized, so every aligned non-null pointer is valid
+        unsafe { &mut *NonNull::<T>::dangling().as_ptr() }
+    }
+
+    #[inline]
+    fn len(&self, archetype: ArchetypeId) -> usize {
+        self.lens.get(archetype.index()).copied().unwrap_or(0)
+    }
+
+    fn drop_pending(&mut self) {
+        if std::mem::replace(&mut self.pending, false) {
+            drop(Self::take());
+        }
+    }
+}
+
+impl<T> Drop for TagStorage<T> {
+    fn drop(&mut self) {
+        if std::mem::needs_drop::<T>() {
+            self.drop_pending();
+            for _ in 0..self.lens.iter().sum() {
+                drop(Self::take());
+            }
+        }
+    }
+}
+
 fn vec_make_available<T: Default>(vec: &mut Vec<T>, index: usize) -> &mut T {
     if vec.len() <= index {
         vec.resize_with(index + 1, Default::default);
@@ -113,6 +219,23 @@ fn vec_make_available<T: Default>(vec: &mut Vec<T>, index: usize) -> &mut T {
     unsafe { vec.get_unchecked_mut(index) }
 }
 
+fn vec_truncate_trailing<T>(vec: &mut Vec<T>, is_empty: impl Fn(&T) -> bool) {
+    while matches!(vec.last(), Some(last) if is_empty(last)) {
+        vec.pop();
+    }
+    vec.shrink_to_fit();
+}
+
+fn vec_remap<T: Default>(vec: &mut Vec<T>, remap: &[Option<ArchetypeId>]) {
+    let mut result = Vec::new();
+    for (old_index, item) in vec.drain(..).enumerate() {
+        if let Some(Some(new_id)) = remap.get(old_index) {
+            *vec_make_available(&mut result, new_id.index()) = item;
+        }
+    }
+    *vec = result;
+}
+
 impl<T> Storage for ArchetypeStorage<T>
 where
     T: Send + Sync + 'static,
@@ -132,9 +255,7 @@ where
 
     #[inline]
     fn contains(&self, _entity: Entity, archetype: ArchetypeId, index: usize) -> bool {
-        self.data
-            .get(archetype.index())
-            .map_or(false, |col| index < col.len())
+        matches!(self.data.get(archetype.index()), Some(col) if index < col.len())
     }
 
     #[inline]
@@ -170,9 +291,7 @@ where
     }
 
     fn flush_push(&mut self, archetype: ArchetypeId) -> Option<usize> {
-        let Some(value) = self.tmp.take() else {
-            return None;
-        };
+        let value = self.tmp.take()?;
         let col = vec_make_available(&mut self.data, archetype.index());
         let index = col.len();
         col.push(value);
@@ -188,9 +307,7 @@ where
         if remove_from_archetype == insert_to_archetype {
             return None;
         }
-        let Some(col) = self.data.get_mut(remove_from_archetype.index()) else {
-            return None;
-        };
+        let col = self.data.get_mut(remove_from_archetype.index())?;
         if remove_from_index >= col.len() {
             return None;
         }
@@ -220,6 +337,135 @@ where
     ) -> Option<&mut Self::Component> {
         self.data.get_mut(archetype.index())?.get_mut(index)
     }
+
+    fn shrink_to_fit(&mut self) {
+        for col in &mut self.data {
+            col.shrink_to_fit();
+        }
+        vec_truncate_trailing(&mut self.data, Vec::is_empty);
+    }
+
+    fn remap_archetypes(&mut self, remap: &[Option<ArchetypeId>]) {
+        vec_remap(&mut self.data, remap);
+    }
+}
+
+impl<T> Storage for TagStorage<T>
+where
+    T: Send + Sync + 'static,
+{
+    const SPARSE: bool = false;
+    type Component = T;
+
+    #[inline]
+    fn fast_contains(
+        _res: &Resources,
+        _entity: Entity,
+        component: &ComponentDetails,
+        archetype: &Archetype,
+    ) -> bool {
+        archetype.components.contains(component.id())
+    }
+
+    #[inline]
+    fn contains(&self, _entity: Entity, archetype: ArchetypeId, index: usize) -> bool {
+        index < self.len(archetype)
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, _entity: Entity, archetype: ArchetypeId, index: usize) -> Option<T> {
+        self.drop_pending();
+        let len = self.lens.get_mut(archetype.index())?;
+        if index < *len {
+            *len -= 1;
+            Some(Self::take())
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn insert(&mut self, _entity: Entity, value: T) {
+        self.drop_pending();
+        Self::store(value);
+        self.pending = true;
+    }
+
+    fn flush_replace(&mut self, archetype: ArchetypeId, index: usize) -> bool {
+        if index < self.len(archetype) && std::mem::replace(&mut self.pending, false) {
+            // drop the old value; the pending value takes its place
+            drop(Self::take());
+            true
+        } else {
+            false
+        }
+    }
+
+    fn flush_push(&mut self, archetype: ArchetypeId) -> Option<usize> {
+        if !std::mem::replace(&mut self.pending, false) {
+            return None;
+        }
+        let len = vec_make_available(&mut self.lens, archetype.index());
+        let index = *len;
+        *len += 1;
+        Some(index)
+    }
+
+    fn swap_remove_and_insert(
+        &mut self,
+        remove_from_archetype: ArchetypeId,
+        remove_from_index: usize,
+        insert_to_archetype: ArchetypeId,
+    ) -> Option<usize> {
+        if remove_from_archetype == insert_to_archetype {
+            return None;
+        }
+        let len = self.lens.get_mut(remove_from_archetype.index())?;
+        if remove_from_index >= *len {
+            return None;
+        }
+        *len -= 1;
+        let len = vec_make_available(&mut self.lens, insert_to_archetype.index());
+        let index = *len;
+        *len += 1;
+        Some(index)
+    }
+
+    #[inline]
+    fn get(
+        &self,
+        entity: Entity,
+        archetype: ArchetypeId,
+        index: usize,
+    ) -> Option<&Self::Component> {
+        if Storage::contains(self, entity, archetype, index) {
+            Some(Self::get_ref())
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn get_mut(
+        &mut self,
+        entity: Entity,
+        archetype: ArchetypeId,
+        index: usize,
+    ) -> Option<&mut Self::Component> {
+        if Storage::contains(self, entity, archetype, index) {
+            Some(Self::get_ref())
+        } else {
+            None
+        }
+    }
+
+    fn shrink_to_fit(&mut self) {
+        vec_truncate_trailing(&mut self.lens, |len| *len == 0);
+    }
+
+    fn remap_archetypes(&mut self, remap: &[Option<ArchetypeId>]) {
+        vec_remap(&mut self.lens, remap);
+    }
 }
 
 impl<T> Storage for SparseStorage<T>
@@ -236,8 +482,10 @@ where
         component: &ComponentDetails,
         _archetype: &Archetype,
     ) -> bool {
-        res.borrow_res_id(component.storage_id.typed::<Self>())
-            .map_or(false, |s| s.contains_key(entity))
+        matches!(
+            res.borrow_res_id(component.storage_id.typed::<Self>()),
+            Some(s) if s.contains_key(entity)
+        )
     }
 
     #[inline]
@@ -293,16 +541,43 @@ where
     ) -> Option<&mut Self::Component> {
         self.get_mut(entity)
     }
+
+    fn shrink_to_fit(&mut self) {
+        // the map has no `shrink_to_fit`, so it is re-created with the
+        // remaining entries
+        let map = std::mem::take(self);
+        *self = map.into_iter().collect();
+    }
 }
 
+/// Storage that tracks the entities whose component was removed.
+///
+/// The removals of the current and the previous frame are kept, and can be
+/// accessed with the [`RemovedComponents`](crate::removed::RemovedComponents)
+/// system parameter.
 pub struct Tracked<S> {
     base: S,
-    pub(crate) removed: Vec<Entity>,
+    removed: Vec<Entity>,
+    // id of the first entry in `removed`
+    first_id: usize,
+    // id of the first entry that was removed in the current frame
+    frame_start_id: usize,
 }
 
 impl<S> Tracked<S> {
-    fn reset(&mut self) {
-        self.removed.clear();
+    fn update(&mut self) {
+        let drained = self.frame_start_id - self.first_id;
+        self.removed.drain(..drained);
+        self.first_id = self.frame_start_id;
+        self.frame_start_id = self.first_id + self.removed.len();
+    }
+
+    /// Returns the entities that were removed since `next_id`, and updates
+    /// `next_id` to the id after the last removal.
+    pub(crate) fn removed_since(&self, next_id: &mut usize) -> &[Entity] {
+        let offset = next_id.saturating_sub(self.first_id);
+        *next_id = self.first_id + self.removed.len();
+        &self.removed[offset.min(self.removed.len())..]
     }
 }
 
@@ -312,6 +587,8 @@ impl<S: FromResourcesMut> FromResourcesMut for Tracked<S> {
         Self {
             base: S::from_resources_mut(resources),
             removed: Vec::new(),
+            first_id: 0,
+            frame_start_id: 0,
         }
     }
 }
@@ -322,7 +599,7 @@ impl<S: Storage> Storage for Tracked<S> {
 
     fn install_systems(schedule: &mut Schedule) {
         schedule
-            .add_system(Self::reset)
+            .add_system(Self::update)
             .into_phase(CoreSystemPhase::First);
     }
 
@@ -349,7 +626,7 @@ impl<S: Storage> Storage for Tracked<S> {
         index: usize,
     ) -> Option<Self::Component> {
         let old = self.base.swap_remove(entity, archetype, index)?;
-        insert_sorted(&mut self.removed, entity);
+        self.removed.push(entity);
         Some(old)
     }
 
@@ -401,11 +678,29 @@ impl<S: Storage> Storage for Tracked<S> {
     ) -> Option<&mut Self::Component> {
         self.base.get_mut(entity, archetype, index)
     }
+
+    #[inline]
+    fn shrink_to_fit(&mut self) {
+        self.base.shrink_to_fit();
+        self.removed.shrink_to_fit();
+    }
+
+    #[inline]
+    fn remap_archetypes(&mut self, remap: &[Option<ArchetypeId>]) {
+        self.base.remap_archetypes(remap);
+    }
+
+    #[inline]
+    fn maintain(&mut self) {
+        self.base.maintain();
+        self.update();
+    }
 }
 
 impl<S> AnyStorage for S
 where
     S: Storage,
+    S::Component: Any,
 {
     fn component_type_id(&self) -> TypeId {
         S::component_type_id()
@@ -440,4 +735,107 @@ where
             insert_to_archetype,
         )
     }
+
+    fn shrink_to_fit(&mut self) {
+        S::shrink_to_fit(self)
+    }
+
+    fn remap_archetypes(&mut self, remap: &[Option<ArchetypeId>]) {
+        S::remap_archetypes(self, remap)
+    }
+
+    fn maintain(&mut self) {
+        S::maintain(self)
+    }
+
+    fn get_any(&self, entity: Entity, archetype: ArchetypeId, index: usize) -> Option<&dyn Any> {
+        let component: &dyn Any = S::get(self, entity, archetype, index)?;
+        Some(component)
+    }
+
+    fn get_any_mut(
+        &mut self,
+        entity: Entity,
+        archetype: ArchetypeId,
+        index: usize,
+    ) -> Option<&mut dyn Any> {
+        let component: &mut dyn Any = S::get_mut(self, entity, archetype, index)?;
+        Some(component)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{
+        prelude::*,
+        query::{With, Without},
+    };
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+    struct A(usize);
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+    #[component(sparse)]
+    struct B(usize);
+
+    static DROPPED_ENEMIES: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, Component)]
+    #[component(tag)]
+    struct Enemy;
+
+    impl Drop for Enemy {
+        fn drop(&mut self) {
+            DROPPED_ENEMIES.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_tag_components() {
+        let mut resources = Resources::new();
+        let mut world = resources.world_mut();
+        let e1 = world.spawn().insert(A(1)).insert(Enemy).id();
+        let e2 = world.spawn().insert(A(2)).id();
+        let e3 = world.spawn().insert(Enemy).insert(A(3)).id();
+        world.spawn().insert(Enemy);
+        // replacing a tag drops the old value
+        world.entity_mut(e3).unwrap().insert(Enemy);
+        assert_eq!(1, DROPPED_ENEMIES.load(Ordering::Relaxed));
+
+        assert!(world.entity(e1).unwrap().contains::<Enemy>());
+        assert!(world.entity(e1).unwrap().borrow::<Enemy>().is_some());
+        assert!(!world.entity(e2).unwrap().contains::<Enemy>());
+        drop(world);
+
+        let mut q = Query::<With<&Enemy, &A>>::new(&mut resources);
+        let mut found: Vec<_> = q.iter().map(|a| a.0).collect();
+        found.sort_unstable();
+        assert_eq!(vec![1, 3], found);
+        drop(q);
+
+        let mut q = Query::<Without<&Enemy, &A>>::new(&mut resources);
+        let found: Vec<_> = q.iter().map(|a| a.0).collect();
+        assert_eq!(vec![2], found);
+        drop(q);
+
+        // moving between archetypes keeps the tag
+        let mut world = resources.world_mut();
+        world.entity_mut(e1).unwrap().insert(B(1));
+        assert!(world.entity(e1).unwrap().contains::<Enemy>());
+        assert!(world.entity(e3).unwrap().contains::<Enemy>());
+
+        world.entity_mut(e1).unwrap().remove::<Enemy>();
+        assert_eq!(2, DROPPED_ENEMIES.load(Ordering::Relaxed));
+        assert!(!world.entity(e1).unwrap().contains::<Enemy>());
+        assert!(world.entity(e3).unwrap().contains::<Enemy>());
+        assert!(world.despawn(e3));
+        assert_eq!(3, DROPPED_ENEMIES.load(Ordering::Relaxed));
+        drop(world);
+
+        // the remaining tag is dropped with the storage
+        drop(resources);
+        assert_eq!(4, DROPPED_ENEMIES.load(Ordering::Relaxed));
+    }
 }
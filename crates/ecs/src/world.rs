@@ -3,22 +3,50 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
+use pulz_schedule::{
+    label::CoreSystemPhase, schedule::Schedule, system::system_fn::ExclusiveResources,
+};
+
 use crate::{
     archetype::Archetypes,
     component::{Component, ComponentId, Components},
     entity::{Entities, Entity},
     get_or_init_component,
     query::{Query, QueryParam},
-    resource::{RemovedResource, Res, Resources},
+    resource::{RemovedResource, Res, ResourceId, Resources},
+    storage::AnyStorage,
     WorldInner,
 };
 
+/// Identifies a world inside of a [`Resources`] collection.
+///
+/// See [`WorldExt::add_world`].
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct WorldId(ResourceId<WorldInner>);
+
+/// A new entity, that was spawned as a copy of another entity (see
+/// [`WorldMut::clone_entity`]).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ClonedEntity {
+    /// The id of the new entity.
+    pub id: Entity,
+    /// The components of the original entity, that were not copied, because
+    /// they have no clone function ([`Component::ON_CLONE`]).
+    pub skipped: Vec<ComponentId>,
+}
+
 pub struct World<'a> {
     pub(crate) res: &'a Resources,
     pub(crate) world: Res<'a, WorldInner>,
+    id: WorldId,
 }
 
 impl World<'_> {
+    #[inline]
+    pub fn id(&self) -> WorldId {
+        self.id
+    }
+
     #[inline]
     pub fn archetypes(&self) -> &Archetypes {
         &self.world.archetypes
@@ -41,6 +69,7 @@ impl Clone for World<'_> {
         Self {
             res: self.res,
             world: Res::clone(&self.world),
+            id: self.id,
         }
     }
 }
@@ -59,6 +88,11 @@ pub struct WorldMut<'a> {
 }
 
 impl WorldMut<'_> {
+    #[inline]
+    pub fn id(&self) -> WorldId {
+        WorldId(self.world.id())
+    }
+
     #[inline]
     pub fn archetypes(&self) -> &Archetypes {
         &self.world.archetypes
@@ -79,7 +113,7 @@ impl WorldMut<'_> {
     where
         T: Component,
     {
-        get_or_init_component::<T>(self.res, &mut self.world.components).1
+        get_or_init_component::<T>(self.res, &mut self.world).1
     }
 
     /// Removes the entity and all its components from the world.
@@ -115,6 +149,18 @@ impl DerefMut for WorldMut<'_> {
     }
 }
 
+/// Access to the worlds that are stored inside of a [`Resources`] collection.
+///
+/// Every `Resources` instance contains a main world ([`WorldExt::world`]).
+/// Additional worlds (for example a _render world_, or one world per server
+/// room) are created with [`WorldExt::add_world`], and are accessed by their
+/// [`WorldId`]. Systems with a [`Query`] parameter always access the main
+/// world.
+///
+/// Entities can be moved or copied between worlds with
+/// [`WorldExt::move_entity`] and [`WorldExt::copy_entity`] (or with
+/// [`WorldMut::move_entity_to`] and [`WorldMut::copy_entity_to`] between the
+/// worlds of different `Resources` instances).
 pub trait WorldExt {
     fn world(&self) -> World<'_>;
     fn world_mut(&mut self) -> WorldMut<'_>;
@@ -122,19 +168,88 @@ pub trait WorldExt {
     fn query<Q>(&mut self) -> Query<'_, Q>
     where
         Q: QueryParam + 'static;
+
+    /// Creates an additional, empty world.
+    ///
+    /// This can also be called from an exclusive system. The `Query`
+    /// parameters of systems still access the main world; use
+    /// [`Self::query_by_world_id`] for the additional world.
+    fn add_world(&mut self) -> WorldId;
+
+    /// Returns the world with the given id.
+    ///
+    /// Panics, when the world doesn't exist or is mutably borrowed.
+    fn world_by_id(&self, id: WorldId) -> World<'_>;
+
+    /// Returns the world with the given id for modification.
+    ///
+    /// Panics, when the world doesn't exist or is borrowed.
+    fn world_mut_by_id(&mut self, id: WorldId) -> WorldMut<'_>;
+
+    fn query_by_world_id<Q>(&mut self, id: WorldId) -> Query<'_, Q>
+    where
+        Q: QueryParam + 'static;
+
+    /// Moves the entity with all its components from the world `from` into
+    /// the world `to`.
+    ///
+    /// Returns the id of the entity in the world `to`, or `None` when the
+    /// entity doesn't exist in the world `from`.
+    fn move_entity(&mut self, entity: Entity, from: WorldId, to: WorldId) -> Option<Entity>;
+
+    /// Copies the entity from the world `from` into the world `to`.
+    ///
+    /// Only components with a clone function ([`Component::ON_CLONE`]) are
+    /// copied, the others are returned in [`ClonedEntity::skipped`] (see
+    /// [`WorldMut::clone_entity`]).
+    ///
+    /// Returns the new entity in the world `to`, or `None` when the entity
+    /// doesn't exist in the world `from`.
+    fn copy_entity(&mut self, entity: Entity, from: WorldId, to: WorldId) -> Option<ClonedEntity>;
 }
 
 impl WorldExt for Resources {
     #[inline]
     fn world(&self) -> World<'_> {
-        let world = self.borrow_res::<WorldInner>().expect("not initialized");
-        World { res: self, world }
+        let id = self.id::<WorldInner>().expect("not initialized");
+        self.world_by_id(WorldId(id))
     }
 
     #[inline]
     fn world_mut(&mut self) -> WorldMut<'_> {
-        let id = self.init::<WorldInner>();
-        let world = self.remove_id(id).unwrap();
+        let id = init_main_world(self);
+        self.world_mut_by_id(WorldId(id))
+    }
+
+    #[inline]
+    fn query<Q>(&mut self) -> Query<'_, Q>
+    where
+        Q: QueryParam,
+    {
+        Query::new(self)
+    }
+
+    fn add_world(&mut self) -> WorldId {
+        let id = self.insert_anonymous(WorldInner {
+            main: false,
+            ..Default::default()
+        });
+        install_maintain_worlds(self);
+        self.get_mut::<AdditionalWorlds>().unwrap().ids.push(id);
+        WorldId(id)
+    }
+
+    fn world_by_id(&self, id: WorldId) -> World<'_> {
+        let world = self.borrow_res_id(id.0).expect("world not available");
+        World {
+            res: self,
+            world,
+            id,
+        }
+    }
+
+    fn world_mut_by_id(&mut self, id: WorldId) -> WorldMut<'_> {
+        let world = self.remove_id(id.0).expect("world not available");
         WorldMut {
             res: self,
             world: ManuallyDrop::new(world),
@@ -142,10 +257,579 @@ impl WorldExt for Resources {
     }
 
     #[inline]
-    fn query<Q>(&mut self) -> Query<'_, Q>
+    fn query_by_world_id<Q>(&mut self, id: WorldId) -> Query<'_, Q>
     where
-        Q: QueryParam,
+        Q: QueryParam + 'static,
     {
-        Query::new(self)
+        Query::new_in_world(self, id.0)
+    }
+
+    fn move_entity(&mut self, entity: Entity, from: WorldId, to: WorldId) -> Option<Entity> {
+        let mut source = self.world_mut_by_id(from);
+        let ent = source.entity_mut(entity)?;
+        if from == to {
+            return Some(ent.id());
+        }
+        let components = ent.take_components();
+        drop(source);
+        Some(self.world_mut_by_id(to).spawn_with(components))
+    }
+
+    fn copy_entity(&mut self, entity: Entity, from: WorldId, to: WorldId) -> Option<ClonedEntity> {
+        let (components, skipped) = self.world_by_id(from).clone_components(entity)?;
+        Some(ClonedEntity {
+            id: self.world_mut_by_id(to).spawn_with(components),
+            skipped,
+        })
+    }
+}
+
+// ids of the worlds created with `WorldExt::add_world`
+#[derive(Default)]
+struct AdditionalWorlds {
+    ids: Vec<ResourceId<WorldInner>>,
+    // `maintain_worlds` was added to the schedule
+    installed: bool,
+}
+
+pub(crate) fn init_main_world(res: &mut Resources) -> ResourceId<WorldInner> {
+    match res.try_init::<WorldInner>() {
+        Ok(id) => {
+            install_maintain_worlds(res);
+            id
+        }
+        Err(id) => id,
+    }
+}
+
+// Installed together with the main world, so `add_world` also works while
+// the schedule is running (it is removed from the resources during the run).
+// When the schedule isn't available, the next `add_world` tries again.
+fn install_maintain_worlds(res: &mut Resources) {
+    let id = res.init::<AdditionalWorlds>();
+    if res.get_mut_id(id).unwrap().installed {
+        return;
+    }
+    if let Some(schedule) = res.get_mut::<Schedule>() {
+        schedule
+            .add_system(maintain_worlds)
+            .into_phase(CoreSystemPhase::First);
+        res.get_mut_id(id).unwrap().installed = true;
+    }
+}
+
+// the storages of the main world install their own systems
+fn maintain_worlds(res: ExclusiveResources<'_>) {
+    let Some(worlds) = res.borrow_res::<AdditionalWorlds>() else {
+        return;
+    };
+    for &id in &worlds.ids {
+        let Some(world) = res.borrow_res_id(id) else {
+            continue;
+        };
+        for component in &world.components.components {
+            if let Some(mut storage) =
+                res.borrow_res_mut_meta::<dyn AnyStorage>(component.storage_id.typed())
+            {
+                storage.maintain();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::Bundle;
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+    struct A(usize);
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+    #[component(sparse)]
+    struct B(usize);
+
+    #[test]
+    fn test_move_entity_to_other_world() {
+        let mut main = Resources::new();
+        let mut render = Resources::new();
+
+        let mut main_world = main.world_mut();
+        let e1 = main_world.spawn().insert(A(1)).insert(B(1)).id();
+        let e2 = main_world.spawn().insert(A(2)).id();
+
+        let mut render_world = render.world_mut();
+        let moved = main_world
+            .move_entity_to(e1, &mut render_world)
+            .expect("entity");
+        assert!(main_world.move_entity_to(e1, &mut render_world).is_none());
+
+        assert!(main_world.entity(e1).is_none());
+        let ent2 = main_world.entity(e2).unwrap();
+        assert_eq!(Some(A(2)), ent2.borrow::<A>().as_deref().copied());
+
+        let moved = render_world.entity(moved).unwrap();
+        assert_eq!(Some(A(1)), moved.borrow::<A>().as_deref().copied());
+        assert_eq!(Some(B(1)), moved.borrow::<B>().as_deref().copied());
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+    #[component(tracked)]
+    struct Tracked(usize);
+
+    #[test]
+    fn test_additional_worlds() {
+        let mut resources = Resources::new();
+        let main_id = resources.world_mut().id();
+        let render_id = resources.add_world();
+        let other_id = resources.add_world();
+        assert_ne!(main_id, render_id);
+        assert_ne!(render_id, other_id);
+        assert_eq!(main_id, resources.world().id());
+
+        let e1 = resources.world_mut().spawn().insert(A(1)).id();
+        let mut render = resources.world_mut_by_id(render_id);
+        assert_eq!(render_id, render.id());
+        let e2 = render.spawn().insert(A(2)).insert(B(2)).id();
+        let e3 = render.spawn().insert(A(3)).id();
+        drop(render);
+
+        let mut query = resources.query::<&A>();
+        assert_eq!(vec![A(1)], query.iter().copied().collect::<Vec<_>>());
+        drop(query);
+        let mut query = resources.query_by_world_id::<&A>(render_id);
+        let mut values: Vec<_> = query.iter().copied().collect();
+        values.sort_by_key(|a| a.0);
+        assert_eq!(vec![A(2), A(3)], values);
+        drop(query);
+        resources.world_mut_by_id(other_id).init::<A>();
+        let mut query = resources.query_by_world_id::<&A>(other_id);
+        assert_eq!(0, query.iter().count());
+        drop(query);
+
+        // the main world doesn't know the entities of the other worlds
+        assert!(resources.world().entity(e1).is_some());
+        let render = resources.world_by_id(render_id);
+        assert_eq!(
+            Some(B(2)),
+            render.entity(e2).unwrap().borrow::<B>().as_deref().copied()
+        );
+        assert_eq!(
+            Some(A(3)),
+            render.entity(e3).unwrap().borrow::<A>().as_deref().copied()
+        );
+    }
+
+    #[test]
+    fn test_move_and_copy_entity_between_worlds() {
+        let mut resources = Resources::new();
+        let main_id = resources.world_mut().id();
+        let render_id = resources.add_world();
+
+        let original = resources
+            .world_mut()
+            .spawn()
+            .insert(A(1))
+            .insert(B(1))
+            .insert(Name("sprite".to_owned()))
+            .id();
+
+        let copy = resources.copy_entity(original, main_id, render_id).unwrap();
+        let component_a = resources.world().components().id::<A>().unwrap();
+        let component_b = resources.world().components().id::<B>().unwrap();
+        // `A` and `B` implement `Clone`, but cloning is opt-in
+        assert_eq!(
+            vec![component_a.untyped(), component_b.untyped()],
+            copy.skipped
+        );
+        let render = resources.world_by_id(render_id);
+        let ent = render.entity(copy.id).unwrap();
+        assert_eq!(
+            Some(Name("sprite".to_owned())),
+            ent.borrow::<Name>().as_deref().cloned()
+        );
+        assert!(ent.borrow::<A>().is_none());
+        drop(render);
+        assert!(resources.world().entity(original).is_some());
+
+        let moved = resources.move_entity(original, main_id, render_id).unwrap();
+        assert!(resources.world().entity(original).is_none());
+        assert!(resources
+            .move_entity(original, main_id, render_id)
+            .is_none());
+        let render = resources.world_by_id(render_id);
+        let ent = render.entity(moved).unwrap();
+        assert_eq!(Some(A(1)), ent.borrow::<A>().as_deref().copied());
+        assert_eq!(Some(B(1)), ent.borrow::<B>().as_deref().copied());
+        assert_eq!(
+            Some(Name("sprite".to_owned())),
+            ent.borrow::<Name>().as_deref().cloned()
+        );
+        drop(render);
+
+        // between worlds of different `Resources`
+        let mut other = Resources::new();
+        let mut other_world = other.world_mut();
+        let render = resources.world_mut_by_id(render_id);
+        let copy = render.copy_entity_to(moved, &mut other_world).unwrap();
+        assert_eq!(2, copy.skipped.len());
+        assert_eq!(
+            Some(Name("sprite".to_owned())),
+            other_world
+                .entity(copy.id)
+                .unwrap()
+                .borrow::<Name>()
+                .as_deref()
+                .cloned()
+        );
+    }
+
+    #[test]
+    fn test_additional_world_storages_are_maintained() {
+        let mut resources = Resources::new();
+        let render_id = resources.add_world();
+        let mut render = resources.world_mut_by_id(render_id);
+        let entity = render.spawn().insert(Tracked(1)).id();
+        render.despawn(entity);
+        drop(render);
+
+        let removed = |resources: &Resources| {
+            let render = resources.world_by_id(render_id);
+            let component = render.components().expect_id::<Tracked>();
+            let component = render.components().get(component).unwrap();
+            let storage = resources
+                .borrow_res_id(
+                    component
+                        .storage_id
+                        .typed::<<Tracked as Component>::Storage>(),
+                )
+                .unwrap();
+            storage.removed_since(&mut 0).to_vec()
+        };
+        assert_eq!(vec![entity], removed(&resources));
+
+        // removals are kept for two frames
+        let mut schedule = resources.remove::<Schedule>().unwrap();
+        schedule.run(&mut resources);
+        assert_eq!(vec![entity], removed(&resources));
+        schedule.run(&mut resources);
+        assert!(removed(&resources).is_empty());
+    }
+
+    #[test]
+    fn test_add_world_from_system() {
+        use std::sync::{Arc, Mutex};
+
+        let mut resources = Resources::new();
+        resources.world_mut();
+        let render_id = Arc::new(Mutex::new(None));
+        let mut schedule = resources.remove::<Schedule>().unwrap();
+        schedule.add_system({
+            let render_id = render_id.clone();
+            move |mut res: ExclusiveResources<'_>| {
+                let mut render_id = render_id.lock().unwrap();
+                if render_id.is_none() {
+                    // the schedule was removed from the resources for the run
+                    let id = res.add_world();
+                    let mut render = res.world_mut_by_id(id);
+                    let entity = render.spawn().insert(Tracked(1)).id();
+                    render.despawn(entity);
+                    *render_id = Some(id);
+                }
+            }
+        });
+        schedule.run(&mut resources);
+        let render_id = render_id.lock().unwrap().unwrap();
+
+        let removed = |resources: &Resources| {
+            let render = resources.world_by_id(render_id);
+            let component = render.components().expect_id::<Tracked>();
+            let component = render.components().get(component).unwrap();
+            let storage = resources
+                .borrow_res_id(
+                    component
+                        .storage_id
+                        .typed::<<Tracked as Component>::Storage>(),
+                )
+                .unwrap();
+            storage.removed_since(&mut 0).len()
+        };
+        assert_eq!(1, removed(&resources));
+        // `maintain_worlds` was installed with the main world
+        schedule.run(&mut resources);
+        schedule.run(&mut resources);
+        assert_eq!(0, removed(&resources));
+    }
+
+    #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Component)]
+    struct Visibility(bool);
+
+    #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Component)]
+    #[component(requires(Visibility))]
+    struct Transform(usize);
+
+    #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Component)]
+    #[component(sparse, requires(Transform))]
+    struct Sprite(usize);
+
+    #[test]
+    fn test_insert_required_components() {
+        let mut resources = Resources::new();
+        let mut world = resources.world_mut();
+        let e1 = world.spawn().insert(Sprite(1)).id();
+        let e2 = world
+            .spawn()
+            .insert(Visibility(true))
+            .insert(Transform(2))
+            .insert(Sprite(2))
+            .id();
+
+        let ent1 = world.entity(e1).unwrap();
+        assert_eq!(
+            Some(Transform(0)),
+            ent1.borrow::<Transform>().as_deref().copied()
+        );
+        assert_eq!(
+            Some(Visibility(false)),
+            ent1.borrow::<Visibility>().as_deref().copied()
+        );
+
+        // existing components are not replaced
+        let ent2 = world.entity(e2).unwrap();
+        assert_eq!(
+            Some(Transform(2)),
+            ent2.borrow::<Transform>().as_deref().copied()
+        );
+        assert_eq!(
+            Some(Visibility(true)),
+            ent2.borrow::<Visibility>().as_deref().copied()
+        );
+    }
+
+    #[test]
+    fn test_type_erased_components() {
+        let mut resources = Resources::new();
+        let mut world = resources.world_mut();
+        let e1 = world.spawn().insert(A(1)).insert(B(2)).id();
+        let e2 = world.spawn().insert(A(3)).id();
+        let a_id = world.init::<A>().untyped();
+        let b_id = world.init::<B>().untyped();
+
+        let ent1 = world.entity(e1).unwrap();
+        assert_eq!(vec![a_id, b_id], ent1.component_ids().collect::<Vec<_>>());
+        let a = ent1.borrow_dyn(a_id).unwrap();
+        assert_eq!(Some(&A(1)), a.downcast_ref::<A>());
+        drop(a);
+        let b = ent1.borrow_dyn(b_id).unwrap();
+        assert_eq!(Some(&B(2)), b.downcast_ref::<B>());
+        drop(b);
+
+        let ent2 = world.entity(e2).unwrap();
+        assert_eq!(vec![a_id], ent2.component_ids().collect::<Vec<_>>());
+        assert!(ent2.borrow_dyn(b_id).is_none());
+
+        let mut ent2 = world.entity_mut(e2).unwrap();
+        ent2.insert(B(4));
+        assert_eq!(vec![a_id, b_id], ent2.component_ids().collect::<Vec<_>>());
+        ent2.borrow_mut_dyn(a_id)
+            .unwrap()
+            .downcast_mut::<A>()
+            .unwrap()
+            .0 = 5;
+        drop(ent2);
+        let ent2 = world.entity(e2).unwrap();
+        assert_eq!(Some(A(5)), ent2.borrow::<A>().as_deref().copied());
+    }
+
+    #[test]
+    fn test_replace_and_take() {
+        let mut resources = Resources::new();
+        let mut world = resources.world_mut();
+        let entities: Vec<_> = (0..3)
+            .map(|i| world.spawn().insert(A(i)).insert(B(i)).id())
+            .collect();
+
+        let mut ent = world.entity_mut(entities[0]).unwrap();
+        assert_eq!(Some(A(0)), ent.replace(A(10)));
+        assert_eq!(Some(B(0)), ent.replace(B(10)));
+        // pending inserts are applied before replacing
+        ent.insert(A(20));
+        assert_eq!(Some(A(20)), ent.replace(A(30)));
+        assert_eq!(Some(A(30)), ent.take::<A>());
+        assert_eq!(None, ent.take::<A>());
+        assert_eq!(None, ent.replace(A(40)));
+        drop(ent);
+
+        assert_eq!(Some(A(1)), world.take::<A>(entities[1]));
+        assert_eq!(Some(B(2)), world.take::<B>(entities[2]));
+        assert_eq!(None, world.take::<B>(entities[2]));
+
+        let get = |world: &WorldMut<'_>, e: Entity| {
+            let ent = world.entity(e).unwrap();
+            let a = ent.borrow::<A>().as_deref().copied();
+            let b = ent.borrow::<B>().as_deref().copied();
+            (a, b)
+        };
+        assert_eq!((Some(A(40)), Some(B(10))), get(&world, entities[0]));
+        assert_eq!((None, Some(B(1))), get(&world, entities[1]));
+        assert_eq!((Some(A(2)), None), get(&world, entities[2]));
+    }
+
+    #[derive(Debug, Default, Clone, PartialEq, Eq, Component)]
+    #[component(on_despawn = despawn_referenced)]
+    struct Children(Vec<Entity>);
+
+    impl crate::component::EntityReferences for Children {
+        fn visit_entities(&self, visitor: &mut dyn FnMut(Entity)) {
+            self.0.iter().copied().for_each(visitor)
+        }
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+    #[component(sparse, on_despawn = detach_from_parent)]
+    struct Parent(Entity);
+
+    fn detach_from_parent(context: &mut crate::entity::DespawnContext<'_>) {
+        let id = context.id();
+        let parent = context.entity().borrow::<Parent>().map(|p| p.0);
+        if let Some(parent) = parent.and_then(|p| context.entity_mut(p)) {
+            if let Some(mut children) = parent.borrow_mut::<Children>() {
+                children.0.retain(|&c| c != id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_despawn_cleanup_policies() {
+        let mut resources = Resources::new();
+        let mut world = resources.world_mut();
+        let root = world.spawn().insert(A(0)).id();
+        let spawn_child = |world: &mut WorldMut<'_>, parent: Entity, value: usize| {
+            let child = world.spawn().insert(A(value)).insert(Parent(parent)).id();
+            let mut parent = world.entity_mut(parent).unwrap();
+            if let Some(mut children) = parent.borrow_mut::<Children>() {
+                children.0.push(child);
+                return child;
+            }
+            parent.insert(Children(vec![child]));
+            child
+        };
+        let c1 = spawn_child(&mut world, root, 1);
+        let c2 = spawn_child(&mut world, root, 2);
+        let g1 = spawn_child(&mut world, c1, 3);
+        let other = world.spawn().insert(A(4)).id();
+
+        // detach
+        assert!(world.despawn(c2));
+        let children = world
+            .entity(root)
+            .unwrap()
+            .borrow::<Children>()
+            .unwrap()
+            .clone();
+        assert_eq!(Children(vec![c1]), children);
+
+        // despawn recursively
+        assert!(world.despawn(root));
+        for entity in [root, c1, c2, g1] {
+            assert!(world.entity(entity).is_none());
+        }
+        assert_eq!(1, world.entities().len());
+        let other = world.entity(other).unwrap();
+        assert_eq!(Some(A(4)), other.borrow::<A>().as_deref().copied());
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Component)]
+    #[component(clone)]
+    struct Name(String);
+
+    #[derive(Debug, PartialEq, Eq, Component)]
+    #[component(sparse, clone = duplicate_handle)]
+    struct Handle(usize, usize);
+
+    // custom clone function (e.g. for bumping a ref-count)
+    fn duplicate_handle(handle: &Handle) -> Handle {
+        Handle(handle.0, handle.1 + 1)
+    }
+
+    #[test]
+    fn test_clone_entity() {
+        let mut resources = Resources::new();
+        let mut world = resources.world_mut();
+        let original = world
+            .spawn()
+            .insert(A(1))
+            .insert(Name("prefab".to_owned()))
+            .insert(Handle(7, 1))
+            .id();
+
+        let copy = world.clone_entity(original).unwrap();
+        assert_ne!(original, copy.id);
+        // `A` implements `Clone`, but has no clone function
+        let component_a = world.components().id::<A>().unwrap();
+        assert_eq!(vec![component_a.untyped()], copy.skipped);
+        let copy = world.entity(copy.id).unwrap();
+        assert_eq!(
+            Some(Name("prefab".to_owned())),
+            copy.borrow::<Name>().as_deref().cloned()
+        );
+        assert_eq!(Some(&Handle(7, 2)), copy.borrow::<Handle>().as_deref());
+        assert!(copy.borrow::<A>().is_none());
+
+        let original = world.entity(original).unwrap();
+        assert_eq!(Some(A(1)), original.borrow::<A>().as_deref().copied());
+        assert_eq!(Some(&Handle(7, 1)), original.borrow::<Handle>().as_deref());
+
+        let removed = world.spawn().id();
+        world.despawn(removed);
+        assert!(world.clone_entity(removed).is_none());
+    }
+
+    #[derive(Bundle)]
+    struct Player {
+        a: A,
+        name: Name,
+    }
+
+    #[test]
+    fn test_typed_entity() {
+        let mut resources = Resources::new();
+        let mut world = resources.world_mut();
+        let player = world.spawn_bundle(Player {
+            a: A(1),
+            name: Name("player".to_owned()),
+        });
+        assert_eq!(A(1), *world.component::<A, _>(player));
+        world.component_mut::<Name, _>(player).0.push('1');
+        assert_eq!("player1", world.component::<Name, _>(player).0);
+
+        // tuples and nested bundles
+        let pair = world.spawn_bundle((
+            B(2),
+            Player {
+                a: A(3),
+                name: Name("nested".to_owned()),
+            },
+        ));
+        assert_eq!(B(2), *world.component::<B, _>(pair));
+        assert_eq!(A(3), *world.component::<A, _>(pair));
+
+        // downgrade and upgrade
+        let entity: Entity = pair.into();
+        assert!(world.typed_entity::<Player>(entity).is_some());
+        assert_eq!(Some(player), world.typed_entity::<Player>(player.id()));
+        assert!(world.typed_entity::<B>(player.id()).is_none());
+        world.entity_mut(entity).unwrap().remove::<Name>();
+        assert!(world.typed_entity::<Player>(entity).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "was despawned")]
+    fn test_typed_entity_despawned() {
+        let mut resources = Resources::new();
+        let mut world = resources.world_mut();
+        let entity = world.spawn_bundle(A(1));
+        world.despawn(entity.id());
+        let _ = world.component::<A, _>(entity);
     }
 }
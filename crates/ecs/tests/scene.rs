@@ -0,0 +1,131 @@
+//! Tests of the `scene` module (requires the `serde` feature).
+
+use std::collections::BTreeMap;
+
+use pulz_ecs::{
+    prelude::*,
+    scene::{EntityMap, MapEntities, SceneComponents, SceneEntity, SceneError},
+};
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Component, Serialize, Deserialize)]
+struct Position(f32, f32);
+
+#[derive(Debug, Copy, Clone, PartialEq, Component, Serialize, Deserialize)]
+struct Parent(Entity);
+
+impl MapEntities for Parent {
+    fn map_entities(&mut self, entity_map: &EntityMap) {
+        self.0 = entity_map.map(self.0);
+    }
+}
+
+fn scene_components() -> SceneComponents {
+    let mut components = SceneComponents::new();
+    components
+        .add::<Position>("Position")
+        .add_mapped::<Parent>("Parent");
+    components
+}
+
+#[test]
+fn test_write_and_spawn_scene() {
+    let mut resources = Resources::new();
+    let (root, child) = {
+        let mut world = resources.world_mut();
+        let root = world.spawn().insert(Position(1.0, 2.0)).id();
+        let child = world
+            .spawn()
+            .insert(Position(3.0, 4.0))
+            .insert(Parent(root))
+            .id();
+        (root, child)
+    };
+
+    let components = scene_components();
+    let scene = components
+        .write_scene(&resources.world(), [root, child])
+        .unwrap();
+    let json = serde_json::to_string(&components.serializer(&scene)).unwrap();
+    let scene = components
+        .deserializer()
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .unwrap();
+
+    let mut other = Resources::new();
+    let mut world = other.world_mut();
+    world.spawn().insert(Position(0.0, 0.0)); // shift entity ids
+    let entity_map = components.spawn_scene(&mut world, &scene).unwrap();
+    assert_eq!(2, entity_map.len());
+
+    let new_root = entity_map.get(root).unwrap();
+    let new_child = entity_map.get(child).unwrap();
+    assert_ne!(root, new_root);
+    let child = world.entity(new_child).unwrap();
+    assert_eq!(
+        Some(Parent(new_root)),
+        child.borrow::<Parent>().as_deref().copied()
+    );
+    assert_eq!(
+        Some(Position(3.0, 4.0)),
+        child.borrow::<Position>().as_deref().copied()
+    );
+}
+
+#[test]
+fn test_unknown_component() {
+    let mut resources = Resources::new();
+    let entity = resources
+        .world_mut()
+        .spawn()
+        .insert(Position(1.0, 2.0))
+        .id();
+    let scene = scene_components()
+        .write_scene(&resources.world(), [entity])
+        .unwrap();
+
+    let result = SceneComponents::new().spawn_scene(&mut resources.world_mut(), &scene);
+    assert!(matches!(result, Err(SceneError::UnknownComponent(name)) if name == "Position"));
+
+    let json = serde_json::to_string(&scene_components().serializer(&scene)).unwrap();
+    let result = SceneComponents::new()
+        .deserializer()
+        .deserialize(&mut serde_json::Deserializer::from_str(&json));
+    let error = result.unwrap_err().to_string();
+    assert!(error.contains("unknown component `Position`"), "{error}");
+}
+
+#[test]
+fn test_duplicate_entity() {
+    let mut resources = Resources::new();
+    let entity = resources
+        .world_mut()
+        .spawn()
+        .insert(Position(1.0, 2.0))
+        .id();
+    let components = scene_components();
+    let result = components.write_scene(&resources.world(), [entity, entity]);
+    assert!(matches!(result, Err(SceneError::DuplicateEntity(e)) if e == entity));
+
+    let mut scene = components
+        .write_scene(&resources.world(), [entity])
+        .unwrap();
+    let json = serde_json::to_string(&components.serializer(&scene)).unwrap();
+    let entities = json.strip_prefix("{\"entities\":[").unwrap();
+    let entities = entities.strip_suffix("]}").unwrap();
+    let duplicated = format!("{{\"entities\":[{entities},{entities}]}}");
+    let result = components
+        .deserializer()
+        .deserialize(&mut serde_json::Deserializer::from_str(&duplicated));
+    let error = result.unwrap_err().to_string();
+    assert!(error.contains("duplicate scene entity"), "{error}");
+
+    scene.entities.push(SceneEntity {
+        entity,
+        components: BTreeMap::new(),
+    });
+    let mut world = resources.world_mut();
+    let result = components.spawn_scene(&mut world, &scene);
+    assert!(matches!(result, Err(SceneError::DuplicateEntity(e)) if e == entity));
+    assert_eq!(1, world.entities().len());
+}
@@ -0,0 +1,86 @@
+//! Tests of running the world in a deterministic `Schedule`.
+
+use pulz_ecs::{prelude::*, system::system_fn::ExclusiveResources};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+struct A(usize);
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Component)]
+struct Visibility(bool);
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Component)]
+#[component(requires(Visibility))]
+struct Transform(usize);
+
+#[derive(Default)]
+struct Frame(usize);
+
+#[derive(Default)]
+struct Log {
+    spawned: Vec<Entity>,
+    iterated: Vec<(Entity, usize)>,
+}
+
+#[test]
+fn test_deterministic_schedule() {
+    fn simulate() -> Log {
+        let mut resources = Resources::new();
+        let mut world = resources.world_mut();
+        world.init::<A>();
+        world.init::<Visibility>();
+        world.init::<Transform>();
+        drop(world);
+        resources.init::<Frame>();
+        resources.init::<Log>();
+        let mut schedule = resources.remove::<Schedule>().unwrap();
+        schedule.set_deterministic(true);
+        schedule.add_system(|mut res: ExclusiveResources<'_>| {
+            let frame = res.get_mut::<Frame>().unwrap().0;
+            // frees slots, that are re-used by the spawns below
+            let despawned: Vec<_> = res
+                .query::<(Entity, &A)>()
+                .iter()
+                .filter(|(_, a)| a.0 % 4 == 0)
+                .map(|(e, _)| e)
+                .collect();
+            let mut spawned = Vec::new();
+            let mut world = res.world_mut();
+            for entity in despawned {
+                world.despawn(entity);
+            }
+            for k in 0..3 {
+                let value = frame * 3 + k;
+                let mut ent = world.spawn();
+                ent.insert(A(value));
+                match k {
+                    1 => {
+                        ent.insert(Visibility(true));
+                    }
+                    2 => {
+                        ent.insert(Transform(value));
+                    }
+                    _ => {}
+                }
+                spawned.push(ent.id());
+            }
+            drop(world);
+            res.get_mut::<Log>().unwrap().spawned.extend(spawned);
+            res.get_mut::<Frame>().unwrap().0 += 1;
+        });
+        schedule.add_system(|mut query: Query<'_, (Entity, &A)>, log: &mut Log| {
+            log.iterated
+                .extend(query.iter().map(|(entity, a)| (entity, a.0)));
+        });
+        for _ in 0..5 {
+            schedule.run(&mut resources);
+        }
+        resources.remove::<Log>().unwrap().into_inner()
+    }
+
+    let first = simulate();
+    let second = simulate();
+    assert_eq!(15, first.spawned.len());
+    assert!(!first.iterated.is_empty());
+    assert_eq!(first.spawned, second.spawned);
+    assert_eq!(first.iterated, second.iterated);
+}
@@ -0,0 +1,28 @@
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, ItemFn};
+
+mod system;
+
+/// Declares the scheduling of a system next to its definition.
+///
+/// The function is kept as it is, and an additional function
+/// `install_<name>(schedule: &mut Schedule) -> SystemId` (with the same
+/// visibility) is generated, that adds the system to the schedule.
+///
+/// Supported attributes:
+///
+/// * `phase = ...`: the phase of the system (`SystemEntryBuilder::into_phase`)
+/// * `before = ...`, `after = ...`: ordering relative to a phase (can be
+///   repeated)
+/// * `name = "..."`: overrides the name of the system
+/// * `run_if_changed = T`: only runs the system, when the resource `T` was
+///   changed (see `run_if_changed`)
+/// * `local(param = ...)`: the initial value of the `Local<T>` parameter
+///   `param` (instead of `FromResources`)
+#[proc_macro_attribute]
+pub fn system(args: TokenStream, input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as ItemFn);
+    system::system_attribute(args.into(), input)
+        .unwrap_or_else(|err| err.write_errors())
+        .into()
+}
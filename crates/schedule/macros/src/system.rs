@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use darling::{ast::NestedMeta, Error, FromMeta, Result};
+use proc_macro2::{Ident, Span, TokenStream};
+use proc_macro_crate::FoundCrate;
+use quote::{format_ident, quote};
+use syn::{Expr, FnArg, GenericArgument, ItemFn, Pat, Path, PathArguments, Token, Type};
+
+#[derive(Default, FromMeta)]
+#[darling(default)]
+pub struct SystemArgs {
+    phase: Option<Expr>,
+    #[darling(multiple)]
+    before: Vec<Expr>,
+    #[darling(multiple)]
+    after: Vec<Expr>,
+    name: Option<String>,
+    run_if_changed: Option<Path>,
+    local: HashMap<Ident, Expr>,
+}
+
+pub fn system_attribute(args: TokenStream, input: ItemFn) -> Result<TokenStream> {
+    let args = SystemArgs::from_list(&NestedMeta::parse_meta_list(args)?)?;
+    if !input.sig.generics.params.is_empty() {
+        return Err(
+            Error::custom("generic systems are not supported").with_span(&input.sig.generics)
+        );
+    }
+
+    let crate_schedule = resolve_schedule_crate()?;
+    let vis = &input.vis;
+    let ident = &input.sig.ident;
+    let install_ident = format_ident!("install_{}", ident);
+    let doc = format!("Adds the system [`{ident}`] to the schedule.");
+
+    let mut name = args.name.clone();
+    let mut system = quote!(#ident);
+    if !args.local.is_empty() {
+        system = seed_locals(&crate_schedule, &input, &args.local)?;
+        // otherwise, the system would be named after the closure
+        name.get_or_insert_with(|| ident.to_string());
+    }
+    if let Some(resource) = &args.run_if_changed {
+        system = quote!(#crate_schedule::resource::run_if_changed::<#resource, _, _>(#system));
+    }
+    let phase = args.phase.iter();
+    let before = args.before.iter();
+    let after = args.after.iter();
+    let name = name.iter();
+
+    Ok(quote! {
+        #input
+
+        #[doc = #doc]
+        #vis fn #install_ident(
+            schedule: &mut #crate_schedule::schedule::Schedule,
+        ) -> #crate_schedule::schedule::SystemId {
+            let mut entry = schedule.add_system(#system);
+            #( entry.into_phase(#phase); )*
+            #( entry.before(#before); )*
+            #( entry.after(#after); )*
+            #( entry.with_name(#name); )*
+            entry.id()
+        }
+    })
+}
+
+// Wraps the function into a closure, that owns the initial values of the
+// seeded `Local<T>` parameters, and passes the other parameters through.
+fn seed_locals(
+    crate_schedule: &Path,
+    input: &ItemFn,
+    locals: &HashMap<Ident, Expr>,
+) -> Result<TokenStream> {
+    let ident = &input.sig.ident;
+    let mut seeded = Vec::new();
+    let mut states = Vec::new();
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    for (i, arg) in input.sig.inputs.iter().enumerate() {
+        let FnArg::Typed(arg) = arg else {
+            return Err(Error::custom("methods are not supported").with_span(arg));
+        };
+        let local = match &*arg.pat {
+            Pat::Ident(pat) => locals.get_key_value(&pat.ident),
+            _ => None,
+        };
+        if let Some((name, init)) = local {
+            let ty = local_type(&arg.ty).ok_or_else(|| {
+                Error::custom("`local` can only seed `Local<T>` parameters").with_span(&arg.ty)
+            })?;
+            let state = format_ident!("__local_{}", i);
+            states.push(quote!(let mut #state: #ty = #init;));
+            args.push(quote!(#crate_schedule::local::Local::new(&mut #state)));
+            seeded.push(name);
+        } else {
+            let param = format_ident!("__arg_{}", i);
+            let ty = &arg.ty;
+            params.push(quote!(#param: #ty));
+            args.push(quote!(#param));
+        }
+    }
+    let mut errors = Error::accumulator();
+    for name in locals.keys() {
+        if !seeded.contains(&name) {
+            errors.push(
+                Error::custom(format!("`{ident}` has no parameter `{name}`")).with_span(name),
+            );
+        }
+    }
+    errors.finish()?;
+    Ok(quote! {{
+        #(#states)*
+        move |#(#params),*| #ident(#(#args),*)
+    }})
+}
+
+// `T` of `Local<'_, T>`
+fn local_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Local" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(generics) = &segment.arguments else {
+        return None;
+    };
+    generics.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+// The generated code can be used with `pulz-schedule` or with `pulz-ecs`
+// (that re-exports `pulz-schedule`).
+fn resolve_schedule_crate() -> Result<Path> {
+    let path = |name: &str| {
+        let mut path: Path = Ident::new(name, Span::call_site()).into();
+        path.leading_colon = Some(Token![::](Span::call_site()));
+        path
+    };
+    match proc_macro_crate::crate_name("pulz-schedule") {
+        // `pulz-schedule` declares `extern crate self as pulz_schedule`
+        Ok(FoundCrate::Itself) => Ok(path("pulz_schedule")),
+        Ok(FoundCrate::Name(name)) => Ok(path(&name)),
+        Err(err) => match proc_macro_crate::crate_name("pulz-ecs") {
+            Ok(FoundCrate::Itself) => Ok(Ident::new("crate", Span::call_site()).into()),
+            Ok(FoundCrate::Name(name)) => Ok(path(&name)),
+            Err(_) => Err(Error::custom(err)),
+        },
+    }
+}
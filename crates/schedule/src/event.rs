@@ -130,11 +130,7 @@ pub struct EventSubscriber<'w, T> {
 impl<'w, T> EventSubscriber<'w, T> {
     #[inline]
     fn offset(&self) -> usize {
-        if self.next_id > self.events.first_id {
-            self.next_id - self.events.first_id
-        } else {
-            0
-        }
+        self.next_id.saturating_sub(self.events.first_id)
     }
 
     pub fn iter(&mut self) -> Iter<'_, T> {
@@ -190,9 +186,6 @@ where
     }
 }
 
-#[doc(hidden)]
-pub struct EventWriterFetch<'r, T>(ResMut<'r, Events<T>>);
-
 impl<T> SystemData for EventWriter<'_, T>
 where
     T: Send + Sync + 'static,
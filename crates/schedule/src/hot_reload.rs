@@ -0,0 +1,242 @@
+//! Hot-reloading of systems from dynamically loaded libraries.
+//!
+//! The library exports a function with the signature of [`RegisterSystemsFn`],
+//! that registers its systems by name. The application loads the library (e.g.
+//! with `libloading`), looks up the function, and passes it to
+//! [`SystemReloader::load`]. When the library was rebuilt, the new version is
+//! loaded and passed to `load` again: systems with a known name are replaced in
+//! place (keeping their phase and ordering), and the schedule is rebuilt on the
+//! next run.
+//!
+//! Loading libraries is not part of this module.
+
+use std::borrow::Cow;
+
+use crate::{
+    schedule::{Schedule, SystemEntryBuilder, SystemId},
+    system::IntoSystemDescriptor,
+};
+
+type HashMap<K, V> = std::collections::HashMap<K, V, fnv::FnvBuildHasher>;
+type HashSet<T> = std::collections::HashSet<T, fnv::FnvBuildHasher>;
+
+/// The signature of the function, that registers the systems of a library.
+pub type RegisterSystemsFn = fn(&mut SystemRegistrar<'_>);
+
+/// Keeps track of the systems, that were loaded from a library.
+#[derive(Default)]
+pub struct SystemReloader {
+    systems: HashMap<Cow<'static, str>, SystemId>,
+    disabled: HashSet<Cow<'static, str>>,
+    generation: usize,
+}
+
+/// Passed to the [`RegisterSystemsFn`] of a library, for registering its
+/// systems.
+pub struct SystemRegistrar<'l> {
+    schedule: &'l mut Schedule,
+    known: &'l HashMap<Cow<'static, str>, SystemId>,
+    registered: HashMap<Cow<'static, str>, SystemId>,
+    replaced: usize,
+}
+
+/// The result of [`SystemReloader::load`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReloadSummary {
+    /// Number of systems, that were added to the schedule.
+    pub added: usize,
+    /// Number of systems, that replaced a system of a previous load.
+    pub replaced: usize,
+    /// Number of systems of the previous load, that are not registered
+    /// anymore. They were replaced with a no-op system.
+    ///
+    /// Systems, that were already disabled by an earlier load, are not counted
+    /// again.
+    pub disabled: usize,
+}
+
+fn disabled_system() {}
+
+impl SystemReloader {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of times, a library was loaded.
+    #[inline]
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Returns the id of the system with the given name, that was loaded from
+    /// the library.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<SystemId> {
+        self.systems.get(name).copied()
+    }
+
+    /// Registers the systems of a (re)loaded library in the schedule.
+    ///
+    /// # Safety
+    ///
+    /// The systems are called across the boundary of the dynamic library:
+    /// the library must be built with the same compiler and the same versions
+    /// of all shared crates (types must have the same layout).
+    /// The previously loaded library must not be unloaded before this
+    /// function returns (the previous systems are dropped here), and `register`
+    /// (and its library) must stay loaded as long as its systems are part of
+    /// the schedule.
+    pub unsafe fn load(
+        &mut self,
+        schedule: &mut Schedule,
+        register: RegisterSystemsFn,
+    ) -> ReloadSummary {
+        let mut registrar = SystemRegistrar {
+            schedule,
+            known: &self.systems,
+            registered: HashMap::default(),
+            replaced: 0,
+        };
+        register(&mut registrar);
+        let SystemRegistrar {
+            schedule,
+            registered,
+            replaced,
+            ..
+        } = registrar;
+
+        let mut disabled = 0;
+        for (name, &id) in &self.systems {
+            if registered.contains_key(name) {
+                // reactivated (or still active)
+                self.disabled.remove(name);
+            } else if self.disabled.insert(name.clone()) {
+                log::warn!("system {name} was removed from the library, disabling it");
+                schedule.replace_system(id, disabled_system);
+                disabled += 1;
+            }
+        }
+        // keep the ids of disabled systems, so they can be reactivated
+        let added = registered.len() - replaced;
+        self.systems.extend(registered);
+        self.generation += 1;
+        ReloadSummary {
+            added,
+            replaced,
+            disabled,
+        }
+    }
+}
+
+impl SystemRegistrar<'_> {
+    /// Registers the system with the given name.
+    ///
+    /// When a system with this name was loaded before, it is replaced and
+    /// `None` is returned. Otherwise, the system is added to the schedule, and
+    /// the returned builder can be used to define its phase and ordering.
+    pub fn add_system<Marker>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        system: impl IntoSystemDescriptor<Marker>,
+    ) -> Option<SystemEntryBuilder<'_>> {
+        let name = name.into();
+        if let Some(&id) = self.known.get(&name) {
+            self.schedule.replace_system(id, system);
+            self.registered.insert(name, id);
+            self.replaced += 1;
+            None
+        } else {
+            let mut builder = self.schedule.add_system(system);
+            builder.with_name(name.clone());
+            self.registered.insert(name, builder.id());
+            Some(builder)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{label::CoreSystemPhase, resource::Resources};
+
+    static VERSION: AtomicUsize = AtomicUsize::new(0);
+
+    fn library_v1(registrar: &mut SystemRegistrar<'_>) {
+        // the builder is only returned, when the system was added
+        if let Some(mut system) =
+            registrar.add_system("update", |counter: &mut usize| *counter += 1)
+        {
+            system.into_phase(CoreSystemPhase::Update);
+        }
+        registrar.add_system("version", || VERSION.store(1, Ordering::Relaxed));
+    }
+
+    fn library_v2(registrar: &mut SystemRegistrar<'_>) {
+        assert!(registrar
+            .add_system("update", |counter: &mut usize| *counter += 10)
+            .is_none());
+    }
+
+    #[test]
+    fn test_reload() {
+        let mut resources = Resources::new();
+        resources.insert(0usize);
+        let mut schedule = Schedule::new();
+        let mut reloader = SystemReloader::new();
+
+        let summary = unsafe { reloader.load(&mut schedule, library_v1) };
+        assert_eq!(2, summary.added);
+        schedule.run(&mut resources);
+        assert_eq!(1, *resources.get_mut::<usize>().unwrap());
+        assert_eq!(1, VERSION.load(Ordering::Relaxed));
+        let update = reloader.get("update").unwrap();
+        assert_eq!("update", schedule.system(update).unwrap().name());
+
+        let summary = unsafe { reloader.load(&mut schedule, library_v2) };
+        assert_eq!(
+            ReloadSummary {
+                added: 0,
+                replaced: 1,
+                disabled: 1,
+            },
+            summary
+        );
+        assert_eq!(2, reloader.generation());
+        VERSION.store(0, Ordering::Relaxed);
+        schedule.run(&mut resources);
+        assert_eq!(11, *resources.get_mut::<usize>().unwrap());
+        assert_eq!(0, VERSION.load(Ordering::Relaxed));
+        assert_eq!(update, reloader.get("update").unwrap());
+
+        // already disabled systems are not counted again
+        let summary = unsafe { reloader.load(&mut schedule, library_v2) };
+        assert_eq!(
+            ReloadSummary {
+                added: 0,
+                replaced: 1,
+                disabled: 0,
+            },
+            summary
+        );
+
+        // disabled systems are reactivated
+        let summary = unsafe { reloader.load(&mut schedule, library_v1) };
+        assert_eq!(
+            ReloadSummary {
+                added: 0,
+                replaced: 2,
+                disabled: 0,
+            },
+            summary
+        );
+        schedule.run(&mut resources);
+        assert_eq!(12, *resources.get_mut::<usize>().unwrap());
+        assert_eq!(1, VERSION.load(Ordering::Relaxed));
+
+        let summary = unsafe { reloader.load(&mut schedule, library_v2) };
+        assert_eq!(1, summary.disabled);
+    }
+}
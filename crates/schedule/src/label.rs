@@ -94,10 +94,17 @@ macro_rules! define_label_enum {
 
 define_label_type!(SystemPhase, SystemPhaseId);
 
+define_label_type!(
+    /// A label for identifying additional schedules in [`Schedules`](crate::schedule::Schedules).
+    ScheduleLabel,
+    ScheduleLabelId,
+);
+
 define_label_enum! {
     pub enum CoreSystemPhase: SystemPhase {
         First,
         Update,
+        ApplyDeferred,
         Last,
     }
 }
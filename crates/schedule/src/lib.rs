@@ -25,23 +25,39 @@
 #![doc(html_no_source)]
 #![doc = include_str!("../README.md")]
 
+// the code generated by `#[system]` refers to `::pulz_schedule`
+#[cfg(test)]
+extern crate self as pulz_schedule;
+
 #[doc(hidden)]
 pub enum Void {}
 
 pub mod event;
+#[cfg(feature = "hot-reload")]
+pub mod hot_reload;
 pub mod label;
 pub mod local;
 pub mod meta;
 pub mod module;
+#[cfg(not(target_os = "unknown"))]
+pub mod pipeline;
+pub mod profiling;
 pub mod resource;
 pub mod schedule;
+pub mod state;
 pub mod system;
+pub mod time;
+pub mod watchdog;
+
+pub use pulz_schedule_macros::system;
 
 pub mod prelude {
     pub use crate::{
         module::{Module, ModuleWithOutput},
-        resource::{FromResources, FromResourcesMut, Res, ResMut, ResourceId, Resources},
-        schedule::Schedule,
-        system::{IntoExclusiveSystem, IntoSystem},
+        resource::{
+            ChangedRes, FromResources, FromResourcesMut, Res, ResMut, ResourceId, Resources,
+        },
+        schedule::{Schedule, Schedules},
+        system::{error::SystemError, IntoExclusiveSystem, IntoSystem},
     };
 }
@@ -7,6 +7,15 @@ use crate::{
 
 pub struct Local<'l, T>(&'l mut T);
 
+impl<'l, T> Local<'l, T> {
+    /// Wraps a value, that is owned by the system (used by
+    /// `#[system(local(...))]` for seeding the initial value).
+    #[inline]
+    pub fn new(value: &'l mut T) -> Self {
+        Self(value)
+    }
+}
+
 impl<'l, T> Deref for Local<'l, T> {
     type Target = T;
 
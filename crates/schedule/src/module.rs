@@ -1,6 +1,9 @@
 use std::any::TypeId;
 
-use crate::{resource::Resources, schedule::Schedule};
+use crate::{
+    resource::Resources,
+    schedule::{Schedule, Schedules},
+};
 
 pub trait ModuleWithOutput: Sized + 'static {
     type Output<'l>;
@@ -9,6 +12,8 @@ pub trait ModuleWithOutput: Sized + 'static {
     fn install_once(&self, _resources: &mut Resources) {}
     fn install_resources(self, _resources: &mut Resources) -> Self::Output<'_>;
     fn install_systems(_schedule: &mut Schedule) {}
+    /// Installs systems into additional (labeled) schedules.
+    fn install_schedules(_schedules: &mut Schedules) {}
 
     #[inline]
     fn install(self, resources: &mut Resources) -> Self::Output<'_> {
@@ -19,10 +24,14 @@ pub trait ModuleWithOutput: Sized + 'static {
 
             let resources_mut: *mut Resources = resources;
             let mut schedule = resources.remove::<Schedule>().unwrap();
+            let mut schedules = resources.remove::<Schedules>().unwrap();
             let output = self.install_resources(resources);
             Self::install_systems(&mut schedule);
-            // SAFETY: will not access schedule, because it was removed
-            unsafe { &mut *resources_mut }.insert_again(schedule);
+            Self::install_schedules(&mut schedules);
+            // SAFETY: will not access schedule and schedules, because they were removed
+            let resources = unsafe { &mut *resources_mut };
+            resources.insert_again(schedules);
+            resources.insert_again(schedule);
             output
         } else {
             self.install_resources(resources)
@@ -35,6 +44,8 @@ pub trait Module: Sized + 'static {
     fn install_once(&self, _resources: &mut Resources) {}
     fn install_resources(self, _resources: &mut Resources) {}
     fn install_systems(_schedule: &mut Schedule) {}
+    /// Installs systems into additional (labeled) schedules.
+    fn install_schedules(_schedules: &mut Schedules) {}
 }
 
 impl<M: Module> ModuleWithOutput for M {
@@ -55,6 +66,10 @@ impl<M: Module> ModuleWithOutput for M {
     fn install_systems(schedule: &mut Schedule) {
         M::install_systems(schedule)
     }
+    #[inline]
+    fn install_schedules(schedules: &mut Schedules) {
+        M::install_schedules(schedules)
+    }
 }
 
 impl<F> Module for F
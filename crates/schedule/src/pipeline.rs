@@ -0,0 +1,527 @@
+//! Pipelining of schedules over multiple frames.
+//!
+//! A [`PipelinedStage`] runs a separate [`Schedule`] (e.g. the rendering) on a
+//! dedicated thread, one frame behind the main schedule (e.g. the simulation).
+//! The main schedule extracts the data that is needed by the stage into a
+//! *frame* value `T`, and passes it to [`PipelinedStage::submit`]. While the
+//! stage processes this frame, the main schedule can already simulate the next
+//! frame.
+//!
+//! The frames are double-buffered: when the stage has finished a frame, its
+//! value is handed back by the next call to `submit`, so its allocations can be
+//! reused for extracting the next frame.
+//!
+//! A [`Pipeline`] ties a stage to a phase of the main schedule: the phases of
+//! the pipeline (e.g. the render phases) are run on the stage, and a set of
+//! resources is double-buffered automatically between the main schedule and
+//! the stage. The systems of the pipelined phases are added to the pipeline
+//! (with [`Pipeline::with_init`]); systems that are added to these phases in
+//! the main schedule are not moved to the stage. The pipelined phases can be
+//! declared with [`Pipeline::with_phase`], so [`Pipeline::install_into`] warns
+//! about systems of the main schedule in these phases.
+
+use std::{
+    any::Any,
+    panic,
+    sync::{
+        mpsc::{sync_channel, Receiver, SyncSender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    label::{CoreSystemPhase, SystemPhase, SystemPhaseId},
+    resource::Resources,
+    schedule::Schedule,
+    system::system_fn::ExclusiveResources,
+};
+
+/// A schedule that is run pipelined on a dedicated thread.
+///
+/// The stage has its own [`Resources`]. The submitted frame `T` is inserted as
+/// a resource into the resources of the stage, before its schedule is run.
+pub struct PipelinedStage<T> {
+    frames: Option<SyncSender<T>>,
+    finished: Receiver<T>,
+    in_flight: bool,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<T: Send + Sync + 'static> PipelinedStage<T> {
+    /// Spawns the thread of the stage.
+    ///
+    /// The resources and the schedule of the stage are created on this thread,
+    /// so they may contain non-send resources and systems. `init` is called
+    /// on the new thread for installing them.
+    pub fn spawn<F>(name: impl Into<String>, init: F) -> Self
+    where
+        F: FnOnce(&mut Resources, &mut Schedule) + Send + 'static,
+    {
+        let (frames, frames_rx) = sync_channel::<T>(1);
+        let (finished_tx, finished) = sync_channel::<T>(1);
+        let thread = thread::Builder::new()
+            .name(name.into())
+            .spawn(move || {
+                let mut resources = Resources::new();
+                let mut schedule = Schedule::new();
+                init(&mut resources, &mut schedule);
+                while let Ok(frame) = frames_rx.recv() {
+                    let id = resources.insert(frame);
+                    schedule.run(&mut resources);
+                    let frame = resources
+                        .remove_id(id)
+                        .expect("frame resource was removed")
+                        .into_inner();
+                    if finished_tx.send(frame).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("unable to spawn thread for pipelined stage");
+        Self {
+            frames: Some(frames),
+            finished,
+            in_flight: false,
+            thread: Some(thread),
+        }
+    }
+
+    /// Submits the next frame to the stage.
+    ///
+    /// Waits until the stage has finished the previous frame, and returns the
+    /// value of this previous frame (`None` for the first frame).
+    ///
+    /// # Panics
+    ///
+    /// Resumes the panic of the stage thread, when a system of the stage has
+    /// panicked.
+    pub fn submit(&mut self, frame: T) -> Option<T> {
+        let previous = self.wait();
+        if self.frames.as_ref().unwrap().send(frame).is_err() {
+            self.join();
+        }
+        self.in_flight = true;
+        previous
+    }
+
+    /// Waits until the stage has finished the current frame, and returns the
+    /// value of this frame.
+    ///
+    /// Returns `None`, when there is no frame in flight.
+    ///
+    /// # Panics
+    ///
+    /// Resumes the panic of the stage thread, when a system of the stage has
+    /// panicked.
+    pub fn wait(&mut self) -> Option<T> {
+        if !self.in_flight {
+            return None;
+        }
+        self.in_flight = false;
+        match self.finished.recv() {
+            Ok(frame) => Some(frame),
+            Err(_) => self.join(),
+        }
+    }
+
+    /// Returns `true`, when a submitted frame is still processed by the stage.
+    #[inline]
+    pub fn is_in_flight(&self) -> bool {
+        self.in_flight
+    }
+
+    fn join(&mut self) -> ! {
+        self.frames = None;
+        if let Some(thread) = self.thread.take() {
+            if let Err(panic) = thread.join() {
+                panic::resume_unwind(panic);
+            }
+        }
+        panic!("pipelined stage has stopped");
+    }
+}
+
+impl<T> Drop for PipelinedStage<T> {
+    fn drop(&mut self) {
+        // closing the channel stops the thread after the current frame
+        self.frames = None;
+        if let Some(thread) = self.thread.take() {
+            // a panic is only propagated by `wait` and `submit`
+            if thread.join().is_err() {
+                log::error!("pipelined stage has panicked");
+            }
+        }
+    }
+}
+
+type BoxedBuffer = Box<dyn Any + Send + Sync>;
+
+struct BufferFns {
+    init: fn(&mut Resources),
+    take: fn(&mut Resources) -> BoxedBuffer,
+    put: fn(&mut Resources, BoxedBuffer),
+}
+
+fn init_buffer<R>(resources: &mut Resources)
+where
+    R: Default + Send + Sync + 'static,
+{
+    resources.init::<R>();
+}
+
+fn take_buffer<R>(resources: &mut Resources) -> BoxedBuffer
+where
+    R: Default + Send + Sync + 'static,
+{
+    match resources.remove::<R>() {
+        Some(value) => Box::new(value.into_inner()),
+        None => Box::<R>::default(),
+    }
+}
+
+fn put_buffer<R>(resources: &mut Resources, value: BoxedBuffer)
+where
+    R: Send + Sync + 'static,
+{
+    let value = value.downcast::<R>().expect("buffer type mismatch");
+    resources.insert(*value);
+}
+
+// the buffered resources of a frame, in the order of `Pipeline::buffers`
+struct PipelineFrame(Vec<BoxedBuffer>);
+
+// resource of the stage
+struct StageBuffers(Arc<[BufferFns]>);
+
+// moves the buffered resources of the submitted frame into the resources of
+// the stage
+fn unpack_frame(mut res: ExclusiveResources<'_>) {
+    let values = std::mem::take(&mut res.get_mut::<PipelineFrame>().expect("frame").0);
+    let buffers = res.borrow_res::<StageBuffers>().unwrap().0.clone();
+    for (buffer, value) in buffers.iter().zip(values) {
+        (buffer.put)(&mut res, value);
+    }
+}
+
+// moves the buffered resources back into the frame, for handing it back
+fn pack_frame(mut res: ExclusiveResources<'_>) {
+    let buffers = res.borrow_res::<StageBuffers>().unwrap().0.clone();
+    let values = buffers.iter().map(|b| (b.take)(&mut res)).collect();
+    res.get_mut::<PipelineFrame>().expect("frame").0 = values;
+}
+
+type PipelineInit = Box<dyn FnOnce(&mut Resources, &mut Schedule) + Send>;
+
+/// A group of phases, that is run pipelined on a dedicated thread, one frame
+/// behind the main schedule.
+///
+/// The resources registered with [`Self::with_buffered`] are double-buffered:
+/// at the end of the hand-off phase (see [`Self::install_into`]), the
+/// resource is moved from the main schedule to the pipeline, and the buffer
+/// of the frame before (which was processed by the pipeline in the meantime)
+/// is moved back. So the main schedule writes into one buffer, while the
+/// pipeline reads the other one.
+pub struct Pipeline {
+    name: String,
+    buffers: Vec<BufferFns>,
+    phases: Vec<SystemPhaseId>,
+    init: Vec<PipelineInit>,
+}
+
+impl Pipeline {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            buffers: Vec::new(),
+            phases: Vec::new(),
+            init: Vec::new(),
+        }
+    }
+
+    /// Double-buffers the resource `R` between the main schedule and the
+    /// pipeline.
+    ///
+    /// A buffer that is handed back to the main schedule contains the data of
+    /// an earlier frame, so its allocations can be re-used; the systems
+    /// filling the buffer are responsible for clearing it.
+    #[must_use]
+    pub fn with_buffered<R>(mut self) -> Self
+    where
+        R: Default + Send + Sync + 'static,
+    {
+        self.buffers.push(BufferFns {
+            init: init_buffer::<R>,
+            take: take_buffer::<R>,
+            put: put_buffer::<R>,
+        });
+        self
+    }
+
+    /// Declares `phase` as a phase of the pipeline.
+    ///
+    /// Only the systems added by [`Self::with_init`] are run by the pipeline;
+    /// the declared phases are used for detecting systems, that were added to
+    /// these phases in the main schedule by mistake.
+    #[must_use]
+    pub fn with_phase(mut self, phase: impl SystemPhase) -> Self {
+        self.phases.push(phase.as_label());
+        self
+    }
+
+    /// Installs the phases and systems (and resources) of the pipeline.
+    ///
+    /// `init` is called on the thread of the pipeline, with its own resources
+    /// and schedule.
+    #[must_use]
+    pub fn with_init<F>(mut self, init: F) -> Self
+    where
+        F: FnOnce(&mut Resources, &mut Schedule) + Send + 'static,
+    {
+        self.init.push(Box::new(init));
+        self
+    }
+
+    /// Starts the thread of the pipeline, and adds a system to the phase
+    /// `hand_off` of the main schedule, that submits the buffered resources to
+    /// the pipeline.
+    ///
+    /// The `hand_off` phase should be run after all systems, that are writing
+    /// to the buffered resources.
+    ///
+    /// A warning is logged, when the main schedule already contains systems
+    /// in one of the phases declared with [`Self::with_phase`]: these systems
+    /// are not run by the pipeline.
+    ///
+    /// # Errors
+    ///
+    /// Returns the pipeline unchanged (without starting the thread), when the
+    /// [`Schedule`] is not available in `resources` (e.g. while it is
+    /// running).
+    ///
+    /// # Panics
+    ///
+    /// The system resumes the panic of the pipeline, when a system of the
+    /// pipeline has panicked.
+    pub fn install_into(
+        self,
+        resources: &mut Resources,
+        hand_off: impl SystemPhase,
+    ) -> Result<(), Self> {
+        let Some(schedule) = resources.borrow_res::<Schedule>() else {
+            return Err(self);
+        };
+        let misplaced: Vec<_> = schedule
+            .systems()
+            .filter(|(_, s)| self.phases.iter().any(|p| p.as_str() == s.phase()))
+            .map(|(_, s)| s.name().to_owned())
+            .collect();
+        drop(schedule);
+        if !misplaced.is_empty() {
+            log::warn!(
+                "pipeline `{}`: the systems {misplaced:?} of the main schedule are in a pipelined phase, and are not run by the pipeline",
+                self.name
+            );
+        }
+        let Self {
+            name,
+            buffers,
+            phases: _,
+            init,
+        } = self;
+        for buffer in &buffers {
+            (buffer.init)(resources);
+        }
+        let buffers: Arc<[BufferFns]> = buffers.into();
+        let stage_buffers = StageBuffers(buffers.clone());
+        let mut stage =
+            PipelinedStage::<PipelineFrame>::spawn(name.clone(), move |res, schedule| {
+                for buffer in stage_buffers.0.iter() {
+                    (buffer.init)(res);
+                }
+                res.insert(stage_buffers);
+                for init in init {
+                    init(res, schedule);
+                }
+                schedule
+                    .add_system(unpack_frame)
+                    .into_phase(CoreSystemPhase::First);
+                schedule
+                    .add_system(pack_frame)
+                    .into_phase(CoreSystemPhase::Last);
+            });
+        let schedule = resources.get_mut::<Schedule>().expect("checked above");
+        schedule
+            .add_system(move |mut res: ExclusiveResources<'_>| {
+                let frame = PipelineFrame(buffers.iter().map(|b| (b.take)(&mut res)).collect());
+                let values = match stage.submit(frame) {
+                    Some(previous) => previous.0,
+                    // first frame: start with new buffers
+                    None => buffers.iter().map(|b| (b.take)(&mut res)).collect(),
+                };
+                for (buffer, value) in buffers.iter().zip(values) {
+                    (buffer.put)(&mut res, value);
+                }
+            })
+            .with_name(format!("pipeline `{name}`"))
+            .into_phase(hand_off);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Frame {
+        number: usize,
+        items: Vec<usize>,
+    }
+
+    #[test]
+    fn test_pipelined_stage() {
+        let processed = Arc::new(AtomicUsize::new(0));
+        let processed2 = processed.clone();
+        let mut stage = PipelinedStage::<Frame>::spawn("render", move |resources, schedule| {
+            resources.insert(processed2);
+            schedule.add_system(|frame: &mut Frame, processed: &Arc<AtomicUsize>| {
+                let sum: usize = frame.items.iter().sum();
+                processed.fetch_add(sum, Ordering::SeqCst);
+                frame.items.clear();
+            });
+        });
+
+        let mut buffer = Frame::default();
+        for number in 1..=3 {
+            buffer.number = number;
+            buffer.items.extend([number; 2]);
+            let previous = stage.submit(buffer);
+            assert!(stage.is_in_flight());
+            buffer = match previous {
+                Some(previous) => {
+                    // buffer of the previous frame is handed back
+                    assert_eq!(number - 1, previous.number);
+                    assert!(previous.items.is_empty());
+                    previous
+                }
+                None => Frame::default(),
+            };
+        }
+        let last = stage.wait().unwrap();
+        assert_eq!(3, last.number);
+        assert!(!stage.is_in_flight());
+        assert_eq!(12, processed.load(Ordering::SeqCst));
+        assert!(stage.wait().is_none());
+    }
+
+    fn failing_system() {
+        panic!("failed in stage");
+    }
+
+    #[test]
+    #[should_panic(expected = "failed in stage")]
+    fn test_pipelined_stage_panic() {
+        let mut stage = PipelinedStage::<usize>::spawn("failing", |_resources, schedule| {
+            schedule.add_system(failing_system);
+        });
+        stage.submit(1);
+        stage.wait();
+    }
+
+    #[test]
+    fn test_pipelined_stage_panic_on_drop() {
+        let mut stage = PipelinedStage::<usize>::spawn("failing", |_resources, schedule| {
+            schedule.add_system(failing_system);
+        });
+        stage.submit(1);
+        // the panic of the stage is not propagated by drop
+        drop(stage);
+    }
+
+    #[derive(Default)]
+    struct RenderData(Vec<usize>);
+
+    crate::define_label_enum! {
+        enum RenderPhase: SystemPhase {
+            Prepare,
+            Draw,
+        }
+    }
+
+    type Drawn = Arc<Mutex<Vec<Vec<usize>>>>;
+
+    #[test]
+    fn test_pipeline() {
+        let drawn = Drawn::default();
+        let drawn2 = drawn.clone();
+        let mut resources = Resources::new();
+        Pipeline::new("render")
+            .with_buffered::<RenderData>()
+            .with_phase(RenderPhase::Prepare)
+            .with_phase(RenderPhase::Draw)
+            .with_init(move |resources, schedule| {
+                resources.insert(drawn2);
+                schedule.add_phase_chain([RenderPhase::Prepare, RenderPhase::Draw]);
+                schedule
+                    .add_system(|data: &mut RenderData| data.0.sort_unstable())
+                    .into_phase(RenderPhase::Prepare);
+                schedule
+                    .add_system(|data: &RenderData, drawn: &Drawn| {
+                        drawn.lock().unwrap().push(data.0.clone());
+                    })
+                    .into_phase(RenderPhase::Draw);
+            })
+            .install_into(&mut resources, CoreSystemPhase::Last)
+            .unwrap_or_else(|_| panic!("schedule not available"));
+
+        let mut schedule = resources.remove::<Schedule>().unwrap();
+        let mut frame = 0;
+        let mut recycled = 0;
+        schedule
+            .add_system(move |data: &mut RenderData| {
+                frame += 1;
+                if data.0.capacity() > 0 {
+                    // buffer of an earlier frame
+                    recycled += 1;
+                    assert_eq!(vec![frame - 2, frame - 1], data.0);
+                }
+                data.0.clear();
+                data.0.extend([frame + 1, frame]);
+                assert!(recycled + 2 >= frame);
+            })
+            .into_phase(CoreSystemPhase::Update);
+
+        for _ in 0..4 {
+            schedule.run(&mut resources);
+        }
+        // stops the pipeline after the last frame
+        drop(schedule);
+        assert_eq!(
+            vec![vec![1, 2], vec![2, 3], vec![3, 4], vec![4, 5]],
+            *drawn.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pipeline_without_schedule() {
+        let mut resources = Resources::new();
+        let schedule = resources.remove::<Schedule>().unwrap();
+        let pipeline = Pipeline::new("render")
+            .with_buffered::<RenderData>()
+            .install_into(&mut resources, CoreSystemPhase::Last)
+            .expect_err("schedule is not available");
+        // not installed: can be installed again, when the schedule is back
+        assert!(resources.id::<RenderData>().is_none());
+        resources.insert_again(schedule);
+        assert!(pipeline
+            .install_into(&mut resources, CoreSystemPhase::Last)
+            .is_ok());
+        assert!(resources.id::<RenderData>().is_some());
+    }
+}
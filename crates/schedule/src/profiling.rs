@@ -0,0 +1,142 @@
+//! Hooks for profilers.
+//!
+//! With the `tracing` feature, every run of a schedule and of each system is
+//! wrapped in a span. System spans use the [name](crate::system::SystemDescriptor::name)
+//! of the system, which defaults to the short function name and can be
+//! customized with [`SystemEntryBuilder::with_name`](crate::schedule::SystemEntryBuilder::with_name).
+//! Each system span is nested in a `phase` span with the name of the phase of
+//! the system (when it was added to a phase). Systems of different phases can run concurrently, so there is
+//! no single span covering a phase; the phase spans group the systems of a
+//! phase instead.
+//!
+//! The `tracy` feature additionally emits frame marks ([`frame_mark`]) and
+//! plots to a running [tracy](https://github.com/wolfpld/tracy) client.
+//! Spans are forwarded to tracy by installing a `tracing-tracy` subscriber.
+
+#[cfg(feature = "tracing")]
+pub(crate) type SpanGuard = tracing::span::EnteredSpan;
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct SpanGuard;
+
+#[cfg(feature = "tracing")]
+pub(crate) struct SystemSpanGuard {
+    // dropped in declaration order: the system span is exited first
+    _system: SpanGuard,
+    _phase: SpanGuard,
+}
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct SystemSpanGuard;
+
+#[cfg(feature = "tracing")]
+#[inline]
+pub(crate) fn system_span(name: &str, phase: &str) -> SystemSpanGuard {
+    let phase = if phase.is_empty() {
+        tracing::Span::none()
+    } else {
+        tracing::info_span!("phase", name = phase)
+    };
+    let phase = phase.entered();
+    SystemSpanGuard {
+        _system: tracing::info_span!("system", name).entered(),
+        _phase: phase,
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn system_span(_name: &str, _phase: &str) -> SystemSpanGuard {
+    SystemSpanGuard
+}
+
+#[cfg(feature = "tracing")]
+#[inline]
+pub(crate) fn schedule_span() -> SpanGuard {
+    tracing::info_span!("schedule").entered()
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn schedule_span() -> SpanGuard {
+    SpanGuard
+}
+
+/// Marks the end of a frame.
+///
+/// This should be called once per frame by the main-loop (after the main
+/// schedule was run). This is a no-op, when the `tracy` feature is disabled
+/// or no tracy client is running.
+#[inline]
+pub fn frame_mark() {
+    #[cfg(feature = "tracy")]
+    if let Some(client) = tracy_client::Client::running() {
+        client.frame_mark();
+    }
+}
+
+#[cfg(feature = "tracy")]
+#[inline]
+pub(crate) fn plot_schedule(systems: usize, task_groups: usize) {
+    if let Some(client) = tracy_client::Client::running() {
+        client.plot(tracy_client::plot_name!("schedule systems"), systems as f64);
+        client.plot(
+            tracy_client::plot_name!("schedule task groups"),
+            task_groups as f64,
+        );
+    }
+}
+
+#[cfg(not(feature = "tracy"))]
+#[inline(always)]
+pub(crate) fn plot_schedule(_systems: usize, _task_groups: usize) {}
+
+/// Shortens a type-name by removing the module paths.
+///
+/// `my_crate::systems::update<my_crate::Foo>` becomes `update<Foo>`.
+/// Closures keep the name of the enclosing function:
+/// `my_crate::main::{{closure}}` becomes `main::{{closure}}`.
+pub(crate) fn short_type_name(type_name: &str) -> String {
+    let mut result = String::with_capacity(type_name.len());
+    let mut segment_start = 0;
+    let mut rest = type_name;
+    while let Some(c) = rest.chars().next() {
+        if let Some(tail) = rest.strip_prefix("::") {
+            if tail.starts_with("{{") {
+                // keep the path of the function, that defines the closure
+                result.push_str("::");
+            } else {
+                result.truncate(segment_start);
+            }
+            rest = tail;
+            continue;
+        }
+        result.push(c);
+        if !(c.is_alphanumeric() || c == '_') {
+            segment_start = result.len();
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::short_type_name;
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!("update", short_type_name("my_crate::systems::update"));
+        assert_eq!(
+            "update<Foo, &Bar>",
+            short_type_name("my_crate::systems::update<my_crate::Foo, &other::Bar>")
+        );
+        assert_eq!(
+            "main::{{closure}}",
+            short_type_name("my_crate::main::{{closure}}")
+        );
+        assert_eq!(
+            "setup::{{closure}}::{{closure}}<Foo>",
+            short_type_name("my_crate::setup::{{closure}}::{{closure}}<my_crate::Foo>")
+        );
+        assert_eq!("plain", short_type_name("plain"));
+    }
+}
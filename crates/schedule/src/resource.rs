@@ -6,20 +6,25 @@ use std::{
     marker::PhantomData,
     ops::{Deref, DerefMut},
     ptr::NonNull,
+    sync::atomic::{AtomicU64, Ordering},
 };
 
 use atomic_refcell::AtomicRefCell;
 pub use atomic_refcell::{AtomicRef as Res, AtomicRefMut as ResMut};
 use pulz_bitset::BitSet;
 
-use crate::system::data::{SystemData, SystemDataFetch, SystemDataState};
+use crate::system::{
+    data::{SystemData, SystemDataFetch, SystemDataState},
+    error::SystemError,
+    IntoSystem, System,
+};
 
 #[repr(transparent)]
 pub struct ResourceId<T: ?Sized = crate::Void>(usize, PhantomData<fn(&T)>);
 
 impl<T: ?Sized> std::fmt::Debug for ResourceId<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("ComponentId").field(&self.0).finish()
+        f.debug_tuple("ResourceId").field(&self.0).finish()
     }
 }
 impl<T: ?Sized> Copy for ResourceId<T> {}
@@ -45,7 +50,7 @@ impl<T: ?Sized> PartialEq<Self> for ResourceId<T> {
 impl<T: ?Sized> PartialOrd<Self> for ResourceId<T> {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.0.partial_cmp(&other.0)
+        Some(self.cmp(other))
     }
 }
 impl<T: ?Sized> Hash for ResourceId<T> {
@@ -65,6 +70,11 @@ impl<T: ?Sized> ResourceId<T> {
     pub fn untyped(self) -> ResourceId {
         self.cast()
     }
+
+    #[inline(always)]
+    pub(crate) fn index(self) -> usize {
+        self.0
+    }
 }
 
 impl ResourceId {
@@ -82,6 +92,7 @@ struct Resource {
     name: Cow<'static, str>,
     type_id: TypeId,
     is_send: bool,
+    changed: AtomicU64,
     value: Option<AtomicRefCell<Box<dyn Any>>>,
 }
 
@@ -125,6 +136,7 @@ impl Resource {
             name,
             type_id,
             is_send: false,
+            changed: AtomicU64::new(0),
             value: None,
         }
     }
@@ -166,19 +178,6 @@ impl Resource {
         self.borrow::<T>().map(|v| *v)
     }
 
-    #[inline]
-    fn get_mut<T>(&mut self) -> Option<&mut T>
-    where
-        T: 'static,
-    {
-        self.value.as_mut()?.get_mut().downcast_mut::<T>()
-    }
-
-    #[inline]
-    fn get_any(&mut self) -> Option<&mut dyn Any> {
-        Some(self.value.as_mut()?.get_mut().deref_mut())
-    }
-
     #[inline]
     fn remove<T>(&mut self) -> Option<RemovedResource<T>>
     where
@@ -211,6 +210,7 @@ pub struct Resources {
     by_type_id: BTreeMap<TypeId, ResourceId>,
     pub(crate) meta_by_type_id: BTreeMap<TypeId, Box<dyn Any + Send + Sync>>,
     pub(crate) modules: BTreeSet<TypeId>,
+    change_tick: AtomicU64,
     _unsend: PhantomData<NonNull<()>>,
 }
 
@@ -222,9 +222,11 @@ impl Resources {
             by_type_id: BTreeMap::new(),
             meta_by_type_id: BTreeMap::new(),
             modules: BTreeSet::new(),
+            change_tick: AtomicU64::new(0),
             _unsend: PhantomData,
         };
         res.init_unsend::<crate::schedule::Schedule>();
+        res.init_unsend::<crate::schedule::Schedules>();
         res
     }
 
@@ -233,7 +235,7 @@ impl Resources {
     where
         T: 'static,
     {
-        let type_id = std::any::TypeId::of::<T>();
+        let type_id = TypeId::of::<T>();
         self.by_type_id.get(&type_id).copied().map(ResourceId::cast)
     }
 
@@ -270,7 +272,7 @@ impl Resources {
     where
         T: 'static,
     {
-        let type_id = std::any::TypeId::of::<T>();
+        let type_id = TypeId::of::<T>();
         let resources = &mut self.resources;
         let id = self
             .by_type_id
@@ -291,10 +293,12 @@ impl Resources {
     where
         T: Send + Sync + 'static,
     {
+        let tick = self.next_change_tick();
         let (id, res) = self.get_resource::<T>();
         res.is_send = true;
         let boxed: Box<dyn Any> = Box::new(value);
         res.value = Some(AtomicRefCell::new(boxed));
+        *res.changed.get_mut() = tick;
         id
     }
 
@@ -302,9 +306,33 @@ impl Resources {
     where
         T: 'static,
     {
+        let tick = self.next_change_tick();
         let (id, res) = self.get_resource::<T>();
         res.is_send = false;
         res.value = Some(AtomicRefCell::new(Box::new(value)));
+        *res.changed.get_mut() = tick;
+        id
+    }
+
+    /// Inserts a resource that is not registered under its type.
+    ///
+    /// Every call creates a new resource, so multiple values of the same
+    /// type can be stored. The resource is only accessible through the
+    /// returned id (e.g. with [`Self::borrow_res_id`]); [`Self::id`] and the
+    /// type-based accessors don't find it.
+    pub fn insert_anonymous<T>(&mut self, value: T) -> ResourceId<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let tick = self.next_change_tick();
+        let id = ResourceId(self.resources.len(), PhantomData);
+        let name = std::any::type_name::<T>();
+        let mut res = Resource::new(id.cast(), TypeId::of::<T>(), Cow::Borrowed(name));
+        res.is_send = true;
+        let boxed: Box<dyn Any> = Box::new(value);
+        res.value = Some(AtomicRefCell::new(boxed));
+        *res.changed.get_mut() = tick;
+        self.resources.push(res);
         id
     }
 
@@ -392,7 +420,10 @@ impl Resources {
     where
         T: 'static,
     {
-        self.resources.get(resource_id.0)?.borrow_mut()
+        let r = self.resources.get(resource_id.0)?;
+        let value = r.borrow_mut()?;
+        self.mark_changed(r);
+        Some(value)
     }
 
     pub fn borrow_res_mut_meta<T>(&self, resource_id: ResourceId<T>) -> Option<ResMut<'_, T>>
@@ -401,11 +432,16 @@ impl Resources {
     {
         let r = self.resources.get(resource_id.0)?;
         let meta = self.get_meta::<T>()?;
-        ResMut::filter_map(r.borrow_any_mut()?, |v| meta.convert_mut(v))
+        let value = ResMut::filter_map(r.borrow_any_mut()?, |v| meta.convert_mut(v))?;
+        self.mark_changed(r);
+        Some(value)
     }
 
     pub fn borrow_res_any_mut(&self, resource_id: ResourceId) -> Option<ResMut<'_, dyn Any>> {
-        self.resources.get(resource_id.0)?.borrow_any_mut()
+        let r = self.resources.get(resource_id.0)?;
+        let value = r.borrow_any_mut()?;
+        self.mark_changed(r);
+        Some(value)
     }
 
     #[inline]
@@ -437,15 +473,18 @@ impl Resources {
     where
         T: 'static,
     {
-        self.resources
-            .get_mut(resource_id.0)
-            .and_then(Resource::get_mut)
+        let r = self.resources.get_mut(resource_id.0)?;
+        let value = r.value.as_mut()?.get_mut().downcast_mut::<T>()?;
+        // only counted as a change, when the resource was found
+        *r.changed.get_mut() = next_tick(&self.change_tick);
+        Some(value)
     }
 
     pub fn get_mut_any(&mut self, resource_id: ResourceId) -> Option<&'_ mut dyn Any> {
-        self.resources
-            .get_mut(resource_id.0)
-            .and_then(Resource::get_any)
+        let r = self.resources.get_mut(resource_id.0)?;
+        let value = r.value.as_mut()?.get_mut().deref_mut();
+        *r.changed.get_mut() = next_tick(&self.change_tick);
+        Some(value)
     }
 
     #[inline]
@@ -470,11 +509,55 @@ impl Resources {
     where
         T: 'static,
     {
-        self.resources
-            .get_mut(removed.id.0)
-            .unwrap()
-            .insert_again(removed)
+        // the resource may have been modified while it was removed
+        let tick = self.next_change_tick();
+        let r = self.resources.get_mut(removed.id.0).unwrap();
+        r.insert_again(removed);
+        *r.changed.get_mut() = tick;
+    }
+
+    #[inline]
+    fn next_change_tick(&self) -> u64 {
+        next_tick(&self.change_tick)
+    }
+
+    #[inline]
+    fn mark_changed(&self, resource: &Resource) {
+        resource
+            .changed
+            .store(self.next_change_tick(), Ordering::Release);
     }
+
+    /// The current change tick.
+    ///
+    /// The change tick is incremented on every mutable access to a resource.
+    #[inline]
+    pub fn change_tick(&self) -> u64 {
+        self.change_tick.load(Ordering::Relaxed)
+    }
+
+    /// Returns the change tick of the last mutable access to the resource.
+    ///
+    /// Mutable access (e.g. with [`Self::borrow_res_mut`], [`Self::get_mut`]
+    /// or a `&mut T` system parameter) counts as a change, regardless of
+    /// whether the value was actually modified.
+    #[inline]
+    pub fn last_changed<T>(&self, resource_id: ResourceId<T>) -> Option<u64> {
+        let r = self.resources.get(resource_id.0)?;
+        Some(r.changed.load(Ordering::Acquire))
+    }
+
+    /// Returns `true`, when the resource was (mutably) accessed after the
+    /// given change tick (see [`Self::change_tick`]).
+    #[inline]
+    pub fn is_changed_since<T>(&self, resource_id: ResourceId<T>, tick: u64) -> bool {
+        matches!(self.last_changed(resource_id), Some(changed) if changed > tick)
+    }
+}
+
+#[inline]
+fn next_tick(change_tick: &AtomicU64) -> u64 {
+    change_tick.fetch_add(1, Ordering::Relaxed) + 1
 }
 
 impl Default for Resources {
@@ -601,7 +684,21 @@ impl ResourceAccess {
     }
     #[inline]
     pub fn is_exclusive<T>(&self, resource: ResourceId<T>) -> bool {
-        self.shared.contains(resource.0)
+        self.exclusive.contains(resource.0)
+    }
+    /// Iterates over the resources with shared (read) access.
+    #[inline]
+    pub fn shared_resources(&self) -> impl Iterator<Item = ResourceId> + '_ {
+        self.shared.iter().map(|i| ResourceId(i, PhantomData))
+    }
+    /// Iterates over the resources with exclusive (write) access.
+    #[inline]
+    pub fn exclusive_resources(&self) -> impl Iterator<Item = ResourceId> + '_ {
+        self.exclusive.iter().map(|i| ResourceId(i, PhantomData))
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.shared.is_empty() && self.exclusive.is_empty()
     }
     #[inline]
     pub fn clear(&mut self) {
@@ -621,6 +718,15 @@ impl ResourceAccess {
     }
 }
 
+impl std::fmt::Debug for ResourceAccess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResourceAccess")
+            .field("shared", &self.shared)
+            .field("exclusive", &self.exclusive)
+            .finish()
+    }
+}
+
 impl Default for ResourceAccess {
     #[inline]
     fn default() -> Self {
@@ -803,3 +909,260 @@ impl<'r, T: 'static> SystemDataFetch<'r> for Option<ResMut<'r, T>> {
         }
     }
 }
+
+/// Shared access to a resource, that also tells whether the resource was
+/// changed since the last run of the system.
+///
+/// See [`Resources::last_changed`] for what counts as a change.
+///
+/// The system is run regardless of the change; use [`run_if_changed`] for
+/// skipping it when the resource is unchanged.
+pub struct ChangedRes<'r, T> {
+    value: &'r T,
+    is_changed: bool,
+}
+
+impl<T> ChangedRes<'_, T> {
+    /// Returns `true`, when the resource was changed since the last run of
+    /// the system (or when the system runs for the first time).
+    #[inline]
+    pub fn is_changed(&self) -> bool {
+        self.is_changed
+    }
+}
+
+impl<T> Deref for ChangedRes<'_, T> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+#[doc(hidden)]
+pub struct ChangedResState<T> {
+    id: ResourceId<T>,
+    last_seen: u64,
+}
+
+#[doc(hidden)]
+pub struct ChangedResFetch<'r, T> {
+    value: Res<'r, T>,
+    is_changed: bool,
+}
+
+impl<T> SystemData for ChangedRes<'_, T>
+where
+    T: 'static,
+{
+    type State = ChangedResState<T>;
+    type Fetch<'r> = ChangedResFetch<'r, T>;
+    type Item<'a> = ChangedRes<'a, T>;
+
+    #[inline]
+    fn get<'a>(fetch: &'a mut Self::Fetch<'_>) -> Self::Item<'a> {
+        ChangedRes {
+            value: &fetch.value,
+            is_changed: fetch.is_changed,
+        }
+    }
+}
+
+unsafe impl<T> SystemDataState for ChangedResState<T>
+where
+    T: 'static,
+{
+    #[inline]
+    fn init(resources: &mut Resources) -> Self {
+        Self {
+            id: resources.expect_id::<T>(),
+            last_seen: 0,
+        }
+    }
+
+    #[inline]
+    fn update_access(&self, _resources: &Resources, access: &mut ResourceAccess) {
+        access.add_shared_checked(self.id);
+    }
+}
+
+impl<'r, T: 'static> SystemDataFetch<'r> for ChangedResFetch<'r, T> {
+    type State = ChangedResState<T>;
+
+    #[inline]
+    fn fetch(res: &'r Resources, state: &'r mut Self::State) -> Self {
+        let value = res.borrow_res_id(state.id).unwrap();
+        let changed = res.last_changed(state.id).unwrap();
+        let is_changed = changed > state.last_seen;
+        state.last_seen = changed;
+        Self { value, is_changed }
+    }
+}
+
+/// Wraps a system, so it is only run when the resource `T` was changed since
+/// its last run (or when it runs for the first time).
+///
+/// In contrast to [`ChangedRes`], the system is skipped completely.
+///
+/// ```
+/// use pulz_schedule::prelude::*;
+/// use pulz_schedule::resource::run_if_changed;
+///
+/// #[derive(Default)]
+/// struct Settings(u32);
+///
+/// fn upload_settings(settings: &Settings) {
+///     // ...
+/// }
+///
+/// let mut resources = Resources::new();
+/// resources.init::<Settings>();
+/// let mut schedule = Schedule::new();
+/// schedule.add_system(run_if_changed::<Settings, _, _>(upload_settings));
+/// schedule.run(&mut resources);
+/// ```
+pub fn run_if_changed<T, S, Marker>(system: S) -> RunIfChanged<T, S::System>
+where
+    T: 'static,
+    S: IntoSystem<(), Marker>,
+{
+    RunIfChanged {
+        system: system.into_system(),
+        id: None,
+        last_seen: 0,
+    }
+}
+
+/// A system, that is only run when a resource was changed (see
+/// [`run_if_changed`]).
+pub struct RunIfChanged<T, S> {
+    system: S,
+    id: Option<ResourceId<T>>,
+    last_seen: u64,
+}
+
+// SAFETY: only the change-tick of the resource is accessed in addition to the
+// wrapped system
+unsafe impl<T, S> System for RunIfChanged<T, S>
+where
+    T: 'static,
+    S: System,
+{
+    fn init(&mut self, resources: &mut Resources) {
+        self.id = Some(resources.expect_id::<T>());
+        self.system.init(resources);
+    }
+
+    fn run(&mut self, resources: &Resources, args: ()) -> Result<(), SystemError> {
+        let id = self.id.expect("not initialized");
+        let changed = resources.last_changed(id).unwrap_or(0);
+        if changed <= self.last_seen {
+            return Ok(());
+        }
+        self.last_seen = changed;
+        self.system.run(resources, args)
+    }
+
+    #[inline]
+    fn is_send(&self) -> bool {
+        self.system.is_send()
+    }
+
+    #[inline]
+    fn update_access(&self, resources: &Resources, access: &mut ResourceAccess) {
+        self.system.update_access(resources, access)
+    }
+
+    #[inline]
+    fn type_name(&self) -> &'static str {
+        self.system.type_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::schedule::Schedule;
+
+    #[derive(Default)]
+    struct Settings(u32);
+
+    #[test]
+    fn test_change_detection() {
+        let mut resources = Resources::new();
+        let id = resources.init::<Settings>();
+        let tick = resources.change_tick();
+        assert!(!resources.is_changed_since(id, tick));
+        let _ = resources.borrow_res::<Settings>().unwrap();
+        assert!(!resources.is_changed_since(id, tick));
+        resources.borrow_res_mut::<Settings>().unwrap().0 = 1;
+        assert!(resources.is_changed_since(id, tick));
+
+        let uploads = Arc::new(AtomicUsize::new(0));
+        let mut schedule = Schedule::new();
+        {
+            let uploads = uploads.clone();
+            schedule.add_system(move |settings: ChangedRes<'_, Settings>| {
+                if settings.is_changed() {
+                    uploads.fetch_add(settings.0 as usize, Ordering::Relaxed);
+                }
+            });
+        }
+        schedule.run(&mut resources);
+        schedule.run(&mut resources);
+        assert_eq!(1, uploads.load(Ordering::Relaxed));
+
+        resources.get_mut::<Settings>().unwrap().0 = 10;
+        schedule.run(&mut resources);
+        schedule.run(&mut resources);
+        assert_eq!(11, uploads.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_insert_anonymous() {
+        let mut resources = Resources::new();
+        let named = resources.insert(Settings(1));
+        let a = resources.insert_anonymous(Settings(2));
+        let b = resources.insert_anonymous(Settings(3));
+        assert_ne!(a, b);
+        assert_eq!(Some(named), resources.id::<Settings>());
+        assert_eq!(1, resources.borrow_res::<Settings>().unwrap().0);
+        assert_eq!(2, resources.borrow_res_id(a).unwrap().0);
+        assert_eq!(3, resources.borrow_res_id(b).unwrap().0);
+    }
+
+    #[test]
+    fn test_run_if_changed() {
+        let mut resources = Resources::new();
+        resources.init::<Settings>();
+        let runs = Arc::new(AtomicUsize::new(0));
+        let mut schedule = Schedule::new();
+        {
+            let runs = runs.clone();
+            schedule.add_system(run_if_changed::<Settings, _, _>(move |_: &Settings| {
+                runs.fetch_add(1, Ordering::Relaxed);
+            }));
+        }
+        schedule.run(&mut resources);
+        schedule.run(&mut resources);
+        assert_eq!(1, runs.load(Ordering::Relaxed));
+
+        resources.get_mut::<Settings>().unwrap().0 = 10;
+        schedule.run(&mut resources);
+        schedule.run(&mut resources);
+        assert_eq!(2, runs.load(Ordering::Relaxed));
+
+        // failed lookups are not counted as a change
+        let tick = resources.change_tick();
+        assert!(resources.get_mut::<u32>().is_none());
+        let id = resources.expect_id::<Settings>();
+        assert!(resources.get_mut_id(id.untyped().typed::<u32>()).is_none());
+        assert_eq!(tick, resources.change_tick());
+        assert!(!resources.is_changed_since(id, tick));
+    }
+}
@@ -1,12 +1,24 @@
-use std::time::Instant;
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 
 use crossbeam_utils::sync::WaitGroup;
 use pulz_bitset::BitSet;
 
 use crate::{
-    label::{CoreSystemPhase, SystemPhase, SystemPhaseId, UndefinedSystemPhase},
-    resource::{ResourceAccess, Resources},
-    system::{ExclusiveSystem, IntoSystemDescriptor, System, SystemDescriptor, SystemVariant},
+    label::{
+        CoreSystemPhase, ScheduleLabel, ScheduleLabelId, SystemPhase, SystemPhaseId,
+        UndefinedSystemPhase,
+    },
+    profiling::{plot_schedule, schedule_span, system_span},
+    resource::{ResourceAccess, ResourceId, Resources},
+    system::{
+        error::{SystemError, SystemErrorPolicy},
+        ExclusiveSystem, IntoSystemDescriptor, System, SystemDescriptor, SystemVariant,
+    },
+    watchdog::{Watchdog, WatchdogState},
 };
 
 type HashMap<K, V> = std::collections::HashMap<K, V, fnv::FnvBuildHasher>;
@@ -172,20 +184,64 @@ struct ResourceMutTracker(Vec<ResourceMutTrackerEntry>);
 
 #[derive(Clone, Debug)]
 enum ResourceConflict {
-    #[allow(unused)] // used for Debug
     ExclusiveExclusive {
-        resource: usize,
+        resource: ResourceId,
         system_a: usize,
         system_b: usize,
     },
-    #[allow(unused)] // used for Debug
     SharedExclusive {
-        resource: usize,
+        resource: ResourceId,
         system_shared: Vec<usize>,
         system_exclusive: usize,
     },
 }
 
+impl ResourceConflict {
+    /// Describes the conflict with the names of the systems and the resource.
+    fn explain(&self, systems: &[SystemDescriptor], resources: &Resources) -> String {
+        use std::fmt::Write;
+        let resource = match self {
+            Self::ExclusiveExclusive { resource, .. } | Self::SharedExclusive { resource, .. } => {
+                *resource
+            }
+        };
+        let resource_name = resources.name(resource).unwrap_or("<unknown>");
+        let mut out = format!("resource conflict on `{resource_name}` ({resource:?}):\n");
+        let mut line = |s: usize, mode: &str| {
+            let _ = writeln!(
+                out,
+                "  - system `{}` (#{s}) {mode} `{resource_name}`",
+                systems[s].name()
+            );
+        };
+        match self {
+            Self::ExclusiveExclusive {
+                system_a, system_b, ..
+            } => {
+                line(*system_a, "writes");
+                line(*system_b, "writes");
+            }
+            Self::SharedExclusive {
+                system_shared,
+                system_exclusive,
+                ..
+            } => {
+                line(*system_exclusive, "writes");
+                for &s in system_shared {
+                    line(s, "reads");
+                }
+            }
+        }
+        out.push_str(
+            "these systems are not ordered relative to each other, so they could run at the same time.\n\
+            help: define an order, e.g. by moving one of the systems into another phase \
+            (`into_phase`, `before`, `after`) that depends on the phase of the other system \
+            (`Schedule::add_phase_dependency`).",
+        );
+        out
+    }
+}
+
 impl ResourceMutTracker {
     #[inline]
     fn new() -> Self {
@@ -202,12 +258,12 @@ impl ResourceMutTracker {
 
     fn mark_exclusive(
         &mut self,
-        resource: usize,
+        resource: ResourceId,
         current_group: usize,
         system: usize,
         result: &mut [usize],
     ) -> Result<(), ResourceConflict> {
-        let entry = self.get_entry_mut(resource);
+        let entry = self.get_entry_mut(resource.index());
         if entry.last_exclusive == current_group {
             Err(ResourceConflict::ExclusiveExclusive {
                 resource,
@@ -235,12 +291,12 @@ impl ResourceMutTracker {
 
     fn mark_shared(
         &mut self,
-        resource: usize,
+        resource: ResourceId,
         current_group: usize,
         system: usize,
         result: &mut [usize],
     ) -> Result<(), ResourceConflict> {
-        let entry = self.get_entry_mut(resource);
+        let entry = self.get_entry_mut(resource.index());
         if entry.last_exclusive == current_group {
             Err(ResourceConflict::SharedExclusive {
                 resource,
@@ -270,10 +326,10 @@ impl ResourceMutTracker {
         system: usize,
         result: &mut [usize],
     ) -> Result<(), ResourceConflict> {
-        for resource in access.exclusive.iter() {
+        for resource in access.exclusive_resources() {
             self.mark_exclusive(resource, current_group, system, result)?;
         }
-        for resource in access.shared.iter() {
+        for resource in access.shared_resources() {
             self.mark_shared(resource, current_group, system, result)?;
         }
         Ok(())
@@ -284,6 +340,10 @@ pub struct Schedule {
     systems: Vec<SystemDescriptor>,
     graph: DependencyGraph,
     ordered_task_groups: Vec<TaskGroup>,
+    error_policy: SystemErrorPolicy,
+    sync_points: BitSet, // dependency nodes
+    deterministic: bool,
+    watchdog: Option<Arc<WatchdogState>>,
     dirty: bool,
 }
 
@@ -292,15 +352,72 @@ impl Schedule {
         let mut graph = DependencyGraph::new();
         graph.insert_phase(CoreSystemPhase::First.as_label()); // < index=0 (FIRST_NODE_INDEX)
         graph.insert_phase(CoreSystemPhase::Last.as_label()); // < index=1 (LAST_NODE_INDEX)
-        graph.insert_phase(CoreSystemPhase::Update.as_label());
+        let update = graph.insert_phase(CoreSystemPhase::Update.as_label()).index;
+        let apply_deferred = graph.insert_phase(CoreSystemPhase::ApplyDeferred.as_label());
+        apply_deferred.dependencies.insert(update);
+        let mut sync_points = BitSet::new();
+        sync_points.insert(apply_deferred.index);
         Self {
             systems: Vec::new(),
             graph,
             ordered_task_groups: Vec::new(),
+            error_policy: SystemErrorPolicy::Panic,
+            sync_points,
+            deterministic: false,
+            watchdog: None,
             dirty: true,
         }
     }
 
+    #[inline]
+    pub fn error_policy(&self) -> SystemErrorPolicy {
+        self.error_policy
+    }
+
+    /// Defines how errors returned by systems of this schedule are handled.
+    #[inline]
+    pub fn set_error_policy(&mut self, policy: SystemErrorPolicy) {
+        self.error_policy = policy;
+    }
+
+    #[inline]
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    /// Enables or disables the deterministic execution mode.
+    ///
+    /// In deterministic mode, all systems are run on the current thread in
+    /// the canonical (topological) order of the schedule (systems without an
+    /// ordering between them run in registration order), and errors are
+    /// handled in the same order. Together with the stable archetype order
+    /// and entity allocation of the world, this makes a run reproducible
+    /// (e.g. for lockstep networking or replays).
+    #[inline]
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+
+    #[inline]
+    pub fn watchdog(&self) -> Option<&Watchdog> {
+        self.watchdog.as_deref().map(WatchdogState::config)
+    }
+
+    /// Installs (or removes) a watchdog, that reports systems exceeding a
+    /// wall-time budget, and runs of this schedule that make no progress.
+    ///
+    /// See [`Watchdog`] for details.
+    pub fn set_watchdog(&mut self, watchdog: Option<Watchdog>) {
+        self.watchdog = watchdog.map(Watchdog::start);
+        self.update_watchdog_names();
+    }
+
+    fn update_watchdog_names(&self) {
+        if let Some(watchdog) = &self.watchdog {
+            watchdog.set_names(self.systems.iter().map(|s| s.name.to_string()));
+        }
+    }
+
     #[inline]
     pub fn add_system<Marker>(
         &mut self,
@@ -314,6 +431,7 @@ impl Schedule {
         let index = self.systems.len();
         self.systems.push(system);
         SystemEntryBuilder {
+            system: self.systems.last_mut().unwrap(),
             graph: &mut self.graph,
             id: SystemId(index),
             dependency_node: !0,
@@ -350,6 +468,134 @@ impl Schedule {
             .insert(first_index);
     }
 
+    /// Inserts a phase between two existing phases: `phase` runs after the
+    /// phase `after`, and before the phase `before`.
+    ///
+    /// This can also be done after the schedule was run (e.g. by a module
+    /// adding a `PrePhysics` phase between [`CoreSystemPhase::Update`] and a
+    /// `Physics` phase). The schedule is rebuilt on the next run.
+    pub fn insert_phase_between(
+        &mut self,
+        after: impl SystemPhase,
+        phase: impl SystemPhase,
+        before: impl SystemPhase,
+    ) {
+        self._add_phase_chain([after.as_label(), phase.as_label(), before.as_label()].into_iter());
+    }
+
+    #[inline]
+    pub fn contains_phase(&self, phase: impl SystemPhase) -> bool {
+        self.graph.phase_labels.contains_key(&phase.as_label())
+    }
+
+    /// Iterates over the labels of all phases of this schedule.
+    pub fn phases(&self) -> impl Iterator<Item = SystemPhaseId> + '_ {
+        let undefined = UndefinedSystemPhase::Undefined.as_label();
+        self.graph
+            .phase_labels
+            .keys()
+            .copied()
+            .filter(move |&l| l != undefined)
+    }
+
+    /// Returns information about the given phase (direct dependencies and
+    /// systems), or `None` when there is no such phase.
+    pub fn phase_info(&self, phase: impl SystemPhase) -> Option<PhaseInfo> {
+        let label = phase.as_label();
+        let &index = self.graph.phase_labels.get(&label)?;
+        let node = &self.graph.nodes[index];
+        let dependencies = self
+            .graph
+            .phase_labels
+            .iter()
+            .filter(|(_, &i)| node.dependencies.contains(i))
+            .map(|(&l, _)| l)
+            .collect();
+        let mut systems: Vec<_> = node
+            .systems
+            .iter()
+            .copied()
+            .chain(
+                node.sub_nodes
+                    .iter()
+                    .flat_map(|n| self.graph.nodes[n].systems.iter().copied()),
+            )
+            .collect();
+        systems.sort_unstable();
+        Some(PhaseInfo {
+            label,
+            dependencies,
+            systems: systems.into_iter().map(SystemId).collect(),
+            is_sync_point: self.sync_points.contains(index),
+        })
+    }
+
+    /// Marks the given phase as a sync-point.
+    ///
+    /// Exclusive and non-send systems are usually delayed as far as possible
+    /// (until another system depends on them), so that they don't interrupt
+    /// concurrent systems. They are not delayed past a sync-point: they are
+    /// run at the end of the sync-point phase, at the latest.
+    ///
+    /// [`CoreSystemPhase::ApplyDeferred`] is a sync-point, that is run after
+    /// [`CoreSystemPhase::Update`]. Systems that apply queued structural changes
+    /// can be added to this phase, and other systems can be ordered relative to it.
+    pub fn add_sync_point(&mut self, phase: impl SystemPhase) {
+        self.dirty = true;
+        let index = self.graph.insert_phase(phase.as_label()).index;
+        self.sync_points.insert(index);
+    }
+
+    /// Returns the system with the given id.
+    ///
+    /// Use [`SystemDescriptor::access`] to get the computed resource access
+    /// of the system (after the schedule was initialized).
+    #[inline]
+    pub fn system(&self, id: SystemId) -> Option<&SystemDescriptor> {
+        self.systems.get(id.0)
+    }
+
+    /// Iterates over all systems of this schedule (in insertion order).
+    #[inline]
+    pub fn systems(&self) -> impl Iterator<Item = (SystemId, &SystemDescriptor)> + '_ {
+        self.systems
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (SystemId(i), s))
+    }
+
+    /// Returns the id of the first system with the given name.
+    pub fn find_system(&self, name: &str) -> Option<SystemId> {
+        self.systems
+            .iter()
+            .position(|s| s.name() == name)
+            .map(SystemId)
+    }
+
+    /// Replaces the system with the given id, and returns the previous system.
+    ///
+    /// The new system keeps the name and the position (phase and ordering) of
+    /// the previous system. The new system is initialized, and the schedule is
+    /// rebuilt (and checked for conflicts) on the next run.
+    ///
+    /// # Panics
+    ///
+    /// Panics when there is no system with the given id.
+    pub fn replace_system<Marker>(
+        &mut self,
+        id: SystemId,
+        system: impl IntoSystemDescriptor<Marker>,
+    ) -> SystemDescriptor {
+        let mut system = system.into_system_descriptor();
+        let previous = &mut self.systems[id.0];
+        system.name = std::mem::take(&mut previous.name);
+        system.phase = previous.phase;
+        self.dirty = true;
+        let mut previous = std::mem::replace(previous, system);
+        previous.name = self.systems[id.0].name.clone();
+        previous
+    }
+
     fn has_exclusive_systems(&self) -> bool {
         self.systems.iter().any(|s| s.is_exclusive())
     }
@@ -366,15 +612,19 @@ impl Schedule {
 
     fn mark_system_resource_dependencies_and_check_conflicts(
         &self,
+        resources: &Resources,
         result: &mut [usize], // result[system] = first dependency (by resources)
         groups: &[Vec<usize>], // groups[group][i] = dependency node
     ) {
-        let mut resources = ResourceMutTracker::new();
+        let mut tracker = ResourceMutTracker::new();
         for (g, group) in groups.iter().enumerate() {
             for (s, access) in self.get_system_accesses(group) {
-                if let Err(e) = resources.mark_access(access, g, s, result) {
+                if let Err(e) = tracker.mark_access(access, g, s, result) {
                     let _ = self.debug_dump_if_env_ext(Some(groups), None);
-                    panic!("resource conflict ({e:?})\nuse PULZ_DUMP_SCHEDULE=[path] to dump a .dot file of the schedule.");
+                    panic!(
+                        "{}\nuse PULZ_DUMP_SCHEDULE=[path] to dump a .dot file of the schedule.",
+                        e.explain(&self.systems, resources)
+                    );
                 }
             }
         }
@@ -407,6 +657,7 @@ impl Schedule {
 
     fn get_conflict_groups_for_systems(
         &self,
+        resources: &Resources,
         groups: &[Vec<usize>], // feoups[group][i] = dependency node
     ) -> Vec<usize> {
         // `groups` define, when a system/node can be scheduled FIRST.
@@ -415,15 +666,16 @@ impl Schedule {
         // (defines the smallest index of the group where it is required next).
         let mut result = Vec::new();
         result.resize(self.systems.len(), !0);
-        self.mark_system_resource_dependencies_and_check_conflicts(&mut result, groups);
+        self.mark_system_resource_dependencies_and_check_conflicts(resources, &mut result, groups);
         self.mark_system_dependencies_from_graph(&mut result, groups);
         result
     }
 
     fn move_nonsync_and_exclusive(
         &self,
-        groups: &mut Vec<Vec<usize>>,
+        groups: &mut [Vec<usize>],
         system_conflict_groups: &[usize],
+        sync_groups: &BitSet,
     ) {
         if groups.is_empty() {
             return;
@@ -441,13 +693,19 @@ impl Schedule {
                 let s = group[j];
                 let system = &self.systems[s];
                 if system.is_exclusive() {
-                    tmp_excl.push(group.swap_remove(j));
+                    tmp_excl.push(group.remove(j));
                 } else if !system.is_send() {
-                    tmp_nosend.push(group.swap_remove(j));
+                    tmp_nosend.push(group.remove(j));
                 } else {
                     j += 1;
                 }
             }
+
+            if sync_groups.contains(i) {
+                // don't delay them past a sync-point
+                group.append(&mut tmp_nosend);
+                group.append(&mut tmp_excl);
+            }
         }
         groups[len - 1].extend(tmp_nosend);
         groups[len - 1].extend(tmp_excl);
@@ -464,7 +722,7 @@ impl Schedule {
             let s = src[j];
             let conflict = system_conflict_groups[s] <= i;
             if conflict {
-                src.swap_remove(j);
+                src.remove(j);
                 groups[i - 1].push(s);
             } else {
                 j += 1;
@@ -472,7 +730,7 @@ impl Schedule {
         }
     }
 
-    fn rebuild(&mut self) {
+    fn rebuild(&mut self, resources: &Resources) {
         // group systems based on their dependency graph
         let groups = match self.graph.build_topological_groups() {
             Ok(groups) => groups,
@@ -483,7 +741,14 @@ impl Schedule {
         };
 
         // add implicit dependencies, and check conflicts
-        let system_conflict_groups = self.get_conflict_groups_for_systems(&groups);
+        let system_conflict_groups = self.get_conflict_groups_for_systems(resources, &groups);
+
+        let sync_groups: BitSet = groups
+            .iter()
+            .enumerate()
+            .filter(|(_, g)| g.iter().any(|&n| self.sync_points.contains(n)))
+            .map(|(i, _)| i)
+            .collect();
 
         // map dependency-nodes to systems
         // groups[group][i] = dependency node => groups[group][j] = system
@@ -497,7 +762,7 @@ impl Schedule {
             .collect::<Vec<_>>();
 
         // move non-sync and exclusive systems to the end as far as possible (first nonsend then exclusive)
-        self.move_nonsync_and_exclusive(&mut groups, &system_conflict_groups);
+        self.move_nonsync_and_exclusive(&mut groups, &system_conflict_groups, &sync_groups);
 
         // build final
         self.ordered_task_groups.clear();
@@ -507,23 +772,31 @@ impl Schedule {
             for &s in group {
                 if self.systems[s].is_exclusive() {
                     if !current_concurrent_group.is_empty() {
-                        current_group_start = i;
                         self.ordered_task_groups
                             .push(TaskGroup::Concurrent(std::mem::take(
                                 &mut current_concurrent_group,
                             )));
                     }
                     self.ordered_task_groups.push(TaskGroup::Exclusive(s));
+                    // exclusive systems are at the end of their group
+                    current_group_start = i + 1;
                 } else {
                     // translate conflict group index to offset into current group
                     let conflict_group_index = system_conflict_groups[s];
                     let conflict_index = if conflict_group_index == !0 {
                         !0
                     } else {
-                        groups[current_group_start..conflict_group_index]
+                        let groups_between = &groups[current_group_start..conflict_group_index];
+                        if groups_between
                             .iter()
-                            .map(|g| g.len())
-                            .sum()
+                            .flatten()
+                            .any(|&s| self.systems[s].is_exclusive())
+                        {
+                            // the exclusive system in between already waits for this system
+                            !0
+                        } else {
+                            groups_between.iter().map(|g| g.len()).sum()
+                        }
                     };
                     current_concurrent_group.push((s, conflict_index));
                 }
@@ -545,20 +818,30 @@ impl Schedule {
                 sys.init(resources)
             }
 
-            self.rebuild();
+            self.rebuild(resources);
+            self.update_watchdog_names();
         }
     }
 
     #[inline]
     pub fn run(&mut self, resources: &mut Resources) {
-        self.executor(resources).run();
+        let deterministic = self.deterministic;
+        let mut executor = self.executor(resources);
+        if deterministic {
+            executor.run_local();
+        } else {
+            executor.run();
+        }
+        plot_schedule(self.systems.len(), self.ordered_task_groups.len());
     }
 
-    pub fn executor<'s>(&'s mut self, resources: &'s mut Resources) -> ScheduleExecution<'_> {
+    pub fn executor<'s>(&'s mut self, resources: &'s mut Resources) -> ScheduleExecution<'s> {
         self.init(resources);
         ScheduleExecution {
             systems: &mut self.systems,
             ordered_task_groups: &self.ordered_task_groups,
+            error_policy: self.error_policy,
+            watchdog: self.watchdog.as_ref(),
             resources,
             tasks_rev: Vec::new(),
         }
@@ -583,10 +866,13 @@ impl Schedule {
         SharedScheduleExecution {
             systems: &mut self.systems,
             concurrent_tasks,
+            error_policy: self.error_policy,
+            watchdog: self.watchdog.as_ref(),
             resources,
 
             #[cfg(not(target_os = "unknown"))]
             tasks_rev: Vec::new(),
+            errors: Mutex::new(Vec::new()),
         }
     }
 
@@ -733,6 +1019,37 @@ impl Schedule {
         writeln!(w, "}}")?;
         Ok(())
     }
+
+    /// Writes a table of the resources that are read and written by every
+    /// system of this schedule.
+    ///
+    /// The access of the systems is only known after the schedule was
+    /// initialized.
+    pub fn write_access(
+        &self,
+        w: &mut dyn std::io::Write,
+        resources: &Resources,
+    ) -> std::io::Result<()> {
+        let name = |id: ResourceId| resources.name(id).unwrap_or("<unknown>");
+        for (s, system) in self.systems.iter().enumerate() {
+            write!(w, "#{s} {}:", system.name())?;
+            let Some(access) = system.access() else {
+                writeln!(w, " exclusive")?;
+                continue;
+            };
+            if access.is_empty() {
+                write!(w, " none")?;
+            }
+            for id in access.shared_resources() {
+                write!(w, " read({})", name(id))?;
+            }
+            for id in access.exclusive_resources() {
+                write!(w, " write({})", name(id))?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
 }
 
 impl Default for Schedule {
@@ -746,6 +1063,17 @@ impl Default for Schedule {
 #[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Debug)]
 pub struct SystemId(usize);
 
+/// Information about a phase of a [`Schedule`].
+#[derive(Clone, Debug)]
+pub struct PhaseInfo {
+    pub label: SystemPhaseId,
+    /// The phases, this phase directly depends on.
+    pub dependencies: Vec<SystemPhaseId>,
+    /// The systems in this phase.
+    pub systems: Vec<SystemId>,
+    pub is_sync_point: bool,
+}
+
 fn insert_sorted(vec: &mut Vec<usize>, value: usize) {
     if let Err(pos) = vec.binary_search(&value) {
         vec.insert(pos, value);
@@ -753,6 +1081,7 @@ fn insert_sorted(vec: &mut Vec<usize>, value: usize) {
 }
 
 pub struct SystemEntryBuilder<'l> {
+    system: &'l mut SystemDescriptor,
     graph: &'l mut DependencyGraph,
     id: SystemId,
     dependency_node: usize,
@@ -772,6 +1101,12 @@ impl SystemEntryBuilder<'_> {
         }
         &mut self.graph.nodes[self.dependency_node]
     }
+    /// Overrides the name of the system, that is used for profiling.
+    #[inline]
+    pub fn with_name(&mut self, name: impl Into<Cow<'static, str>>) -> &mut Self {
+        self.system.set_name(name);
+        self
+    }
     #[inline]
     pub fn into_phase(&mut self, label: impl SystemPhase) -> &mut Self {
         // will be delayed until drop
@@ -802,6 +1137,9 @@ impl SystemEntryBuilder<'_> {
 
 impl Drop for SystemEntryBuilder<'_> {
     fn drop(&mut self) {
+        if self.phase != UndefinedSystemPhase::Undefined.as_label() {
+            self.system.phase = self.phase.as_str();
+        }
         if self.dependency_node != !0 {
             let parent = self.graph.insert_phase(self.phase);
             parent.sub_nodes.insert(self.dependency_node);
@@ -903,8 +1241,9 @@ impl ExclusiveSystem for ExclusiveSystemSchedule {
         self.0.init(resources)
     }
     #[inline]
-    fn run(&mut self, resources: &mut Resources, _args: ()) {
-        self.0.run(resources)
+    fn run(&mut self, resources: &mut Resources, _args: ()) -> Result<(), SystemError> {
+        self.0.run(resources);
+        Ok(())
     }
 }
 
@@ -924,8 +1263,15 @@ unsafe impl System for ConcurrentSystemSchedule {
     }
 
     #[inline]
-    fn run(&mut self, resources: &Resources, _args: ()) {
-        self.0.shared_executor(resources).run()
+    fn run(&mut self, resources: &Resources, _args: ()) -> Result<(), SystemError> {
+        let deterministic = self.0.deterministic;
+        let mut executor = self.0.shared_executor(resources);
+        if deterministic {
+            executor.run_local();
+        } else {
+            executor.run();
+        }
+        Ok(())
     }
 
     #[inline]
@@ -964,7 +1310,105 @@ impl Resources {
     pub fn run<Marker>(&mut self, sys: impl IntoSystemDescriptor<Marker>) {
         let mut d = sys.into_system_descriptor();
         d.init(self);
-        d.run_exclusive(self);
+        if let Err(error) = d.run_exclusive(self) {
+            SystemErrorPolicy::Panic.handle(self, error);
+        }
+    }
+
+    /// Runs the schedule with the given label from the [`Schedules`] resource.
+    ///
+    /// Returns `false`, when there is no schedule with this label.
+    pub fn run_schedule(&mut self, label: impl ScheduleLabel) -> bool {
+        let label = label.as_label();
+        let Some(mut schedule) = self.get_mut::<Schedules>().and_then(|s| s.remove(label)) else {
+            return false;
+        };
+        schedule.run(self);
+        self.get_mut::<Schedules>()
+            .expect("schedules")
+            .insert(label, schedule);
+        true
+    }
+}
+
+/// A collection of additional schedules identified by a [`ScheduleLabel`]
+/// (e.g. a fixed-update schedule).
+///
+/// This is available as a resource (like the main [`Schedule`]). The
+/// schedules are run with [`Resources::run_schedule`] or from a system of
+/// another schedule with [`RunSchedule`].
+#[derive(Default, Debug)]
+pub struct Schedules(HashMap<ScheduleLabelId, Schedule>);
+
+impl Schedules {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn contains(&self, label: impl ScheduleLabel) -> bool {
+        self.0.contains_key(&label.as_label())
+    }
+
+    #[inline]
+    pub fn get(&self, label: impl ScheduleLabel) -> Option<&Schedule> {
+        self.0.get(&label.as_label())
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, label: impl ScheduleLabel) -> Option<&mut Schedule> {
+        self.0.get_mut(&label.as_label())
+    }
+
+    /// Returns the schedule with the given label, or creates a new empty
+    /// schedule.
+    #[inline]
+    pub fn get_or_insert(&mut self, label: impl ScheduleLabel) -> &mut Schedule {
+        self.0.entry(label.as_label()).or_default()
+    }
+
+    #[inline]
+    pub fn insert(&mut self, label: impl ScheduleLabel, schedule: Schedule) -> Option<Schedule> {
+        self.0.insert(label.as_label(), schedule)
+    }
+
+    #[inline]
+    pub fn remove(&mut self, label: impl ScheduleLabel) -> Option<Schedule> {
+        self.0.remove(&label.as_label())
+    }
+
+    #[inline]
+    pub fn labels(&self) -> impl Iterator<Item = ScheduleLabelId> + '_ {
+        self.0.keys().copied()
+    }
+}
+
+/// An exclusive system, that runs the schedule with the given label from the
+/// [`Schedules`] resource.
+pub struct RunSchedule(ScheduleLabelId);
+
+impl RunSchedule {
+    #[inline]
+    pub fn new(label: impl ScheduleLabel) -> Self {
+        Self(label.as_label())
+    }
+}
+
+impl ExclusiveSystem for RunSchedule {
+    #[inline]
+    fn init(&mut self, _resources: &mut Resources) {}
+
+    fn run(&mut self, resources: &mut Resources, _args: ()) -> Result<(), SystemError> {
+        if resources.run_schedule(self.0) {
+            Ok(())
+        } else {
+            Err(SystemError::new(format!("schedule {:?} not found", self.0)))
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        self.0.as_str()
     }
 }
 
@@ -972,6 +1416,8 @@ impl Resources {
 pub struct ScheduleExecution<'s> {
     systems: &'s mut [SystemDescriptor],
     ordered_task_groups: &'s [TaskGroup],
+    error_policy: SystemErrorPolicy,
+    watchdog: Option<&'s Arc<WatchdogState>>,
     resources: &'s mut Resources,
     #[cfg(not(target_os = "unknown"))]
     // Is one item longer than task_group.len().
@@ -983,11 +1429,15 @@ pub struct ScheduleExecution<'s> {
 pub struct SharedScheduleExecution<'s> {
     systems: &'s mut [SystemDescriptor],
     concurrent_tasks: &'s [(usize, usize)],
+    error_policy: SystemErrorPolicy,
+    watchdog: Option<&'s Arc<WatchdogState>>,
     resources: &'s Resources,
     #[cfg(not(target_os = "unknown"))]
     // Is one item longer than task_group.len().
     // The task `i` of a task_group will wait on WaitGroup [task_group.len() - current_sub_entry]!
     tasks_rev: Vec<WaitGroup>,
+    // errors of systems running on the thread-pool; handled after the group has finished
+    errors: Mutex<Vec<SystemError>>,
 }
 
 #[cfg(not(target_os = "unknown"))]
@@ -1062,20 +1512,33 @@ pub mod threadpool {
 impl<'s> ScheduleExecution<'s> {
     /// Runs a single iteration of all active systems on the *current thread*.
     pub fn run_local(&mut self) {
+        let _span = schedule_span();
+        let _watchdog = self.watchdog.map(|w| w.begin_run());
         for group in self.ordered_task_groups {
             match group {
                 &TaskGroup::Exclusive(system_index) => {
-                    self.systems[system_index].run_exclusive(self.resources);
+                    let _watch = self.watchdog.map(|w| w.enter(system_index));
+                    let result = self.systems[system_index].run_exclusive(self.resources);
+                    self.handle_result(result);
                 }
                 TaskGroup::Concurrent(entries) => {
                     for &(system_index, _signal_task) in entries {
-                        self.systems[system_index].run_shared(self.resources);
+                        let _watch = self.watchdog.map(|w| w.enter(system_index));
+                        let result = self.systems[system_index].run_shared(self.resources);
+                        self.handle_result(result);
                     }
                 }
             }
         }
     }
 
+    #[inline]
+    fn handle_result(&self, result: Result<(), SystemError>) {
+        if let Err(error) = result {
+            self.error_policy.handle(self.resources, error);
+        }
+    }
+
     /// The current target does not support spawning threads.
     /// Therefore this is an alias to `run_local`
     #[cfg(target_os = "unknown")]
@@ -1091,17 +1554,24 @@ impl<'s> ScheduleExecution<'s> {
     #[cfg(not(target_os = "unknown"))]
     #[inline]
     pub fn run(&mut self) {
+        let _span = schedule_span();
+        let _watchdog = self.watchdog.map(|w| w.begin_run());
         for group in self.ordered_task_groups {
             match group {
                 &TaskGroup::Exclusive(system_index) => {
-                    self.systems[system_index].run_exclusive(self.resources);
+                    let _watch = self.watchdog.map(|w| w.enter(system_index));
+                    let result = self.systems[system_index].run_exclusive(self.resources);
+                    self.handle_result(result);
                 }
                 TaskGroup::Concurrent(entries) => {
                     let mut shared = SharedScheduleExecution {
                         systems: self.systems,
                         concurrent_tasks: entries,
+                        error_policy: self.error_policy,
+                        watchdog: self.watchdog,
                         resources: self.resources,
                         tasks_rev: std::mem::take(&mut self.tasks_rev),
+                        errors: Mutex::new(Vec::new()),
                     };
                     shared.run();
                     std::mem::swap(&mut self.tasks_rev, &mut shared.tasks_rev);
@@ -1114,8 +1584,12 @@ impl<'s> ScheduleExecution<'s> {
 impl<'s> SharedScheduleExecution<'s> {
     /// Runs a single iteration of all active systems on the *current thread*.
     pub fn run_local(&mut self) {
+        let _watchdog = self.watchdog.map(|w| w.begin_run());
         for &(system_index, _signal_task) in self.concurrent_tasks {
-            self.systems[system_index].run_shared(self.resources);
+            let _watch = self.watchdog.map(|w| w.enter(system_index));
+            if let Err(error) = self.systems[system_index].run_shared(self.resources) {
+                self.error_policy.handle(self.resources, error);
+            }
         }
     }
 
@@ -1134,6 +1608,7 @@ impl<'s> SharedScheduleExecution<'s> {
     #[cfg(not(target_os = "unknown"))]
     #[inline]
     pub fn run(&mut self) {
+        let _watchdog = self.watchdog.map(|w| w.begin_run());
         self.tasks_rev
             .resize_with(self.concurrent_tasks.len() + 1, Default::default);
         for &(system_index, signal_task) in self.concurrent_tasks {
@@ -1145,9 +1620,8 @@ impl<'s> SharedScheduleExecution<'s> {
             };
             let signal_wait_group = self.tasks_rev[signal_wait_group_index].clone();
 
-            let SystemVariant::Concurrent(system, _) =
-                &mut self.systems[system_index].system_variant
-            else {
+            let descriptor = &mut self.systems[system_index];
+            let SystemVariant::Concurrent(system, _) = &mut descriptor.system_variant else {
                 unreachable!("expected a concurrent system!");
             };
 
@@ -1158,27 +1632,51 @@ impl<'s> SharedScheduleExecution<'s> {
             //
             // This also has multiple references into self.systems, but the one entry is
             // accessed by at most one loop-iteration / spawned-thread
-            let (resources, system) = unsafe {
+            let (resources, system, name, errors) = unsafe {
                 let resources: *const _ = self.resources;
                 let system: *mut _ = system;
-                (&*resources, &mut *system)
+                let name: *const str = &*descriptor.name;
+                let errors: *const _ = &self.errors;
+                (&*resources, &mut *system, &*name, &*errors)
             };
+            let phase = descriptor.phase;
 
             if system.is_send() {
                 let resources = resources.as_send(); // shared borrow
-                self::threadpool::spawn(move || {
+                let watchdog = self.watchdog.cloned();
+                threadpool::spawn(move || {
                     current_wait_group.wait();
-                    system.run_send(resources, ());
+                    let watch = watchdog.as_ref().map(|w| w.enter(system_index));
+                    let _span = system_span(name, phase);
+                    if let Err(error) = system.run_send(resources, ()) {
+                        errors
+                            .lock()
+                            .unwrap()
+                            .push(error.with_system(name.to_owned()));
+                    }
+                    drop(watch);
                     drop(signal_wait_group);
                 });
             } else {
                 // execute local
                 current_wait_group.wait();
-                system.run(self.resources, ());
+                let watch = self.watchdog.map(|w| w.enter(system_index));
+                let _span = system_span(name, phase);
+                if let Err(error) = system.run(self.resources, ()) {
+                    errors
+                        .lock()
+                        .unwrap()
+                        .push(error.with_system(name.to_owned()));
+                }
+                drop(watch);
                 drop(signal_wait_group);
             }
         }
         self.join();
+
+        for error in self.errors.get_mut().unwrap().drain(..) {
+            self.error_policy.handle(self.resources, error);
+        }
     }
 
     #[cfg(not(target_os = "unknown"))]
@@ -1218,11 +1716,41 @@ macro_rules! dump_schedule_dot {
 }
 
 #[cfg(test)]
-mod tests {
-    use std::sync::{atomic::AtomicUsize, Arc};
+pub(crate) mod tests {
+    use std::sync::{atomic::AtomicUsize, Arc, Mutex};
 
     use super::*;
-    use crate::system::{ExclusiveSystem, System};
+    use crate::system::{system_fn::ExclusiveResources, ExclusiveSystem, System};
+
+    /// Records the order in which its systems are run.
+    #[derive(Clone, Default)]
+    pub struct OrderLog(Arc<Mutex<Vec<&'static str>>>);
+
+    impl OrderLog {
+        /// A system that appends `name` to the log.
+        pub fn system(&self, name: &'static str) -> impl Fn() + Send + Sync + 'static {
+            let order = self.0.clone();
+            move || order.lock().unwrap().push(name)
+        }
+
+        /// An exclusive system that appends `name` to the log.
+        pub fn exclusive_system(
+            &self,
+            name: &'static str,
+        ) -> impl Fn(ExclusiveResources<'_>) + Send + Sync + 'static {
+            let order = self.0.clone();
+            move |_| order.lock().unwrap().push(name)
+        }
+
+        /// Takes the names logged so far.
+        pub fn take(&self) -> Vec<&'static str> {
+            std::mem::take(&mut *self.0.lock().unwrap())
+        }
+    }
+
+    pub fn order_log() -> OrderLog {
+        OrderLog::default()
+    }
 
     #[test]
     fn test_schedule() {
@@ -1231,8 +1759,9 @@ mod tests {
         let counter = Arc::new(AtomicUsize::new(0));
         unsafe impl System for Sys {
             fn init(&mut self, _resources: &mut Resources) {}
-            fn run(&mut self, _arg: &Resources, _arg2: ()) {
+            fn run(&mut self, _arg: &Resources, _arg2: ()) -> Result<(), SystemError> {
                 self.0.fetch_add(1, std::sync::atomic::Ordering::AcqRel);
+                Ok(())
             }
             fn is_send(&self) -> bool {
                 true
@@ -1242,8 +1771,9 @@ mod tests {
         struct ExSys;
         impl ExclusiveSystem for ExSys {
             fn init(&mut self, _resources: &mut Resources) {}
-            fn run(&mut self, arg: &mut Resources, _arg2: ()) {
+            fn run(&mut self, arg: &mut Resources, _arg2: ()) -> Result<(), SystemError> {
                 arg.insert(A);
+                Ok(())
             }
         }
 
@@ -1263,4 +1793,379 @@ mod tests {
         assert_eq!(1, counter.load(std::sync::atomic::Ordering::Acquire));
         assert!(resources.get_mut::<A>().is_some());
     }
+
+    #[test]
+    fn test_concurrent_groups_after_exclusive() {
+        struct R(usize);
+        struct S(usize);
+
+        let order = order_log();
+
+        let mut schedule = Schedule::new();
+        schedule
+            .add_system(|r: &mut R| r.0 += 1)
+            .into_phase(CoreSystemPhase::First);
+        schedule
+            .add_system(|_: ExclusiveResources<'_>| {})
+            .into_phase(CoreSystemPhase::First);
+        schedule
+            .add_system(|r: &R, s: &mut S| s.0 += r.0)
+            .into_phase(CoreSystemPhase::Update);
+        schedule
+            .add_system(order.system("update"))
+            .into_phase(CoreSystemPhase::Update);
+        schedule
+            .add_system(|s: &S| assert_eq!(1, s.0))
+            .into_phase(CoreSystemPhase::Last);
+        schedule
+            .add_system(order.system("last"))
+            .into_phase(CoreSystemPhase::Last);
+
+        let mut resources = Resources::new();
+        resources.insert(R(0));
+        resources.insert(S(0));
+        schedule.run(&mut resources);
+
+        assert_eq!(vec!["update", "last"], order.take());
+        assert_eq!(1, resources.get_mut::<S>().unwrap().0);
+    }
+    #[test]
+    fn test_sync_points() {
+        crate::define_label_enum! {
+            enum TestPhase: SystemPhase {
+                Late,
+                Sync,
+                Later,
+            }
+        }
+
+        let order = order_log();
+
+        let mut schedule = Schedule::new();
+        schedule.add_phase_chain([
+            CoreSystemPhase::ApplyDeferred.as_label(),
+            TestPhase::Late.as_label(),
+            TestPhase::Sync.as_label(),
+            TestPhase::Later.as_label(),
+        ]);
+        schedule.add_sync_point(TestPhase::Sync);
+        schedule
+            .add_system(order.exclusive_system("update"))
+            .into_phase(CoreSystemPhase::Update);
+        schedule
+            .add_system(order.system("late"))
+            .into_phase(TestPhase::Late);
+        schedule
+            .add_system(order.exclusive_system("late_exclusive"))
+            .into_phase(TestPhase::Late);
+        schedule
+            .add_system(order.system("later"))
+            .into_phase(TestPhase::Later);
+        schedule
+            .add_system(order.exclusive_system("later_exclusive"))
+            .into_phase(TestPhase::Later);
+
+        let mut resources = Resources::new();
+        schedule.run(&mut resources);
+
+        assert_eq!(
+            vec![
+                "update",
+                "late",
+                "late_exclusive",
+                "later",
+                "later_exclusive"
+            ],
+            order.take()
+        );
+    }
+
+    #[test]
+    fn test_system_names() {
+        fn update_positions() {}
+        fn update_velocities() {}
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(update_positions);
+        schedule
+            .add_system(update_velocities)
+            .with_name("physics::velocities");
+        assert_eq!("update_positions", schedule.systems[0].name());
+        assert_eq!("physics::velocities", schedule.systems[1].name());
+        assert!(schedule.systems[0]
+            .type_name()
+            .ends_with("::update_positions"));
+
+        schedule.add_system(|| {}).into_phase(CoreSystemPhase::Last);
+        assert_eq!("test_system_names::{{closure}}", schedule.systems[2].name());
+        assert_eq!("", schedule.systems[0].phase());
+        assert_eq!("CoreSystemPhase::Last", schedule.systems[2].phase());
+    }
+
+    #[test]
+    fn test_system_attribute() {
+        #[derive(Default)]
+        struct Order(Vec<&'static str>);
+
+        #[derive(Default)]
+        struct Settings;
+
+        #[crate::system(phase = CoreSystemPhase::Last)]
+        fn last(order: &mut Order) {
+            order.0.push("last");
+        }
+
+        #[crate::system(
+            phase = CoreSystemPhase::Update,
+            after = CoreSystemPhase::First,
+            before = CoreSystemPhase::ApplyDeferred,
+            name = "physics::update"
+        )]
+        fn update(order: &mut Order) {
+            order.0.push("update");
+        }
+
+        #[crate::system(phase = CoreSystemPhase::First, run_if_changed = Settings)]
+        fn apply_settings(order: &mut Order, _settings: &Settings) {
+            order.0.push("apply_settings");
+        }
+
+        let mut resources = Resources::new();
+        resources.init::<Order>();
+        resources.init::<Settings>();
+        let mut schedule = Schedule::new();
+        schedule.set_deterministic(true);
+        install_last(&mut schedule);
+        let update_id = install_update(&mut schedule);
+        install_apply_settings(&mut schedule);
+        assert_eq!(
+            "physics::update",
+            schedule.system(update_id).unwrap().name()
+        );
+        assert_eq!(
+            "CoreSystemPhase::Update",
+            schedule.system(update_id).unwrap().phase()
+        );
+
+        schedule.run(&mut resources);
+        schedule.run(&mut resources);
+        assert_eq!(
+            vec!["apply_settings", "update", "last", "update", "last"],
+            resources.get_mut::<Order>().unwrap().0
+        );
+    }
+
+    #[test]
+    fn test_system_attribute_local() {
+        use crate::local::Local;
+
+        #[derive(Default)]
+        struct Order(Vec<usize>);
+
+        #[crate::system(local(counter = 10), run_if_changed = Order)]
+        fn count(order: &mut Order, mut counter: Local<'_, usize>, mut unseeded: Local<'_, usize>) {
+            *counter += 1;
+            *unseeded += 1;
+            order.0.push(*counter + *unseeded * 100);
+        }
+
+        let mut resources = Resources::new();
+        resources.init::<Order>();
+        let mut schedule = Schedule::new();
+        let id = install_count(&mut schedule);
+        assert_eq!("count", schedule.system(id).unwrap().name());
+        schedule.run(&mut resources);
+        schedule.run(&mut resources);
+        assert_eq!(vec![111, 212], resources.get_mut::<Order>().unwrap().0);
+    }
+
+    #[test]
+    fn test_deterministic() {
+        use std::thread;
+
+        let order = order_log();
+        let main_thread = thread::current().id();
+
+        let mut schedule = Schedule::new();
+        schedule.set_deterministic(true);
+        for name in ["a", "b", "c", "d"] {
+            let log = order.system(name);
+            schedule.add_system(move || {
+                assert_eq!(main_thread, thread::current().id());
+                log();
+            });
+        }
+        let mut resources = Resources::new();
+        schedule.run(&mut resources);
+        let first_run = order.take();
+        // independent systems run in the order of their registration
+        assert_eq!(vec!["a", "b", "c", "d"], first_run);
+
+        for _ in 0..10 {
+            schedule.run(&mut resources);
+            assert_eq!(first_run, order.take());
+        }
+    }
+
+    #[test]
+    fn test_labeled_schedules() {
+        crate::define_label_enum! {
+            enum TestSchedule: ScheduleLabel {
+                FixedUpdate,
+                Missing,
+            }
+        }
+
+        let order = order_log();
+
+        let mut resources = Resources::new();
+        let schedules = resources.get_mut::<Schedules>().unwrap();
+        schedules
+            .get_or_insert(TestSchedule::FixedUpdate)
+            .add_system(order.system("fixed"));
+        assert!(!schedules.contains(TestSchedule::Missing));
+
+        let mut schedule = resources.remove::<Schedule>().unwrap();
+        schedule
+            .add_system(order.system("first"))
+            .into_phase(CoreSystemPhase::First);
+        schedule
+            .add_system(RunSchedule::new(TestSchedule::FixedUpdate))
+            .into_phase(CoreSystemPhase::Update);
+        schedule
+            .add_system(order.system("last"))
+            .into_phase(CoreSystemPhase::Last);
+        schedule.run(&mut resources);
+        resources.insert_again(schedule);
+
+        assert_eq!(vec!["first", "fixed", "last"], order.take());
+        assert!(resources.run_schedule(TestSchedule::FixedUpdate));
+        assert!(!resources.run_schedule(TestSchedule::Missing));
+        assert_eq!(vec!["fixed"], order.take());
+    }
+
+    #[derive(Default)]
+    struct Counter(usize);
+
+    #[test]
+    fn test_system_access() {
+        fn read_counter(_counter: &Counter) {}
+        fn write_counter(counter: &mut Counter) {
+            counter.0 += 1;
+        }
+
+        let mut resources = Resources::new();
+        let counter_id = resources.init::<Counter>();
+        let mut schedule = Schedule::new();
+        let read_id = schedule.add_system(read_counter).id();
+        let write_id = schedule
+            .add_system(write_counter)
+            .into_phase(CoreSystemPhase::First)
+            .id();
+        schedule.init(&mut resources);
+
+        let access = schedule.system(read_id).unwrap().access().unwrap();
+        assert!(access.is_shared(counter_id));
+        assert!(!access.is_exclusive(counter_id));
+        let access = schedule.system(write_id).unwrap().access().unwrap();
+        assert!(!access.is_shared(counter_id));
+        assert!(access.is_exclusive(counter_id));
+        assert_eq!(
+            vec![counter_id.untyped()],
+            access.exclusive_resources().collect::<Vec<_>>()
+        );
+        assert_eq!(2, schedule.systems().count());
+
+        let mut table = Vec::new();
+        schedule.write_access(&mut table, &resources).unwrap();
+        let table = String::from_utf8(table).unwrap();
+        let lines: Vec<_> = table.lines().collect();
+        assert!(lines[0].starts_with("#0 read_counter: read("), "{table}");
+        assert!(lines[1].starts_with("#1 write_counter: write("), "{table}");
+        assert!(lines[1].ends_with("::Counter)"), "{table}");
+    }
+
+    #[test]
+    #[should_panic(expected = "system `write_a` (#0) writes")]
+    fn test_resource_conflict_explanation() {
+        fn write_a(counter: &mut Counter) {
+            counter.0 += 1;
+        }
+        fn write_b(counter: &mut Counter) {
+            counter.0 += 2;
+        }
+
+        let mut resources = Resources::new();
+        resources.init::<Counter>();
+        let mut schedule = Schedule::new();
+        schedule.add_system(write_a);
+        schedule.add_system(write_b);
+        schedule.init(&mut resources);
+    }
+
+    #[test]
+    fn test_replace_system() {
+        let mut resources = Resources::new();
+        resources.init::<Counter>();
+        let mut schedule = Schedule::new();
+        let id = schedule
+            .add_system(|counter: &mut Counter| counter.0 += 1)
+            .with_name("count")
+            .id();
+        schedule.run(&mut resources);
+        assert_eq!(Some(id), schedule.find_system("count"));
+
+        let previous = schedule.replace_system(id, |counter: &mut Counter| counter.0 += 10);
+        assert_eq!("count", previous.name());
+        assert_eq!("count", schedule.system(id).unwrap().name());
+        schedule.run(&mut resources);
+        assert_eq!(11, resources.get_mut::<Counter>().unwrap().0);
+    }
+
+    #[test]
+    fn test_insert_phase_between() {
+        crate::define_label_enum! {
+            enum TestPhase: SystemPhase {
+                PrePhysics,
+            }
+        }
+
+        let order = order_log();
+
+        let mut resources = Resources::new();
+        let mut schedule = Schedule::new();
+        schedule
+            .add_system(order.system("update"))
+            .into_phase(CoreSystemPhase::Update);
+        schedule
+            .add_system(order.system("last"))
+            .into_phase(CoreSystemPhase::Last);
+        schedule.run(&mut resources);
+        assert_eq!(vec!["update", "last"], order.take());
+        assert!(!schedule.contains_phase(TestPhase::PrePhysics));
+
+        schedule.insert_phase_between(
+            CoreSystemPhase::Update,
+            TestPhase::PrePhysics,
+            CoreSystemPhase::ApplyDeferred,
+        );
+        let id = schedule
+            .add_system(order.system("pre_physics"))
+            .into_phase(TestPhase::PrePhysics)
+            .id();
+        schedule.run(&mut resources);
+        assert_eq!(vec!["update", "pre_physics", "last"], order.take());
+
+        assert!(schedule.contains_phase(TestPhase::PrePhysics));
+        assert!(schedule
+            .phases()
+            .any(|p| p == TestPhase::PrePhysics.as_label()));
+        let info = schedule.phase_info(TestPhase::PrePhysics).unwrap();
+        assert_eq!(vec![CoreSystemPhase::Update.as_label()], info.dependencies);
+        assert_eq!(vec![id], info.systems);
+        assert!(!info.is_sync_point);
+        let info = schedule.phase_info(CoreSystemPhase::ApplyDeferred).unwrap();
+        assert!(info.is_sync_point);
+        assert_eq!(2, info.dependencies.len());
+    }
 }
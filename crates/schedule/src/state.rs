@@ -0,0 +1,317 @@
+//! States of an application (e.g. menu, loading, gameplay), with schedules
+//! that are run when a state is entered or left.
+//!
+//! ```
+//! use pulz_schedule::{prelude::*, state::{in_state, State, StateSchedules}};
+//!
+//! #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+//! enum GameState {
+//!     Menu,
+//!     Playing,
+//! }
+//!
+//! fn spawn_level() {
+//!     // ...
+//! }
+//!
+//! fn move_player() {
+//!     // ...
+//! }
+//!
+//! let mut resources = Resources::new();
+//! State::install_into(&mut resources, GameState::Menu);
+//! resources
+//!     .get_mut::<StateSchedules<GameState>>()
+//!     .unwrap()
+//!     .on_enter(GameState::Playing)
+//!     .add_system(spawn_level);
+//! let mut schedule = resources.remove::<Schedule>().unwrap();
+//! schedule.add_system(in_state(GameState::Playing, move_player));
+//!
+//! resources.get_mut::<State<GameState>>().unwrap().set(GameState::Playing);
+//! schedule.run(&mut resources);
+//! assert_eq!(GameState::Playing, resources.get_mut::<State<GameState>>().unwrap().get());
+//! ```
+
+use std::{fmt::Debug, hash::Hash};
+
+use crate::{
+    label::CoreSystemPhase,
+    resource::{ResourceAccess, ResourceId, Resources},
+    schedule::Schedule,
+    system::{error::SystemError, system_fn::ExclusiveResources, IntoSystem, System},
+};
+
+type HashMap<K, V> = std::collections::HashMap<K, V, fnv::FnvBuildHasher>;
+
+/// Types that can be used as a state (usually a field-less enum).
+pub trait States: Copy + Eq + Hash + Debug + Send + Sync + 'static {}
+
+impl<S> States for S where S: Copy + Eq + Hash + Debug + Send + Sync + 'static {}
+
+/// A resource holding the current state of type `S`.
+///
+/// Transitions requested with [`State::set`] are applied by a system in
+/// [`CoreSystemPhase::First`], that runs the [`StateSchedules`] of the
+/// transition.
+pub struct State<S> {
+    current: S,
+    next: Option<S>,
+    entered: bool,
+}
+
+impl<S: States> State<S> {
+    /// Inserts the `State<S>` (with the given initial state) and the
+    /// [`StateSchedules<S>`] resources, and adds the system, that applies
+    /// the transitions, to the schedule.
+    ///
+    /// The [`StateSchedules::on_enter`] schedule of the initial state is run
+    /// on the first run of the schedule.
+    pub fn install_into(resources: &mut Resources, initial: S) {
+        if resources.try_init_unsend::<StateSchedules<S>>().is_ok() {
+            resources.insert(Self {
+                current: initial,
+                next: None,
+                entered: false,
+            });
+            let mut schedule = resources.borrow_res_mut::<Schedule>().unwrap();
+            schedule
+                .add_system(apply_state_transitions::<S>)
+                .into_phase(CoreSystemPhase::First);
+        }
+    }
+
+    /// The current state.
+    #[inline]
+    pub fn get(&self) -> S {
+        self.current
+    }
+
+    /// The state, that will be entered on the next transition.
+    #[inline]
+    pub fn next(&self) -> Option<S> {
+        self.next
+    }
+
+    /// Requests a transition into the given state.
+    ///
+    /// The transition is applied at the beginning of the next run of the
+    /// schedule (in [`CoreSystemPhase::First`]). Requesting the current
+    /// state again does nothing.
+    #[inline]
+    pub fn set(&mut self, next: S) {
+        self.next = Some(next);
+    }
+}
+
+/// The schedules, that are run on transitions of the state `S`.
+///
+/// A transition from `a` to `b` runs `on_exit(a)`, `on_transition(a, b)` and
+/// `on_enter(b)` (in this order). The schedules can request another
+/// transition, which is applied right after the current one.
+pub struct StateSchedules<S> {
+    on_enter: HashMap<S, Schedule>,
+    on_exit: HashMap<S, Schedule>,
+    on_transition: HashMap<(S, S), Schedule>,
+}
+
+impl<S> Default for StateSchedules<S> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            on_enter: HashMap::default(),
+            on_exit: HashMap::default(),
+            on_transition: HashMap::default(),
+        }
+    }
+}
+
+impl<S: States> StateSchedules<S> {
+    /// The schedule, that is run when `state` is entered.
+    pub fn on_enter(&mut self, state: S) -> &mut Schedule {
+        self.on_enter.entry(state).or_default()
+    }
+
+    /// The schedule, that is run when `state` is left.
+    pub fn on_exit(&mut self, state: S) -> &mut Schedule {
+        self.on_exit.entry(state).or_default()
+    }
+
+    /// The schedule, that is run on the transition from `from` to `to`.
+    pub fn on_transition(&mut self, from: S, to: S) -> &mut Schedule {
+        self.on_transition.entry((from, to)).or_default()
+    }
+}
+
+fn run_state_schedule<K: Eq + Hash>(
+    resources: &mut Resources,
+    schedules: &mut HashMap<K, Schedule>,
+    key: K,
+) {
+    if let Some(schedule) = schedules.get_mut(&key) {
+        schedule.run(resources);
+    }
+}
+
+fn apply_state_transitions<S: States>(mut res: ExclusiveResources<'_>) {
+    let Some(mut schedules) = res.remove::<StateSchedules<S>>() else {
+        return;
+    };
+    let state = res.get_mut::<State<S>>().expect("state");
+    if !state.entered {
+        state.entered = true;
+        let current = state.current;
+        run_state_schedule(&mut res, &mut schedules.on_enter, current);
+    }
+    loop {
+        let state = res.get_mut::<State<S>>().expect("state");
+        let Some(next) = state.next.take() else {
+            break;
+        };
+        let current = state.current;
+        if next == current {
+            continue;
+        }
+        run_state_schedule(&mut res, &mut schedules.on_exit, current);
+        run_state_schedule(&mut res, &mut schedules.on_transition, (current, next));
+        res.get_mut::<State<S>>().expect("state").current = next;
+        run_state_schedule(&mut res, &mut schedules.on_enter, next);
+    }
+    res.insert_again(schedules);
+}
+
+/// Wraps a system, so it is only run while the state `S` is `state`.
+pub fn in_state<S, Sys, Marker>(state: S, system: Sys) -> InState<S, Sys::System>
+where
+    S: States,
+    Sys: IntoSystem<(), Marker>,
+{
+    InState {
+        system: system.into_system(),
+        state,
+        id: None,
+    }
+}
+
+/// A system, that is only run in a specific state (see [`in_state`]).
+pub struct InState<S, Sys> {
+    system: Sys,
+    state: S,
+    id: Option<ResourceId<State<S>>>,
+}
+
+// SAFETY: the state resource is accessed (shared) in addition to the wrapped
+// system, and added to the access
+unsafe impl<S, Sys> System for InState<S, Sys>
+where
+    S: States,
+    Sys: System,
+{
+    fn init(&mut self, resources: &mut Resources) {
+        self.id = Some(resources.expect_id::<State<S>>());
+        self.system.init(resources);
+    }
+
+    fn run(&mut self, resources: &Resources, args: ()) -> Result<(), SystemError> {
+        let id = self.id.expect("not initialized");
+        let current = resources.borrow_res_id(id).expect("state").current;
+        if current != self.state {
+            return Ok(());
+        }
+        self.system.run(resources, args)
+    }
+
+    #[inline]
+    fn is_send(&self) -> bool {
+        self.system.is_send()
+    }
+
+    #[inline]
+    fn update_access(&self, resources: &Resources, access: &mut ResourceAccess) {
+        if let Some(id) = self.id {
+            access.add_shared_checked(id);
+        }
+        self.system.update_access(resources, access)
+    }
+
+    #[inline]
+    fn type_name(&self) -> &'static str {
+        self.system.type_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::tests::order_log;
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    enum GameState {
+        Loading,
+        Menu,
+        Playing,
+    }
+
+    #[test]
+    fn test_state_transitions() {
+        let order = order_log();
+
+        let mut resources = Resources::new();
+        State::install_into(&mut resources, GameState::Loading);
+        {
+            let schedules = resources.get_mut::<StateSchedules<GameState>>().unwrap();
+            schedules
+                .on_enter(GameState::Loading)
+                .add_system(|state: &mut State<GameState>| {
+                    // loading is done immediately
+                    state.set(GameState::Menu);
+                });
+            schedules
+                .on_enter(GameState::Menu)
+                .add_system(order.system("enter_menu"));
+            schedules
+                .on_exit(GameState::Menu)
+                .add_system(order.system("exit_menu"));
+            schedules
+                .on_transition(GameState::Menu, GameState::Playing)
+                .add_system(order.system("menu_to_playing"));
+            schedules
+                .on_enter(GameState::Playing)
+                .add_system(order.system("enter_playing"));
+        }
+        let mut schedule = resources.remove::<Schedule>().unwrap();
+        schedule.add_system(in_state(GameState::Playing, order.system("playing")));
+
+        schedule.run(&mut resources);
+        assert_eq!(
+            GameState::Menu,
+            resources.get_mut::<State<GameState>>().unwrap().get()
+        );
+        assert_eq!(vec!["enter_menu"], order.take());
+
+        // no transition
+        resources
+            .get_mut::<State<GameState>>()
+            .unwrap()
+            .set(GameState::Menu);
+        schedule.run(&mut resources);
+        assert!(order.take().is_empty());
+
+        resources
+            .get_mut::<State<GameState>>()
+            .unwrap()
+            .set(GameState::Playing);
+        schedule.run(&mut resources);
+        schedule.run(&mut resources);
+        assert_eq!(
+            vec![
+                "exit_menu",
+                "menu_to_playing",
+                "enter_playing",
+                "playing",
+                "playing"
+            ],
+            order.take()
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use std::{borrow::Cow, error::Error, fmt};
+
+use crate::{event::Events, resource::Resources};
+
+/// An error returned by a fallible system.
+///
+/// Systems can return `Result<(), E>` for any error type `E`, that can be
+/// converted into a `SystemError`. How the error is handled, is defined by
+/// the [`SystemErrorPolicy`] of the schedule.
+pub struct SystemError {
+    system: Option<Cow<'static, str>>,
+    error: Box<dyn Error + Send + Sync + 'static>,
+}
+
+impl SystemError {
+    /// Creates a new error from an error or a message.
+    pub fn new(error: impl Into<Box<dyn Error + Send + Sync + 'static>>) -> Self {
+        Self {
+            system: None,
+            error: error.into(),
+        }
+    }
+
+    /// The name of the system that returned this error (see
+    /// [`SystemDescriptor::name`](super::SystemDescriptor::name)).
+    #[inline]
+    pub fn system(&self) -> Option<&str> {
+        self.system.as_deref()
+    }
+
+    #[inline]
+    pub(crate) fn with_system(mut self, system: impl Into<Cow<'static, str>>) -> Self {
+        if self.system.is_none() {
+            self.system = Some(system.into());
+        }
+        self
+    }
+
+    #[inline]
+    pub fn error(&self) -> &(dyn Error + Send + Sync + 'static) {
+        self.error.as_ref()
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Box<dyn Error + Send + Sync + 'static> {
+        self.error
+    }
+}
+
+impl<E> From<E> for SystemError
+where
+    E: Error + Send + Sync + 'static,
+{
+    #[inline]
+    fn from(error: E) -> Self {
+        Self::new(error)
+    }
+}
+
+impl fmt::Display for SystemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(system) = &self.system {
+            write!(f, "system `{system}` failed: {}", self.error)
+        } else {
+            fmt::Display::fmt(&self.error, f)
+        }
+    }
+}
+
+impl fmt::Debug for SystemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SystemError")
+            .field("system", &self.system)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+/// The return-type of a system function: either `()` or `Result<(), E>`.
+pub trait IntoSystemResult {
+    fn into_system_result(self) -> Result<(), SystemError>;
+}
+
+impl IntoSystemResult for () {
+    #[inline]
+    fn into_system_result(self) -> Result<(), SystemError> {
+        Ok(())
+    }
+}
+
+impl<E> IntoSystemResult for Result<(), E>
+where
+    E: Into<SystemError>,
+{
+    #[inline]
+    fn into_system_result(self) -> Result<(), SystemError> {
+        self.map_err(Into::into)
+    }
+}
+
+/// Defines how a schedule handles errors returned by its systems.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SystemErrorPolicy {
+    /// Panics with the error (default).
+    #[default]
+    Panic,
+    /// Logs the error and continues with the next systems.
+    Log,
+    /// Sends the error into the `Events<SystemError>` resource and continues
+    /// with the next systems.
+    ///
+    /// The events need to be installed with
+    /// `Events::<SystemError>::install_into`; otherwise the error is logged.
+    Event,
+}
+
+impl SystemErrorPolicy {
+    pub fn handle(self, resources: &Resources, error: SystemError) {
+        match self {
+            Self::Panic => panic!("{error}"),
+            Self::Log => log::error!("{error}"),
+            Self::Event => {
+                if let Some(mut events) = resources.borrow_res_mut::<Events<SystemError>>() {
+                    events.send(error);
+                } else {
+                    log::error!("{error}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use super::*;
+    use crate::{event::EventSubscriber, label::CoreSystemPhase, schedule::Schedule};
+
+    #[derive(Debug)]
+    struct SurfaceLost;
+
+    impl fmt::Display for SurfaceLost {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("surface lost")
+        }
+    }
+
+    impl Error for SurfaceLost {}
+
+    struct Counter(usize);
+
+    fn failing_system(counter: &mut Counter) -> Result<(), SurfaceLost> {
+        counter.0 += 1;
+        Err(SurfaceLost)
+    }
+
+    fn counting_system(counter: &mut Counter) {
+        counter.0 += 10;
+    }
+
+    fn build(policy: SystemErrorPolicy) -> (Resources, Schedule) {
+        let mut resources = Resources::new();
+        resources.insert(Counter(0));
+        Events::<SystemError>::install_into(&mut resources);
+        let mut schedule = Schedule::new();
+        schedule.set_error_policy(policy);
+        schedule
+            .add_system(failing_system)
+            .with_name("render")
+            .into_phase(CoreSystemPhase::Update);
+        schedule
+            .add_system(counting_system)
+            .into_phase(CoreSystemPhase::Last);
+        (resources, schedule)
+    }
+
+    #[test]
+    fn test_error_policy_event() {
+        let (mut resources, mut schedule) = build(SystemErrorPolicy::Event);
+        schedule.run(&mut resources);
+        assert_eq!(11, resources.borrow_res::<Counter>().unwrap().0);
+
+        fn check_errors(mut errors: EventSubscriber<'_, SystemError>) {
+            let errors: Vec<_> = errors.iter().collect();
+            assert_eq!(1, errors.len());
+            assert_eq!(Some("render"), errors[0].system());
+            assert_eq!("surface lost", errors[0].error().to_string());
+        }
+        resources.run(check_errors);
+    }
+
+    #[test]
+    fn test_error_policy_log() {
+        let (mut resources, mut schedule) = build(SystemErrorPolicy::Log);
+        schedule.run(&mut resources);
+        schedule.run(&mut resources);
+        assert_eq!(22, resources.borrow_res::<Counter>().unwrap().0);
+        assert!(resources
+            .borrow_res::<Events<SystemError>>()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "surface lost")]
+    fn test_error_policy_panic() {
+        let (mut resources, mut schedule) = build(SystemErrorPolicy::Panic);
+        schedule.run(&mut resources);
+    }
+}
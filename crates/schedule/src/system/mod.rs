@@ -1,6 +1,13 @@
-use crate::resource::{ResourceAccess, Resources, ResourcesSend};
+use std::borrow::Cow;
+
+use self::error::SystemError;
+use crate::{
+    profiling::{short_type_name, system_span},
+    resource::{ResourceAccess, Resources, ResourcesSend},
+};
 
 pub mod data;
+pub mod error;
 pub mod system_fn;
 
 /// # Safety
@@ -8,11 +15,11 @@ pub mod system_fn;
 /// The `is_send` method must not return `true`, when unsend resources are accessed!
 pub unsafe trait System<Args = ()>: Send + Sync {
     fn init(&mut self, resources: &mut Resources);
-    fn run(&mut self, resources: &Resources, args: Args);
+    fn run(&mut self, resources: &Resources, args: Args) -> Result<(), SystemError>;
 
     fn is_send(&self) -> bool;
 
-    fn run_send(&mut self, resources: &ResourcesSend, args: Args) {
+    fn run_send(&mut self, resources: &ResourcesSend, args: Args) -> Result<(), SystemError> {
         assert!(self.is_send(), "system is not send");
         // SAFETY: no unsend resources are accessed (defined by unsafe trait contract)
         unsafe { self.run(resources.as_unsend(), args) }
@@ -27,7 +34,7 @@ pub unsafe trait System<Args = ()>: Send + Sync {
 
 pub trait ExclusiveSystem<Args = ()> {
     fn init(&mut self, _resources: &mut Resources);
-    fn run(&mut self, resources: &mut Resources, args: Args);
+    fn run(&mut self, resources: &mut Resources, args: Args) -> Result<(), SystemError>;
 
     fn type_name(&self) -> &'static str {
         std::any::type_name::<Self>()
@@ -74,6 +81,8 @@ where
 
 pub struct SystemDescriptor {
     pub(crate) system_variant: SystemVariant,
+    pub(crate) name: Cow<'static, str>,
+    pub(crate) phase: &'static str,
     // TODO: add a mechanism, that tracks identity of resource-set
     is_initialized: bool,
 }
@@ -86,6 +95,8 @@ impl SystemDescriptor {
     {
         let system = s.into_system();
         Self {
+            name: short_type_name(system.type_name()).into(),
+            phase: "",
             system_variant: SystemVariant::Concurrent(Box::new(system), ResourceAccess::new()),
             is_initialized: false,
         }
@@ -98,6 +109,8 @@ impl SystemDescriptor {
     {
         let system = s.into_exclusive_system();
         Self {
+            name: short_type_name(system.type_name()).into(),
+            phase: "",
             system_variant: SystemVariant::Exclusive(Box::new(system)),
             is_initialized: false,
         }
@@ -128,8 +141,35 @@ impl SystemDescriptor {
         }
     }
 
+    /// The name of the system, that is used for profiling.
+    ///
+    /// Defaults to the type-name without module-paths (e.g. the name of the
+    /// system function).
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn set_name(&mut self, name: impl Into<Cow<'static, str>>) {
+        self.name = name.into();
+    }
+
+    /// The name of the phase of the system, that is used for profiling.
+    ///
+    /// Empty, when the system was not added to a phase.
+    #[inline]
+    pub fn phase(&self) -> &str {
+        self.phase
+    }
+
+    /// The resources accessed by this system.
+    ///
+    /// The access is computed when the system is initialized (and is empty
+    /// before). Exclusive systems have access to all resources and return
+    /// `None`.
     #[inline]
-    pub(crate) fn access(&self) -> Option<&ResourceAccess> {
+    pub fn access(&self) -> Option<&ResourceAccess> {
         match &self.system_variant {
             SystemVariant::Concurrent(_, a) => Some(a),
             SystemVariant::Exclusive(_) => None,
@@ -143,6 +183,8 @@ impl SystemDescriptor {
                 system_variant: SystemVariant::Exclusive(Box::new(ConcurrentAsExclusiveSystem(
                     system,
                 ))),
+                name: self.name,
+                phase: self.phase,
                 is_initialized: self.is_initialized,
             },
         }
@@ -164,28 +206,34 @@ impl SystemDescriptor {
         self.is_initialized = true;
     }
 
-    pub fn run_exclusive(&mut self, resources: &mut Resources) {
+    pub fn run_exclusive(&mut self, resources: &mut Resources) -> Result<(), SystemError> {
+        let _span = system_span(&self.name, self.phase);
         assert!(self.is_initialized);
         match self.system_variant {
             SystemVariant::Exclusive(ref mut system) => system.run(resources, ()),
             SystemVariant::Concurrent(ref mut system, _) => system.run(resources, ()),
         }
+        .map_err(|e| e.with_system(self.name.clone()))
     }
 
-    pub fn run_shared(&mut self, resources: &Resources) {
+    pub fn run_shared(&mut self, resources: &Resources) -> Result<(), SystemError> {
+        let _span = system_span(&self.name, self.phase);
         assert!(self.is_initialized);
         match self.system_variant {
             SystemVariant::Exclusive(_) => panic!("no exclusive access"),
             SystemVariant::Concurrent(ref mut system, _) => system.run(resources, ()),
         }
+        .map_err(|e| e.with_system(self.name.clone()))
     }
 
-    pub fn run_send(&mut self, resources: &ResourcesSend) {
+    pub fn run_send(&mut self, resources: &ResourcesSend) -> Result<(), SystemError> {
+        let _span = system_span(&self.name, self.phase);
         assert!(self.is_initialized && self.is_send());
         match self.system_variant {
             SystemVariant::Concurrent(ref mut system, _) => system.run_send(resources, ()),
             _ => panic!("exclusive systems are not `send`!"),
         }
+        .map_err(|e| e.with_system(self.name.clone()))
     }
 }
 
@@ -193,6 +241,8 @@ impl std::fmt::Debug for SystemDescriptor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut s = f.debug_struct("System");
         s.field("type", &self.type_name());
+        s.field("name", &self.name());
+        s.field("phase", &self.phase());
         s.field("exclusive", &self.is_exclusive());
         s.field("send", &self.is_send());
         s.finish()
@@ -213,12 +263,12 @@ where
     }
 
     #[inline]
-    fn run(&mut self, resources: &Resources, args: Args) {
+    fn run(&mut self, resources: &Resources, args: Args) -> Result<(), SystemError> {
         self.as_mut().run(resources, args)
     }
 
     #[inline]
-    fn run_send(&mut self, resources: &ResourcesSend, args: Args) {
+    fn run_send(&mut self, resources: &ResourcesSend, args: Args) -> Result<(), SystemError> {
         self.as_mut().run_send(resources, args)
     }
 
@@ -248,7 +298,7 @@ where
     }
 
     #[inline]
-    fn run(&mut self, resources: &mut Resources, args: Args) {
+    fn run(&mut self, resources: &mut Resources, args: Args) -> Result<(), SystemError> {
         self.as_mut().run(resources, args)
     }
 
@@ -268,7 +318,7 @@ where
         self.0.init(resources)
     }
     #[inline]
-    fn run(&mut self, resources: &mut Resources, args: Args) {
+    fn run(&mut self, resources: &mut Resources, args: Args) -> Result<(), SystemError> {
         self.0.run(resources, args)
     }
 }
@@ -3,28 +3,30 @@ use crate::{
     resource::{ResourceAccess, Resources},
     system::{
         data::{SystemData, SystemDataState},
+        error::{IntoSystemResult, SystemError},
         ExclusiveSystem, System,
     },
 };
 
 #[doc(hidden)]
-pub struct SystemFnImpl<Args, P: SystemData, F> {
+pub struct SystemFnImpl<Args, P: SystemData, F, Out = ()> {
     func: F,
     is_send: bool,
     state: Option<P::State>,
-    _phantom: std::marker::PhantomData<fn(Args)>,
+    _phantom: std::marker::PhantomData<fn(Args) -> Out>,
 }
 
 #[doc(hidden)]
-pub struct ExclusiveSystemFnImpl<Args, F> {
+pub struct ExclusiveSystemFnImpl<Args, F, Out = ()> {
     func: F,
-    _phantom: std::marker::PhantomData<fn(Args)>,
+    _phantom: std::marker::PhantomData<fn(Args) -> Out>,
 }
 
-impl<Args, P, F> SystemFnImpl<Args, P, F>
+impl<Args, P, F, Out> SystemFnImpl<Args, P, F, Out>
 where
     P: SystemData,
-    F: SystemFn<Args, P, ()>,
+    F: SystemFn<Args, P, Out>,
+    Out: IntoSystemResult,
 {
     #[inline]
     pub fn new(func: F) -> Self {
@@ -37,9 +39,10 @@ where
     }
 }
 
-impl<Args, F> ExclusiveSystemFnImpl<Args, F>
+impl<Args, F, Out> ExclusiveSystemFnImpl<Args, F, Out>
 where
-    F: ExclusiveSystemFn<Args, ()>,
+    F: ExclusiveSystemFn<Args, Out>,
+    Out: IntoSystemResult,
 {
     #[inline]
     pub fn new(func: F) -> Self {
@@ -50,23 +53,25 @@ where
     }
 }
 
-impl<Args, P, F> IntoSystem<Args, P> for F
+impl<Args, P, F, Out> IntoSystem<Args, (P, Out)> for F
 where
     P: SystemData,
-    F: SystemFn<Args, P, ()>,
+    F: SystemFn<Args, P, Out>,
+    Out: IntoSystemResult + 'static,
 {
-    type System = SystemFnImpl<Args, P, F>;
+    type System = SystemFnImpl<Args, P, F, Out>;
     #[inline]
     fn into_system(self) -> Self::System {
-        SystemFnImpl::<Args, P, F>::new(self)
+        SystemFnImpl::<Args, P, F, Out>::new(self)
     }
 }
 
 // TODO: analyze safety
-unsafe impl<Args, P, F> System<Args> for SystemFnImpl<Args, P, F>
+unsafe impl<Args, P, F, Out> System<Args> for SystemFnImpl<Args, P, F, Out>
 where
     P: SystemData,
-    F: SystemFn<Args, P, ()>,
+    F: SystemFn<Args, P, Out>,
+    Out: IntoSystemResult + 'static,
 {
     #[inline]
     fn init(&mut self, resources: &mut Resources) {
@@ -77,10 +82,10 @@ where
     }
 
     #[inline]
-    fn run(&mut self, resources: &Resources, args: Args) {
+    fn run(&mut self, resources: &Resources, args: Args) -> Result<(), SystemError> {
         let state = self.state.as_mut().expect("not initialized");
         let mut params = <P::Fetch<'_> as SystemDataFetch<'_>>::fetch(resources, state);
-        SystemFn::call(&mut self.func, args, P::get(&mut params));
+        SystemFn::call(&mut self.func, args, P::get(&mut params)).into_system_result()
     }
 
     fn is_send(&self) -> bool {
@@ -98,25 +103,27 @@ where
     }
 }
 
-impl<Args, F> IntoExclusiveSystem<Args, ()> for F
+impl<Args, F, Out> IntoExclusiveSystem<Args, Out> for F
 where
-    F: ExclusiveSystemFn<Args, ()> + 'static,
+    F: ExclusiveSystemFn<Args, Out> + 'static,
+    Out: IntoSystemResult,
 {
-    type System = ExclusiveSystemFnImpl<Args, F>;
+    type System = ExclusiveSystemFnImpl<Args, F, Out>;
     #[inline]
     fn into_exclusive_system(self) -> Self::System {
-        ExclusiveSystemFnImpl::<Args, F>::new(self)
+        ExclusiveSystemFnImpl::<Args, F, Out>::new(self)
     }
 }
 
-impl<Args, F> ExclusiveSystem<Args> for ExclusiveSystemFnImpl<Args, F>
+impl<Args, F, Out> ExclusiveSystem<Args> for ExclusiveSystemFnImpl<Args, F, Out>
 where
-    F: ExclusiveSystemFn<Args, ()>,
+    F: ExclusiveSystemFn<Args, Out>,
+    Out: IntoSystemResult,
 {
     fn init(&mut self, _resources: &mut Resources) {}
     #[inline]
-    fn run(&mut self, resources: &mut Resources, args: Args) {
-        ExclusiveSystemFn::call(&mut self.func, args, resources)
+    fn run(&mut self, resources: &mut Resources, args: Args) -> Result<(), SystemError> {
+        ExclusiveSystemFn::call(&mut self.func, args, resources).into_system_result()
     }
 
     fn type_name(&self) -> &'static str {
@@ -302,7 +309,7 @@ mod tests {
             let mut sys = IntoSystem::<(&'_ mut usize,), _>::into_system(sys_a);
             sys.init(&mut resources);
             sys.update_access(&resources, &mut access);
-            sys.run(&resources, (&mut a,));
+            sys.run(&resources, (&mut a,)).unwrap();
         }
 
         assert_eq!(11, value.load(std::sync::atomic::Ordering::Acquire));
@@ -0,0 +1,328 @@
+//! Frame time, with time scaling, pausing and a fixed timestep.
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    define_label_enum,
+    label::{CoreSystemPhase, ScheduleLabel},
+    resource::Resources,
+    schedule::{Schedule, Schedules},
+    system::system_fn::ExclusiveResources,
+};
+
+define_label_enum! {
+    /// The labeled schedules of the time module.
+    ///
+    /// `FixedUpdate` is run zero or more times per frame with the fixed
+    /// timestep of [`FixedTime`].
+    pub enum TimeSchedule: ScheduleLabel {
+        FixedUpdate,
+    }
+}
+
+/// The time of the current frame.
+///
+/// [`Time::delta`] is the wall-time since the last frame, clamped to
+/// [`Time::max_delta`] (to avoid a spiral of death after a long stall, e.g.
+/// a debugger break), and scaled by [`Time::relative_speed`]. When paused,
+/// the delta is zero.
+pub struct Time {
+    last_update: Option<Instant>,
+    raw_delta: Duration,
+    delta: Duration,
+    elapsed: Duration,
+    relative_speed: f64,
+    paused: bool,
+    max_delta: Duration,
+}
+
+impl Time {
+    pub const DEFAULT_MAX_DELTA: Duration = Duration::from_millis(250);
+
+    pub fn new() -> Self {
+        Self {
+            last_update: None,
+            raw_delta: Duration::ZERO,
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            relative_speed: 1.0,
+            paused: false,
+            max_delta: Self::DEFAULT_MAX_DELTA,
+        }
+    }
+
+    /// Inserts the `Time` and [`FixedTime`] resources, and adds a system to
+    /// [`CoreSystemPhase::First`], that updates the time and runs the
+    /// [`TimeSchedule::FixedUpdate`] schedule.
+    pub fn install_into(resources: &mut Resources) {
+        if resources.try_init::<Self>().is_ok() {
+            resources.init::<FixedTime>();
+            resources
+                .get_mut::<Schedules>()
+                .unwrap()
+                .get_or_insert(TimeSchedule::FixedUpdate);
+            let mut schedule = resources.borrow_res_mut::<Schedule>().unwrap();
+            schedule
+                .add_system(update_time)
+                .into_phase(CoreSystemPhase::First);
+        }
+    }
+
+    /// Starts a new frame at the current instant.
+    #[inline]
+    pub fn update(&mut self) {
+        self.update_with_instant(Instant::now());
+    }
+
+    /// Starts a new frame at the given instant.
+    pub fn update_with_instant(&mut self, now: Instant) {
+        self.raw_delta = match self.last_update {
+            Some(last_update) => now.saturating_duration_since(last_update),
+            None => Duration::ZERO,
+        };
+        self.last_update = Some(now);
+        self.delta = if self.paused {
+            Duration::ZERO
+        } else {
+            self.raw_delta
+                .min(self.max_delta)
+                .mul_f64(self.relative_speed)
+        };
+        self.elapsed += self.delta;
+    }
+
+    /// The scaled time since the last frame.
+    #[inline]
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// The unscaled and unclamped wall-time since the last frame.
+    #[inline]
+    pub fn raw_delta(&self) -> Duration {
+        self.raw_delta
+    }
+
+    /// The sum of all scaled deltas.
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    #[inline]
+    pub fn relative_speed(&self) -> f64 {
+        self.relative_speed
+    }
+
+    /// Sets the speed of the time relative to the wall-time (e.g. `0.5` for
+    /// slow motion).
+    ///
+    /// # Panics
+    ///
+    /// Panics when `speed` is negative or not finite.
+    pub fn set_relative_speed(&mut self, speed: f64) {
+        assert!(
+            speed.is_finite() && speed >= 0.0,
+            "invalid relative speed {speed}"
+        );
+        self.relative_speed = speed;
+    }
+
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    #[inline]
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    #[inline]
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    #[inline]
+    pub fn max_delta(&self) -> Duration {
+        self.max_delta
+    }
+
+    /// Sets the maximum wall-time of a frame, that is taken into account.
+    #[inline]
+    pub fn set_max_delta(&mut self, max_delta: Duration) {
+        self.max_delta = max_delta;
+    }
+}
+
+impl Default for Time {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The fixed timestep of the [`TimeSchedule::FixedUpdate`] schedule.
+///
+/// The scaled [`Time::delta`] of every frame is accumulated, and the schedule
+/// is run once for every full timestep. So pausing and time scaling apply to
+/// the fixed update, too.
+pub struct FixedTime {
+    timestep: Duration,
+    accumulator: Duration,
+}
+
+impl FixedTime {
+    pub const DEFAULT_TIMESTEP: Duration = Duration::from_micros(15625); // 64 Hz
+
+    #[inline]
+    pub fn new(timestep: Duration) -> Self {
+        assert!(!timestep.is_zero(), "timestep must not be zero");
+        Self {
+            timestep,
+            accumulator: Duration::ZERO,
+        }
+    }
+
+    #[inline]
+    pub fn timestep(&self) -> Duration {
+        self.timestep
+    }
+
+    #[inline]
+    pub fn set_timestep(&mut self, timestep: Duration) {
+        assert!(!timestep.is_zero(), "timestep must not be zero");
+        self.timestep = timestep;
+    }
+
+    /// The accumulated time, that was not yet consumed by a fixed step.
+    #[inline]
+    pub fn accumulated(&self) -> Duration {
+        self.accumulator
+    }
+
+    #[inline]
+    pub fn accumulate(&mut self, delta: Duration) {
+        self.accumulator += delta;
+    }
+
+    /// Consumes one timestep from the accumulated time, and returns `false`
+    /// when there is not enough time left.
+    #[inline]
+    pub fn expend(&mut self) -> bool {
+        if let Some(remaining) = self.accumulator.checked_sub(self.timestep) {
+            self.accumulator = remaining;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Default for FixedTime {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_TIMESTEP)
+    }
+}
+
+fn run_fixed_update(resources: &mut Resources) {
+    let delta = resources.get_mut::<Time>().expect("time").delta();
+    resources
+        .get_mut::<FixedTime>()
+        .expect("fixed time")
+        .accumulate(delta);
+    while resources
+        .get_mut::<FixedTime>()
+        .expect("fixed time")
+        .expend()
+    {
+        resources.run_schedule(TimeSchedule::FixedUpdate);
+    }
+}
+
+fn update_time(mut res: ExclusiveResources<'_>) {
+    res.get_mut::<Time>().expect("time").update();
+    run_fixed_update(&mut res);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_scaling() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut time = Time::new();
+        time.update_with_instant(start);
+        assert_eq!(Duration::ZERO, time.delta());
+
+        time.update_with_instant(start + ms(10));
+        assert_eq!(ms(10), time.delta());
+
+        time.set_relative_speed(0.5);
+        time.update_with_instant(start + ms(30));
+        assert_eq!(ms(20), time.raw_delta());
+        assert_eq!(ms(10), time.delta());
+
+        time.pause();
+        time.update_with_instant(start + ms(40));
+        assert_eq!(Duration::ZERO, time.delta());
+        time.unpause();
+
+        // a long stall is clamped to `max_delta`
+        time.set_relative_speed(1.0);
+        time.set_max_delta(ms(100));
+        time.update_with_instant(start + ms(5040));
+        assert_eq!(ms(100), time.delta());
+        assert_eq!(ms(120), time.elapsed());
+    }
+
+    #[test]
+    fn test_fixed_update() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let steps = Arc::new(AtomicUsize::new(0));
+        let mut resources = Resources::new();
+        Time::install_into(&mut resources);
+        resources.insert(FixedTime::new(Duration::from_millis(10)));
+        {
+            let steps = steps.clone();
+            resources
+                .get_mut::<Schedules>()
+                .unwrap()
+                .get_or_insert(TimeSchedule::FixedUpdate)
+                .add_system(move || {
+                    steps.fetch_add(1, Ordering::Relaxed);
+                });
+        }
+
+        let start = Instant::now();
+        let frame = |resources: &mut Resources, millis: u64| {
+            let time = resources.get_mut::<Time>().unwrap();
+            time.update_with_instant(start + Duration::from_millis(millis));
+            run_fixed_update(resources);
+        };
+        frame(&mut resources, 0);
+        frame(&mut resources, 25);
+        assert_eq!(2, steps.load(Ordering::Relaxed));
+        assert_eq!(
+            Duration::from_millis(5),
+            resources.get_mut::<FixedTime>().unwrap().accumulated()
+        );
+
+        // slow motion: 20ms wall-time are 10ms scaled time
+        resources.get_mut::<Time>().unwrap().set_relative_speed(0.5);
+        frame(&mut resources, 45);
+        assert_eq!(3, steps.load(Ordering::Relaxed));
+
+        // paused: no fixed steps
+        resources.get_mut::<Time>().unwrap().pause();
+        frame(&mut resources, 145);
+        assert_eq!(3, steps.load(Ordering::Relaxed));
+    }
+}
@@ -0,0 +1,327 @@
+//! Detection of long-running or deadlocked systems.
+//!
+//! A [`Watchdog`] is attached to a schedule with
+//! [`Schedule::set_watchdog`](crate::schedule::Schedule::set_watchdog). It
+//! reports systems that exceed a wall-time budget, and (on a separate
+//! thread) schedules that make no progress for a given time, together with
+//! the names of the systems that are currently running.
+//!
+//! By default, the events are logged as warnings. A custom callback can be
+//! provided with [`Watchdog::with_callback`].
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// An event reported by the [`Watchdog`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WatchdogEvent<'a> {
+    /// A system has exceeded the system budget.
+    SlowSystem {
+        system: &'a str,
+        elapsed: Duration,
+        budget: Duration,
+    },
+    /// The schedule has made no progress (no system has finished) for the
+    /// given time.
+    Stalled {
+        running: Vec<&'a str>,
+        elapsed: Duration,
+    },
+}
+
+impl fmt::Display for WatchdogEvent<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SlowSystem {
+                system,
+                elapsed,
+                budget,
+            } => write!(f, "system `{system}` took {elapsed:?} (budget: {budget:?})"),
+            Self::Stalled { running, elapsed } => {
+                write!(f, "schedule made no progress for {elapsed:?}")?;
+                if running.is_empty() {
+                    write!(f, " (no system is running)")
+                } else {
+                    write!(f, ", running systems:")?;
+                    for name in running {
+                        write!(f, " `{name}`")?;
+                    }
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+type Callback = Arc<dyn Fn(&WatchdogEvent<'_>) + Send + Sync>;
+
+/// Configuration of a watchdog for a schedule.
+#[derive(Clone)]
+pub struct Watchdog {
+    system_budget: Option<Duration>,
+    stall_timeout: Option<Duration>,
+    callback: Callback,
+}
+
+impl Default for Watchdog {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Watchdog {
+    /// Creates a watchdog without a budget and without a stall-timeout, that
+    /// logs its events.
+    pub fn new() -> Self {
+        Self {
+            system_budget: None,
+            stall_timeout: None,
+            callback: Arc::new(|event| log::warn!("watchdog: {event}")),
+        }
+    }
+
+    /// Reports systems, that run longer than `budget`.
+    #[inline]
+    #[must_use]
+    pub fn with_system_budget(mut self, budget: Duration) -> Self {
+        self.system_budget = Some(budget);
+        self
+    }
+
+    /// Reports a running schedule, that makes no progress for `timeout`.
+    ///
+    /// This is checked on a separate thread.
+    #[inline]
+    #[must_use]
+    pub fn with_stall_timeout(mut self, timeout: Duration) -> Self {
+        self.stall_timeout = Some(timeout);
+        self
+    }
+
+    /// Calls `callback` for the events of the watchdog (instead of logging
+    /// them).
+    ///
+    /// The callback may be called from any thread.
+    #[inline]
+    #[must_use]
+    pub fn with_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&WatchdogEvent<'_>) + Send + Sync + 'static,
+    {
+        self.callback = Arc::new(callback);
+        self
+    }
+
+    #[inline]
+    pub fn system_budget(&self) -> Option<Duration> {
+        self.system_budget
+    }
+
+    #[inline]
+    pub fn stall_timeout(&self) -> Option<Duration> {
+        self.stall_timeout
+    }
+
+    pub(crate) fn start(self) -> Arc<WatchdogState> {
+        let state = Arc::new(WatchdogState {
+            config: self,
+            names: Mutex::new(Vec::new()),
+            running: Mutex::new(Vec::new()),
+            progress: AtomicU64::new(0),
+            active_runs: AtomicUsize::new(0),
+        });
+        #[cfg(not(target_os = "unknown"))]
+        if let Some(timeout) = state.config.stall_timeout {
+            let weak = Arc::downgrade(&state);
+            std::thread::Builder::new()
+                .name("schedule watchdog".into())
+                .spawn(move || monitor(weak, timeout))
+                .expect("unable to spawn watchdog thread");
+        }
+        state
+    }
+}
+
+pub(crate) struct WatchdogState {
+    config: Watchdog,
+    names: Mutex<Vec<String>>,
+    // systems (index) that are currently running, with their start time
+    running: Mutex<Vec<(usize, Instant)>>,
+    // number of finished systems
+    progress: AtomicU64,
+    active_runs: AtomicUsize,
+}
+
+impl WatchdogState {
+    pub(crate) fn set_names(&self, names: impl Iterator<Item = String>) {
+        let mut current = self.names.lock().unwrap();
+        current.clear();
+        current.extend(names);
+    }
+
+    pub(crate) fn begin_run(self: &Arc<Self>) -> WatchdogRunGuard {
+        self.active_runs.fetch_add(1, Ordering::AcqRel);
+        WatchdogRunGuard(self.clone())
+    }
+
+    pub(crate) fn enter(self: &Arc<Self>, system_index: usize) -> WatchdogSystemGuard {
+        let start = Instant::now();
+        self.running.lock().unwrap().push((system_index, start));
+        WatchdogSystemGuard {
+            state: self.clone(),
+            system_index,
+            start,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn config(&self) -> &Watchdog {
+        &self.config
+    }
+
+    fn system_name(names: &[String], system_index: usize) -> &str {
+        names.get(system_index).map_or("?", String::as_str)
+    }
+}
+
+#[must_use]
+pub(crate) struct WatchdogRunGuard(Arc<WatchdogState>);
+
+impl Drop for WatchdogRunGuard {
+    fn drop(&mut self) {
+        self.0.active_runs.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[must_use]
+pub(crate) struct WatchdogSystemGuard {
+    state: Arc<WatchdogState>,
+    system_index: usize,
+    start: Instant,
+}
+
+impl Drop for WatchdogSystemGuard {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        let state = &self.state;
+        {
+            let mut running = state.running.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(pos) = running
+                .iter()
+                .position(|&(index, start)| index == self.system_index && start == self.start)
+            {
+                running.swap_remove(pos);
+            }
+        }
+        state.progress.fetch_add(1, Ordering::AcqRel);
+        if let Some(budget) = state.config.system_budget {
+            if elapsed > budget {
+                let names = state.names.lock().unwrap_or_else(|e| e.into_inner());
+                (state.config.callback)(&WatchdogEvent::SlowSystem {
+                    system: WatchdogState::system_name(&names, self.system_index),
+                    elapsed,
+                    budget,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "unknown"))]
+fn monitor(state: std::sync::Weak<WatchdogState>, timeout: Duration) {
+    let interval = (timeout / 4).min(Duration::from_millis(100));
+    let mut last_progress = 0;
+    let mut last_change = Instant::now();
+    let mut reported = false;
+    loop {
+        std::thread::sleep(interval);
+        let Some(state) = state.upgrade() else {
+            return; // watchdog was removed
+        };
+        let progress = state.progress.load(Ordering::Acquire);
+        if progress != last_progress || state.active_runs.load(Ordering::Acquire) == 0 {
+            last_progress = progress;
+            last_change = Instant::now();
+            reported = false;
+            continue;
+        }
+        let elapsed = last_change.elapsed();
+        if elapsed >= timeout && !reported {
+            reported = true;
+            let names = state.names.lock().unwrap_or_else(|e| e.into_inner());
+            let running = state.running.lock().unwrap_or_else(|e| e.into_inner());
+            let running = running
+                .iter()
+                .map(|&(index, _)| WatchdogState::system_name(&names, index))
+                .collect();
+            (state.config.callback)(&WatchdogEvent::Stalled { running, elapsed });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{resource::Resources, schedule::Schedule};
+
+    fn fast_system() {}
+
+    fn slow_system() {
+        std::thread::sleep(Duration::from_millis(300));
+    }
+
+    fn recording_watchdog(events: &Arc<Mutex<Vec<String>>>) -> Watchdog {
+        let events = events.clone();
+        Watchdog::new().with_callback(move |event| {
+            let entry = match event {
+                WatchdogEvent::SlowSystem { system, .. } => format!("slow {system}"),
+                WatchdogEvent::Stalled { running, .. } => format!("stalled {running:?}"),
+            };
+            events.lock().unwrap().push(entry);
+        })
+    }
+
+    #[test]
+    fn test_watchdog_system_budget() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut resources = Resources::new();
+        let mut schedule = Schedule::new();
+        schedule.add_system(fast_system);
+        schedule.add_system(slow_system);
+        schedule.set_watchdog(Some(
+            recording_watchdog(&events).with_system_budget(Duration::from_millis(100)),
+        ));
+        schedule.run(&mut resources);
+
+        let events = events.lock().unwrap();
+        assert_eq!(1, events.len());
+        assert!(events[0].starts_with("slow "));
+        assert!(events[0].ends_with("slow_system"));
+    }
+
+    #[test]
+    fn test_watchdog_stall_timeout() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut resources = Resources::new();
+        let mut schedule = Schedule::new();
+        schedule.add_system(slow_system);
+        schedule.set_watchdog(Some(
+            recording_watchdog(&events).with_stall_timeout(Duration::from_millis(50)),
+        ));
+        schedule.run(&mut resources);
+
+        let events = events.lock().unwrap();
+        assert_eq!(1, events.len());
+        assert!(events[0].starts_with("stalled "));
+        assert!(events[0].contains("slow_system"));
+    }
+}
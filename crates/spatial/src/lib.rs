@@ -0,0 +1,561 @@
+#![warn(
+    // missing_docs,
+    // rustdoc::missing_doc_code_examples,
+    future_incompatible,
+    rust_2018_idioms,
+    unused,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_qualifications,
+    unused_crate_dependencies,
+    clippy::cargo,
+    clippy::multiple_crate_versions,
+    clippy::empty_line_after_outer_attr,
+    clippy::fallible_impl_from,
+    clippy::redundant_pub_crate,
+    clippy::use_self,
+    clippy::suspicious_operation_groupings,
+    clippy::useless_let_if_seq,
+    // clippy::missing_errors_doc,
+    // clippy::missing_panics_doc,
+    clippy::wildcard_imports
+)]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/HellButcher/pulz/master/docs/logo.png")]
+#![doc(html_no_source)]
+#![doc = include_str!("../README.md")]
+
+use std::collections::HashMap;
+
+use pulz_ecs::{
+    define_label_enum,
+    label::{CoreSystemPhase, SystemPhase},
+    query::Query,
+    removed::RemovedComponents,
+    resource::Resources,
+    schedule::Schedule,
+    Component, Entity, WorldExt,
+};
+
+/// World-space, axis-aligned bounds of an entity.
+///
+/// This is the input of the [`SpatialGrid`]: keep it in sync with the
+/// transform of the entity. Removals are tracked, so the grid doesn't need
+/// to search for removed entities.
+#[derive(Copy, Clone, Debug, PartialEq, Component)]
+#[component(tracked)]
+pub struct Aabb {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl Aabb {
+    #[inline]
+    pub const fn new(min: [f32; 3], max: [f32; 3]) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center_half_extents(center: [f32; 3], half_extents: [f32; 3]) -> Self {
+        Self {
+            min: std::array::from_fn(|i| center[i] - half_extents[i]),
+            max: std::array::from_fn(|i| center[i] + half_extents[i]),
+        }
+    }
+
+    #[inline]
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..3).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+
+    /// Returns `true`, when the sphere overlaps these bounds.
+    pub fn intersects_sphere(&self, center: [f32; 3], radius: f32) -> bool {
+        let distance_squared: f32 = (0..3)
+            .map(|i| {
+                let d = center[i] - center[i].clamp(self.min[i], self.max[i]);
+                d * d
+            })
+            .sum();
+        distance_squared <= radius * radius
+    }
+
+    /// Returns the distance (in multiples of `direction`) at which the ray
+    /// enters these bounds, or `None` when it misses them within
+    /// `max_distance`.
+    ///
+    /// The distance is `0.0`, when the origin is inside the bounds.
+    pub fn ray_distance(
+        &self,
+        origin: [f32; 3],
+        direction: [f32; 3],
+        max_distance: f32,
+    ) -> Option<f32> {
+        let mut t_min = 0.0_f32;
+        let mut t_max = max_distance;
+        for i in 0..3 {
+            if direction[i] == 0.0 {
+                if origin[i] < self.min[i] || origin[i] > self.max[i] {
+                    return None;
+                }
+            } else {
+                let inv = 1.0 / direction[i];
+                let t0 = (self.min[i] - origin[i]) * inv;
+                let t1 = (self.max[i] - origin[i]) * inv;
+                t_min = t_min.max(t0.min(t1));
+                t_max = t_max.min(t0.max(t1));
+                if t_min > t_max {
+                    return None;
+                }
+            }
+        }
+        Some(t_min)
+    }
+}
+
+define_label_enum! {
+    /// Phase in which the [`SpatialGrid`] is updated.
+    ///
+    /// This phase runs after [`CoreSystemPhase::First`] (where removals of
+    /// [`Aabb`] are tracked) and before [`CoreSystemPhase::Update`].
+    pub enum SpatialPhase: SystemPhase {
+        Update,
+    }
+}
+
+type Cell = [i32; 3];
+
+// entries spanning more cells are kept in the overflow-list
+const MAX_CELLS_PER_ENTRY: u64 = 64;
+
+struct Entry {
+    bounds: Aabb,
+    min_cell: Cell,
+    max_cell: Cell,
+}
+
+/// A uniform grid of the entities with an [`Aabb`] component (of the main
+/// world).
+///
+/// The grid is updated by a system in [`SpatialPhase::Update`] (see
+/// [`SpatialGrid::install_into`]), so it contains the bounds of the end of
+/// the previous frame. Removed entities are taken from
+/// [`RemovedComponents<Aabb>`](RemovedComponents). There is no change
+/// detection for components, so the update still compares the bounds of
+/// every entity with an `Aabb` (O(n) per frame), but only the entities,
+/// whose cells have changed, are moved.
+///
+/// Every entity is stored in all the cells its bounds overlap, so the cell
+/// size should be in the order of the typical entity size. Entities with
+/// large (or infinite) bounds are kept in a separate list, that is checked by
+/// every query.
+///
+/// The queries return every entity only once, ordered by id (or by distance
+/// for [`SpatialGrid::raycast`]).
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<Cell, Vec<Entity>>,
+    overflow: Vec<Entity>,
+    entries: HashMap<Entity, Entry>,
+    // the update system was added to the schedule
+    installed: bool,
+}
+
+impl SpatialGrid {
+    /// Creates an empty grid with cubic cells of the given size.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `cell_size` is not positive and finite.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(
+            cell_size.is_finite() && cell_size > 0.0,
+            "invalid cell size {cell_size}"
+        );
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            overflow: Vec::new(),
+            entries: HashMap::new(),
+            installed: false,
+        }
+    }
+
+    /// Inserts a `SpatialGrid` resource, and adds the system, that updates
+    /// the grid from the [`Aabb`] components, to the schedule.
+    ///
+    /// When the schedule is not available (it is removed from the resources
+    /// while it is running), only the resource is inserted: call this again
+    /// later to add the system.
+    pub fn install_into(resources: &mut Resources, cell_size: f32) {
+        if resources.id::<Self>().is_none() {
+            resources.insert(Self::new(cell_size));
+        }
+        let id = resources.expect_id::<Self>();
+        if resources.get_mut_id(id).unwrap().installed || resources.get_mut::<Schedule>().is_none()
+        {
+            return;
+        }
+        // registering the component needs the schedule as well
+        resources.world_mut().init::<Aabb>();
+        let schedule = resources.get_mut::<Schedule>().unwrap();
+        schedule.add_phase_chain([
+            CoreSystemPhase::First.as_label(),
+            SpatialPhase::Update.as_label(),
+            CoreSystemPhase::Update.as_label(),
+        ]);
+        schedule
+            .add_system(update_spatial_grid)
+            .into_phase(SpatialPhase::Update);
+        resources.get_mut_id(id).unwrap().installed = true;
+    }
+
+    #[inline]
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// The number of entities in the grid.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The bounds of the entity, as known by the grid.
+    #[inline]
+    pub fn bounds(&self, entity: Entity) -> Option<Aabb> {
+        self.entries.get(&entity).map(|e| e.bounds)
+    }
+
+    // infinite coordinates saturate to `i32::MIN`/`i32::MAX`; the caller has
+    // to reject NaN.
+    fn cell_of(&self, point: [f32; 3]) -> Cell {
+        point.map(|v| (v / self.cell_size).floor() as i32)
+    }
+
+    fn num_cells(min_cell: Cell, max_cell: Cell) -> u64 {
+        (0..3)
+            .map(|i| (max_cell[i] as i64 - min_cell[i] as i64 + 1).max(0) as u64)
+            .fold(1, u64::saturating_mul)
+    }
+
+    fn cells_in(min_cell: Cell, max_cell: Cell) -> impl Iterator<Item = Cell> {
+        (min_cell[0]..=max_cell[0]).flat_map(move |x| {
+            (min_cell[1]..=max_cell[1])
+                .flat_map(move |y| (min_cell[2]..=max_cell[2]).map(move |z| [x, y, z]))
+        })
+    }
+
+    fn add_to_cells(&mut self, entity: Entity, min_cell: Cell, max_cell: Cell) {
+        if Self::num_cells(min_cell, max_cell) > MAX_CELLS_PER_ENTRY {
+            self.overflow.push(entity);
+            return;
+        }
+        for cell in Self::cells_in(min_cell, max_cell) {
+            self.cells.entry(cell).or_default().push(entity);
+        }
+    }
+
+    fn remove_from_cells(&mut self, entity: Entity, min_cell: Cell, max_cell: Cell) {
+        if Self::num_cells(min_cell, max_cell) > MAX_CELLS_PER_ENTRY {
+            self.overflow.retain(|e| *e != entity);
+            return;
+        }
+        for cell in Self::cells_in(min_cell, max_cell) {
+            if let Some(entities) = self.cells.get_mut(&cell) {
+                entities.retain(|e| *e != entity);
+                if entities.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    /// Inserts the entity, or updates its bounds.
+    ///
+    /// Bounds containing NaN are rejected: the entity is removed from the
+    /// grid, and `false` is returned.
+    pub fn insert(&mut self, entity: Entity, bounds: Aabb) -> bool {
+        if bounds.min.iter().chain(&bounds.max).any(|v| v.is_nan()) {
+            self.remove(entity);
+            return false;
+        }
+        let min_cell = self.cell_of(bounds.min);
+        let max_cell = self.cell_of(bounds.max);
+        if let Some(entry) = self.entries.get_mut(&entity) {
+            entry.bounds = bounds;
+            if entry.min_cell == min_cell && entry.max_cell == max_cell {
+                return true;
+            }
+            let (old_min, old_max) = (entry.min_cell, entry.max_cell);
+            entry.min_cell = min_cell;
+            entry.max_cell = max_cell;
+            self.remove_from_cells(entity, old_min, old_max);
+        } else {
+            self.entries.insert(
+                entity,
+                Entry {
+                    bounds,
+                    min_cell,
+                    max_cell,
+                },
+            );
+        }
+        self.add_to_cells(entity, min_cell, max_cell);
+        true
+    }
+
+    /// Removes the entity from the grid.
+    pub fn remove(&mut self, entity: Entity) -> bool {
+        if let Some(entry) = self.entries.remove(&entity) {
+            self.remove_from_cells(entity, entry.min_cell, entry.max_cell);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Updates the grid from the [`Aabb`] components of the main world.
+    ///
+    /// `removed` are the entities, that were despawned or lost their `Aabb`
+    /// since the last update (see [`RemovedComponents`]). Entities, whose
+    /// `Aabb` contains NaN, are removed as well.
+    pub fn update(&mut self, query: &mut Query<'_, (Entity, &Aabb)>, removed: &[Entity]) {
+        // removals first: the `Aabb` may have been inserted again
+        for &entity in removed {
+            self.remove(entity);
+        }
+        for (entity, bounds) in query.iter() {
+            self.insert(entity, *bounds);
+        }
+    }
+
+    // Collects the entities of all cells overlapping the region, that match
+    // the filter.
+    fn collect(&self, region: &Aabb, mut filter: impl FnMut(&Aabb) -> bool) -> Vec<Entity> {
+        let mut result: Vec<Entity> = Vec::new();
+        if region.min.iter().chain(&region.max).any(|v| v.is_nan()) {
+            return result;
+        }
+        let min_cell = self.cell_of(region.min);
+        let max_cell = self.cell_of(region.max);
+        let num_cells = Self::num_cells(min_cell, max_cell);
+        let mut visit = |entities: &Vec<Entity>| {
+            for &entity in entities {
+                if filter(&self.entries[&entity].bounds) {
+                    result.push(entity);
+                }
+            }
+        };
+        if num_cells > self.cells.len() as u64 {
+            // large region: only visit the occupied cells
+            for (cell, entities) in &self.cells {
+                if (0..3).all(|i| min_cell[i] <= cell[i] && cell[i] <= max_cell[i]) {
+                    visit(entities);
+                }
+            }
+        } else {
+            for cell in Self::cells_in(min_cell, max_cell) {
+                if let Some(entities) = self.cells.get(&cell) {
+                    visit(entities);
+                }
+            }
+        }
+        visit(&self.overflow);
+        result.sort_unstable();
+        result.dedup();
+        result
+    }
+
+    /// Returns the entities, whose bounds overlap the given bounds.
+    pub fn query_aabb(&self, bounds: &Aabb) -> Vec<Entity> {
+        self.collect(bounds, |b| b.intersects(bounds))
+    }
+
+    /// Returns the entities, whose bounds overlap the given sphere.
+    pub fn query_sphere(&self, center: [f32; 3], radius: f32) -> Vec<Entity> {
+        let region = Aabb::from_center_half_extents(center, [radius; 3]);
+        self.collect(&region, |b| b.intersects_sphere(center, radius))
+    }
+
+    /// Returns the entities hit by the ray within `max_distance` (in
+    /// multiples of `direction`), together with the distance, ordered by
+    /// distance.
+    pub fn raycast(
+        &self,
+        origin: [f32; 3],
+        direction: [f32; 3],
+        max_distance: f32,
+    ) -> Vec<(Entity, f32)> {
+        let end: [f32; 3] = std::array::from_fn(|i| origin[i] + direction[i] * max_distance);
+        let region = Aabb::new(
+            std::array::from_fn(|i| origin[i].min(end[i])),
+            std::array::from_fn(|i| origin[i].max(end[i])),
+        );
+        let mut hits: Vec<(Entity, f32)> = self
+            .collect(&region, |_| true)
+            .into_iter()
+            .filter_map(|entity| {
+                let bounds = &self.entries[&entity].bounds;
+                let distance = bounds.ray_distance(origin, direction, max_distance)?;
+                Some((entity, distance))
+            })
+            .collect();
+        hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+        hits
+    }
+}
+
+fn update_spatial_grid(
+    grid: &mut SpatialGrid,
+    mut query: Query<'_, (Entity, &Aabb)>,
+    removed: RemovedComponents<'_, Aabb>,
+) {
+    grid.update(&mut query, &removed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_queries() {
+        let mut resources = Resources::new();
+        let mut world = resources.world_mut();
+        let a = world.spawn().id();
+        let b = world.spawn().id();
+        let c = world.spawn().id();
+        drop(world);
+
+        let mut grid = SpatialGrid::new(1.0);
+        grid.insert(a, Aabb::new([0.0; 3], [0.5; 3]));
+        grid.insert(b, Aabb::new([2.0, 0.0, 0.0], [4.5, 0.5, 0.5]));
+        grid.insert(c, Aabb::new([-10.0; 3], [-9.0; 3]));
+        assert_eq!(3, grid.len());
+
+        assert_eq!(vec![a], grid.query_aabb(&Aabb::new([0.25; 3], [1.0; 3])));
+        let mut expected = vec![a, b];
+        expected.sort_unstable();
+        assert_eq!(
+            expected,
+            grid.query_aabb(&Aabb::new([0.0; 3], [3.0, 1.0, 1.0]))
+        );
+        assert_eq!(vec![c], grid.query_sphere([-8.0, -9.5, -9.5], 1.5));
+        assert!(grid.query_sphere([-8.0, -9.5, -9.5], 0.5).is_empty());
+
+        // a large region visits the occupied cells only
+        assert_eq!(3, grid.query_aabb(&Aabb::new([-1e6; 3], [1e6; 3])).len());
+
+        let hits = grid.raycast([-1.0, 0.25, 0.25], [1.0, 0.0, 0.0], 10.0);
+        assert_eq!(vec![(a, 1.0), (b, 3.0)], hits);
+        assert_eq!(
+            vec![(a, 1.0)],
+            grid.raycast([-1.0, 0.25, 0.25], [1.0, 0.0, 0.0], 2.0)
+        );
+
+        // moving `b` into the cell of `a`
+        grid.insert(b, Aabb::new([0.25; 3], [0.75; 3]));
+        assert!(grid
+            .query_aabb(&Aabb::new([3.0, 0.0, 0.0], [4.0, 0.5, 0.5]))
+            .is_empty());
+        assert_eq!(expected, grid.query_sphere([0.5; 3], 0.1));
+
+        assert!(grid.remove(a));
+        assert!(!grid.remove(a));
+        assert_eq!(vec![b], grid.query_sphere([0.5; 3], 0.1));
+    }
+
+    #[test]
+    fn test_grid_large_and_invalid_bounds() {
+        let mut resources = Resources::new();
+        let mut world = resources.world_mut();
+        let a = world.spawn().id();
+        let sky = world.spawn().id();
+        let terrain = world.spawn().id();
+        drop(world);
+
+        let mut grid = SpatialGrid::new(1.0);
+        grid.insert(a, Aabb::new([0.0; 3], [0.5; 3]));
+        assert!(grid.insert(sky, Aabb::new([f32::NEG_INFINITY; 3], [f32::INFINITY; 3])));
+        assert!(grid.insert(terrain, Aabb::new([-1e9, -1.0, -1e9], [1e9, 0.0, 1e9])));
+        assert_eq!(3, grid.len());
+        assert_eq!(1, grid.cells.len());
+
+        let mut expected = vec![a, sky, terrain];
+        expected.sort_unstable();
+        assert_eq!(expected, grid.query_sphere([0.25, 0.0, 0.25], 0.1));
+        let mut expected = vec![sky, terrain];
+        expected.sort_unstable();
+        assert_eq!(expected, grid.query_sphere([500.0, -0.5, 500.0], 0.1));
+        assert_eq!(vec![sky], grid.query_sphere([500.0; 3], 0.1));
+
+        // moving out of the overflow-list
+        grid.insert(terrain, Aabb::new([10.0; 3], [11.0; 3]));
+        assert_eq!(vec![sky], grid.query_sphere([500.0, -0.5, 500.0], 0.1));
+        assert_eq!(expected, grid.query_sphere([10.5; 3], 0.1));
+
+        assert!(!grid.insert(a, Aabb::new([f32::NAN; 3], [0.5; 3])));
+        assert_eq!(None, grid.bounds(a));
+        assert!(grid
+            .query_aabb(&Aabb::new([f32::NAN; 3], [1.0; 3]))
+            .is_empty());
+
+        assert!(grid.remove(sky));
+        assert!(grid.overflow.is_empty());
+    }
+
+    #[test]
+    fn test_install_without_schedule() {
+        let mut resources = Resources::new();
+        let schedule = resources.remove::<Schedule>().unwrap();
+        SpatialGrid::install_into(&mut resources, 1.0);
+        assert!(resources.id::<SpatialGrid>().is_some());
+        resources.insert_again(schedule);
+
+        // the system is added, when the schedule is available again
+        SpatialGrid::install_into(&mut resources, 1.0);
+        let a = resources
+            .world_mut()
+            .spawn()
+            .insert(Aabb::new([0.0; 3], [1.0; 3]))
+            .id();
+        let mut schedule = resources.remove::<Schedule>().unwrap();
+        schedule.run(&mut resources);
+        let grid = resources.get_mut::<SpatialGrid>().unwrap();
+        assert_eq!(vec![a], grid.query_sphere([0.5; 3], 0.1));
+    }
+
+    #[test]
+    fn test_grid_update_system() {
+        let mut resources = Resources::new();
+        SpatialGrid::install_into(&mut resources, 2.0);
+        let mut world = resources.world_mut();
+        let a = world.spawn().insert(Aabb::new([0.0; 3], [1.0; 3])).id();
+        let b = world.spawn().insert(Aabb::new([10.0; 3], [11.0; 3])).id();
+        let c = world.spawn().insert(Aabb::new([0.0; 3], [0.5; 3])).id();
+        drop(world);
+
+        let mut schedule = resources.remove::<Schedule>().unwrap();
+        schedule.run(&mut resources);
+        let near_origin = Aabb::new([-1.0; 3], [1.0; 3]);
+        let grid = resources.get_mut::<SpatialGrid>().unwrap();
+        assert_eq!(3, grid.len());
+        let mut expected = vec![a, c];
+        expected.sort_unstable();
+        assert_eq!(expected, grid.query_aabb(&near_origin));
+
+        let mut world = resources.world_mut();
+        *world.entity_mut(b).unwrap().borrow_mut::<Aabb>().unwrap() =
+            Aabb::new([0.5; 3], [0.75; 3]);
+        world.despawn(a);
+        world.entity_mut(c).unwrap().remove::<Aabb>();
+        drop(world);
+        schedule.run(&mut resources);
+        let grid = resources.get_mut::<SpatialGrid>().unwrap();
+        assert_eq!(1, grid.len());
+        assert_eq!(vec![b], grid.query_aabb(&near_origin));
+    }
+}